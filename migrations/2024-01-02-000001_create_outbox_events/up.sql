@@ -0,0 +1,9 @@
+-- Events recorded in the same database as the data they describe, to be
+-- published to an EventSink once committed (transactional outbox)
+CREATE TABLE outbox_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    published_at TEXT
+);
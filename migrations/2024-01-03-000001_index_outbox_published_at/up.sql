@@ -0,0 +1,5 @@
+-- Pending events are `published_at IS NULL`; the relay and the backlog gauge
+-- (OutboxWriter::backlog_count) look them up through this index instead of
+-- scanning the table. The rowid is part of every index entry, so pending
+-- events also come back in id order without a sort.
+CREATE INDEX idx_outbox_events_published_at ON outbox_events(published_at);
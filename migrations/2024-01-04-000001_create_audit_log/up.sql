@@ -0,0 +1,15 @@
+-- Who changed what, written by AuditLog::record in the same transaction as
+-- the change itself. `before`/`after` hold only the fields that changed
+-- (JSON); `before` is NULL for creations and `after` for deletions.
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    actor_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    before TEXT,
+    after TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- History of one entity, newest first
+CREATE INDEX idx_audit_log_entity ON audit_log(entity, created_at);
@@ -0,0 +1,2 @@
+DROP INDEX idx_outbox_events_dedup_key;
+ALTER TABLE outbox_events DROP COLUMN dedup_key;
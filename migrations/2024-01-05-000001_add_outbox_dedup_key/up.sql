@@ -0,0 +1,7 @@
+-- Stable key delivered with every event, so consumers can drop the
+-- duplicates at-least-once delivery produces (see ProcessedEvents).
+-- OutboxWriter::enqueue sets a UUID; rows recorded before this migration
+-- get a random hex key.
+ALTER TABLE outbox_events ADD COLUMN dedup_key TEXT NOT NULL DEFAULT '';
+UPDATE outbox_events SET dedup_key = lower(hex(randomblob(16)));
+CREATE UNIQUE INDEX idx_outbox_events_dedup_key ON outbox_events(dedup_key);
@@ -0,0 +1,7 @@
+-- Consumer side of outbox delivery: dedup keys of events already handled,
+-- written by ProcessedEvents::mark_processed in the same transaction as the
+-- handling itself.
+CREATE TABLE processed_events (
+    dedup_key TEXT PRIMARY KEY NOT NULL,
+    processed_at TEXT NOT NULL
+);
@@ -1,51 +1,657 @@
-use crate::models::{AppEnv, Environment};
-use std::env::var;
+use crate::constants::CONFIG_CONSTANT;
+use crate::models::{
+  AppEnv, Environment, SameSite, SecurityHeadersConfig, SessionConfig, TrailingSlash,
+};
+use crate::utils::Secret;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env::VarError;
+use std::sync::OnceLock;
 use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{EnvFilter, Registry, reload};
 
+/// Minimum estimated entropy, in bits, accepted for `SECRET`.
+///
+/// Estimated as `length × Shannon entropy per character`, which under-counts
+/// truly random input on short strings: 32 random hex characters score about
+/// 115 bits, 24 random base64 characters about 100. Generate secrets with e.g.
+/// `openssl rand -base64 48` to stay well above the threshold.
+pub const MIN_SECRET_ENTROPY_BITS: f64 = 100.0;
+
+/// Placeholder secrets from docs, examples and common defaults (compared case-insensitively).
+const WEAK_SECRETS: [&str; 10] = [
+  "changeme",
+  "change-me",
+  "secret",
+  "password",
+  "default",
+  "test",
+  "jwt-secret",
+  "your-secret-key",
+  "your-secret-key-min-32-chars",
+  "super-secret-axum-starter",
+];
+
+/// Where a setting's value came from, highest precedence first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+  /// The process environment, including anything `run.sh` exported from an
+  /// `.env.*` file.
+  Env,
+  /// `.env` in the working directory.
+  DotEnv,
+  /// The `[env]` table of `config/constant.toml`.
+  ConstantFile,
+  /// Not set anywhere; the default in [`load_environment_with`] applies.
+  Default,
+}
+
+impl std::fmt::Display for ConfigSource {
+  fn fmt(
+    &self,
+    f: &mut std::fmt::Formatter<'_>,
+  ) -> std::fmt::Result {
+    f.write_str(match self {
+      ConfigSource::Env => "env",
+      ConfigSource::DotEnv => ".env",
+      ConfigSource::ConstantFile => "constant.toml",
+      ConfigSource::Default => "default",
+    })
+  }
+}
+
+/// Looks settings up by variable name in one fixed order: process
+/// environment, then `.env`, then the `[env]` table of `constant.toml`, then
+/// the hardcoded default. The first source that has the name wins, even with
+/// an empty value.
+///
+/// ```toml
+/// # config/constant.toml
+/// [env]
+/// PORT = 3000
+/// CORS_ORIGINS = "https://app.example.com"
+/// ```
+///
+/// Every lookup records the source it was answered from (never the value, so
+/// secrets stay out of the log); [`log_sources`](Self::log_sources) writes
+/// them out once logging is up.
+#[derive(Debug, Default)]
+pub struct Resolver {
+  env: HashMap<String, String>,
+  dotenv: HashMap<String, String>,
+  constant: HashMap<String, String>,
+  resolved: RefCell<Vec<(String, ConfigSource)>>,
+}
+
+impl Resolver {
+  pub fn new(
+    env: HashMap<String, String>,
+    dotenv: HashMap<String, String>,
+    constant: HashMap<String, String>,
+  ) -> Self {
+    Self {
+      env,
+      dotenv,
+      constant,
+      resolved: RefCell::default(),
+    }
+  }
+
+  /// Resolver over this process: its environment (variables that are not
+  /// valid UTF-8 are ignored), `./.env` and [`CONFIG_CONSTANT`]. Missing files
+  /// are skipped; malformed ones panic like any other invalid config.
+  pub fn from_process() -> Self {
+    let env = std::env::vars_os()
+      .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+      .collect();
+    let read = |path: &str| match std::fs::read_to_string(path) {
+      Ok(source) => Some(source),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+      Err(e) => panic!("CONFIG_FILE_UNREADABLE: {path}: {e}"),
+    };
+    let dotenv = read(".env")
+      .map(|source| parse_dotenv(&source).unwrap_or_else(|e| panic!("DOTENV_INVALID: {e}")))
+      .unwrap_or_default();
+    let constant = read(CONFIG_CONSTANT)
+      .map(|source| {
+        parse_constant_env(&source).unwrap_or_else(|e| panic!("CONSTANT_ENV_INVALID: {e}"))
+      })
+      .unwrap_or_default();
+    Self::new(env, dotenv, constant)
+  }
+
+  /// Value of `key` from the highest-precedence source that has it, or
+  /// `Err(NotPresent)` when the default should apply.
+  pub fn var(
+    &self,
+    key: &str,
+  ) -> Result<String, VarError> {
+    let found = [
+      (ConfigSource::Env, &self.env),
+      (ConfigSource::DotEnv, &self.dotenv),
+      (ConfigSource::ConstantFile, &self.constant),
+    ]
+    .into_iter()
+    .find_map(|(source, values)| Some((source, values.get(key)?.clone())));
+    let source = found.as_ref().map_or(ConfigSource::Default, |(s, _)| *s);
+    self.resolved.borrow_mut().push((key.to_string(), source));
+    found.map(|(_, value)| value).ok_or(VarError::NotPresent)
+  }
+
+  /// Source the last lookup of `key` was answered from, if it was looked up.
+  pub fn source(
+    &self,
+    key: &str,
+  ) -> Option<ConfigSource> {
+    let resolved = self.resolved.borrow();
+    resolved
+      .iter()
+      .rev()
+      .find(|(k, _)| k == key)
+      .map(|(_, s)| *s)
+  }
+
+  /// Logs `CONFIG_RESOLVED` at debug level for every setting looked up.
+  pub fn log_sources(&self) {
+    for (key, source) in self.resolved.borrow().iter() {
+      tracing::debug!(key = %key, source = %source, "CONFIG_RESOLVED");
+    }
+  }
+}
+
+/// Parses `KEY=value` lines; blank lines, `#` comments and a leading
+/// `export ` are allowed, and one pair of matching quotes around the value is
+/// removed. No interpolation or multi-line values.
+fn parse_dotenv(source: &str) -> Result<HashMap<String, String>, String> {
+  let mut values = HashMap::new();
+  for (n, line) in source.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) = line
+      .split_once('=')
+      .filter(|(key, _)| !key.trim().is_empty())
+      .ok_or_else(|| format!("line {}: expected KEY=value", n + 1))?;
+    let value = value.trim();
+    let unquoted = ['"', '\'']
+      .into_iter()
+      .find_map(|q| value.strip_prefix(q)?.strip_suffix(q))
+      .unwrap_or(value);
+    values.insert(key.trim().to_string(), unquoted.to_string());
+  }
+  Ok(values)
+}
+
+/// Reads the `[env]` table of a `constant.toml` document. Strings are taken
+/// as is; numbers and booleans as written.
+fn parse_constant_env(source: &str) -> Result<HashMap<String, String>, String> {
+  let document: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+  let Some(table) = document.get("env") else {
+    return Ok(HashMap::new());
+  };
+  let table = table.as_table().ok_or("[env] must be a table")?;
+  table
+    .iter()
+    .map(|(key, value)| {
+      let value = match value {
+        toml::Value::String(s) => s.clone(),
+        toml::Value::Integer(n) => n.to_string(),
+        toml::Value::Float(n) => n.to_string(),
+        toml::Value::Boolean(b) => b.to_string(),
+        _ => return Err(format!("env.{key}: expected a string, number or boolean")),
+      };
+      Ok((key.clone(), value))
+    })
+    .collect()
+}
+
+/// Reads the [`Environment`] through [`Resolver::from_process`].
+///
+/// Side-effect free apart from the weak-secret warning, so calling it more
+/// than once (e.g. per test app) is safe. See [`Resolver`] for where each
+/// setting may come from.
 pub fn load_environment() -> Environment {
-  let mode = var("APP_ENV")
+  load_environment_with(&Resolver::from_process())
+}
+
+/// Reads the [`Environment`] from `resolver`; every lookup is recorded, so
+/// [`Resolver::log_sources`] afterwards shows where each setting came from.
+pub fn load_environment_with(resolver: &Resolver) -> Environment {
+  let mode = resolver
+    .var("APP_ENV")
     .unwrap_or_else(|_| "local".to_string())
     .parse::<AppEnv>()
     .expect("APP_ENVIRONMENT_INVALID");
 
-  let secret = var("SECRET").expect("SECRET_REQUIRED");
+  // Staging/production env files leave SECRET to the deploy environment, so an
+  // unset or empty value is reported as missing rather than as weak.
+  let secret = resolver
+    .var("SECRET")
+    .ok()
+    .filter(|secret| !secret.is_empty())
+    .expect("SECRET_REQUIRED: set SECRET in the deploy environment");
+  if let Some(reason) = secret_weakness(&secret) {
+    match mode {
+      AppEnv::Staging | AppEnv::Production => panic!("SECRET_WEAK: {reason}"),
+      // Logging is initialised from the loaded environment, so warn on stderr directly.
+      AppEnv::Local => eprintln!("WARNING SECRET_WEAK: {reason} (refused in staging/production)"),
+    }
+  }
 
-  let port = var("PORT")
+  let port = resolver
+    .var("PORT")
     .unwrap_or_else(|_| "3000".to_string())
     .parse::<u16>()
     .expect("PORT_NUMBER_INVALID");
 
+  let analytics_database_url = resolver
+    .var("ANALYTICS_DATABASE_URL")
+    .ok()
+    .filter(|url| !url.is_empty());
+
+  let bind_uds = resolver.var("BIND_UDS").ok().filter(|p| !p.is_empty());
+
   // Default 300 seconds (5 minutes) — was incorrectly 3000
-  let timeout = var("TIMEOUT")
+  let timeout = resolver
+    .var("TIMEOUT")
     .unwrap_or_else(|_| "300".to_string())
     .parse::<u64>()
     .expect("ENV_TIMEOUT_INVALID");
 
-  let database_url = var("DATABASE_URL").expect("DATABASE_URL_REQUIRED");
+  let max_concurrency = resolver
+    .var("MAX_CONCURRENCY")
+    .unwrap_or_else(|_| "512".to_string())
+    .parse::<usize>()
+    .ok()
+    .filter(|n| *n > 0)
+    .expect("ENV_MAX_CONCURRENCY_INVALID");
+
+  let request_buffer = resolver
+    .var("REQUEST_BUFFER")
+    .unwrap_or_else(|_| "1024".to_string())
+    .parse::<usize>()
+    .ok()
+    .filter(|n| *n > 0)
+    .expect("ENV_REQUEST_BUFFER_INVALID");
+
+  let health_pool_degraded_pct = resolver
+    .var("HEALTH_POOL_DEGRADED_PCT")
+    .unwrap_or_else(|_| "80".to_string())
+    .parse::<u8>()
+    .ok()
+    .filter(|pct| (1..=100).contains(pct))
+    .expect("ENV_HEALTH_POOL_DEGRADED_PCT_INVALID");
+
+  let metrics_interval_secs = resolver
+    .var("METRICS_INTERVAL_SECS")
+    .unwrap_or_else(|_| "15".to_string())
+    .parse::<u64>()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .expect("ENV_METRICS_INTERVAL_SECS_INVALID");
+
+  // Hyper refuses more than 100 header fields on its own, before this applies.
+  let max_header_count = resolver
+    .var("MAX_HEADER_COUNT")
+    .unwrap_or_else(|_| "64".to_string())
+    .parse::<usize>()
+    .ok()
+    .filter(|n| (1..=100).contains(n))
+    .expect("ENV_MAX_HEADER_COUNT_INVALID");
+
+  let max_header_bytes = resolver
+    .var("MAX_HEADER_BYTES")
+    .unwrap_or_else(|_| "16384".to_string())
+    .parse::<usize>()
+    .ok()
+    .filter(|n| *n > 0)
+    .expect("ENV_MAX_HEADER_BYTES_INVALID");
+
+  let max_decompressed_bytes = resolver
+    .var("MAX_DECOMPRESSED_BYTES")
+    .unwrap_or_else(|_| "2097152".to_string())
+    .parse::<usize>()
+    .ok()
+    .filter(|n| *n > 0)
+    .expect("ENV_MAX_DECOMPRESSED_BYTES_INVALID");
+
+  let sla_ms = resolver
+    .var("SLA_MS")
+    .unwrap_or_else(|_| "1000".to_string())
+    .parse::<u64>()
+    .ok()
+    .filter(|ms| *ms > 0)
+    .expect("ENV_SLA_MS_INVALID");
+
+  let trace_sample_rate = match resolver.var("TRACE_SAMPLE_RATE") {
+    Ok(v) => v
+      .parse::<f64>()
+      .ok()
+      .filter(|rate| (0.0..=1.0).contains(rate))
+      .expect("ENV_TRACE_SAMPLE_RATE_INVALID"),
+    Err(_) if matches!(mode, AppEnv::Production) => 0.01,
+    Err(_) => 1.0,
+  };
+
+  let http_connect_timeout_secs = resolver
+    .var("HTTP_CONNECT_TIMEOUT")
+    .unwrap_or_else(|_| "5".to_string())
+    .parse::<u64>()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .expect("ENV_HTTP_CONNECT_TIMEOUT_INVALID");
+
+  let http_request_timeout_secs = resolver
+    .var("HTTP_REQUEST_TIMEOUT")
+    .unwrap_or_else(|_| "10".to_string())
+    .parse::<u64>()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .expect("ENV_HTTP_REQUEST_TIMEOUT_INVALID");
+
+  // Same lookup as curl and reqwest: upper case first, then lower case
+  let https_proxy = resolver
+    .var("HTTPS_PROXY")
+    .or_else(|_| resolver.var("https_proxy"))
+    .ok()
+    .filter(|url| !url.is_empty());
+  if let Some(url) = &https_proxy {
+    // The error never includes the URL, which may carry credentials
+    reqwest::Url::parse(url).expect("ENV_HTTPS_PROXY_INVALID");
+  }
+  let no_proxy = resolver
+    .var("NO_PROXY")
+    .or_else(|_| resolver.var("no_proxy"))
+    .ok()
+    .filter(|list| !list.is_empty());
+
+  let database_url = resolver.var("DATABASE_URL").expect("DATABASE_URL_REQUIRED");
+
+  let trailing_slash = resolver
+    .var("TRAILING_SLASH")
+    .unwrap_or_else(|_| "rewrite".to_string())
+    .parse::<TrailingSlash>()
+    .expect("ENV_TRAILING_SLASH_INVALID");
 
-  let cors_origins = var("CORS_ORIGINS")
+  let security_headers = security_headers(resolver, &mode);
+  let session = session_config(resolver, &mode);
+
+  let cors_origins = resolver
+    .var("CORS_ORIGINS")
     .unwrap_or_else(|_| "http://localhost:5000,http://localhost:8080".to_string())
     .split(',')
     .map(|s| s.trim().to_string())
     .filter(|s| !s.is_empty())
     .collect::<Vec<String>>();
 
-  let log_dir = var("LOG_DIR").unwrap_or_else(|_| "data/logs".to_string());
+  let log_dir = resolver
+    .var("LOG_DIR")
+    .unwrap_or_else(|_| "data/logs".to_string());
+  let log_filter = resolver
+    .var(EnvFilter::DEFAULT_ENV)
+    .ok()
+    .filter(|directives| !directives.is_empty());
+  let backup_dir = resolver
+    .var("BACKUP_DIR")
+    .unwrap_or_else(|_| "data/backups".to_string());
+  let cache_snapshot_path = resolver
+    .var("CACHE_SNAPSHOT_PATH")
+    .ok()
+    .filter(|p| !p.is_empty());
+
+  let cache_memory_limit_mb = resolver
+    .var("CACHE_MEMORY_LIMIT_MB")
+    .ok()
+    .filter(|mb| !mb.is_empty())
+    .map(|mb| {
+      mb.parse::<u64>()
+        .ok()
+        .filter(|mb| *mb > 0)
+        .expect("ENV_CACHE_MEMORY_LIMIT_MB_INVALID")
+    });
+
+  let cache_low_water_entries = resolver
+    .var("CACHE_LOW_WATER_ENTRIES")
+    .unwrap_or_else(|_| "1000".to_string())
+    .parse::<usize>()
+    .expect("ENV_CACHE_LOW_WATER_ENTRIES_INVALID");
+
+  let admin_token = resolver.var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+  if let Some(reason) = admin_token.as_deref().and_then(secret_weakness) {
+    match mode {
+      AppEnv::Staging | AppEnv::Production => panic!("ADMIN_TOKEN_WEAK: {reason}"),
+      AppEnv::Local => {
+        eprintln!("WARNING ADMIN_TOKEN_WEAK: {reason} (refused in staging/production)")
+      }
+    }
+  }
+
+  let api_keys = resolver
+    .var("API_KEYS")
+    .unwrap_or_default()
+    .split(',')
+    .map(|s| s.trim().to_string())
+    .filter(|s| !s.is_empty())
+    .collect::<Vec<String>>();
+  for reason in api_keys.iter().filter_map(|key| secret_weakness(key)) {
+    match mode {
+      AppEnv::Staging | AppEnv::Production => panic!("API_KEY_WEAK: {reason}"),
+      AppEnv::Local => eprintln!("WARNING API_KEY_WEAK: {reason} (refused in staging/production)"),
+    }
+  }
+
+  let webhook_secrets = resolver
+    .var("WEBHOOK_SECRETS")
+    .unwrap_or_default()
+    .split(',')
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .map(|pair| {
+      let (source, secret) = pair
+        .split_once(':')
+        .filter(|(source, secret)| !source.is_empty() && !secret.is_empty())
+        .expect("ENV_WEBHOOK_SECRETS_INVALID: expected source:secret");
+      (source.to_string(), secret.to_string())
+    })
+    .collect::<Vec<(String, String)>>();
+  for reason in webhook_secrets
+    .iter()
+    .filter_map(|(_, secret)| secret_weakness(secret))
+  {
+    match mode {
+      AppEnv::Staging | AppEnv::Production => panic!("WEBHOOK_SECRET_WEAK: {reason}"),
+      AppEnv::Local => {
+        eprintln!("WARNING WEBHOOK_SECRET_WEAK: {reason} (refused in staging/production)")
+      }
+    }
+  }
+
+  let db_adaptive_acquire = resolver
+    .var("DB_ADAPTIVE_ACQUIRE")
+    .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true"));
+
+  let db_pre_ping_secs = resolver.var("DB_PRE_PING_SECS").ok().map(|v| {
+    v.parse::<u64>()
+      .ok()
+      .filter(|secs| *secs > 0)
+      .expect("ENV_DB_PRE_PING_SECS_INVALID")
+  });
+
+  let log_sql = resolver
+    .var("LOG_SQL")
+    .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true"));
+  let log_sql = match mode {
+    AppEnv::Local => log_sql,
+    _ => {
+      if log_sql {
+        eprintln!("WARNING LOG_SQL_IGNORED: only honored in local mode");
+      }
+      false
+    }
+  };
+
+  let api_docs = match resolver.var("API_DOCS") {
+    Ok(v) => match v.to_lowercase().as_str() {
+      "1" | "true" => true,
+      "0" | "false" => false,
+      _ => panic!("ENV_API_DOCS_INVALID"),
+    },
+    Err(_) => !matches!(mode, AppEnv::Production),
+  };
 
   Environment {
     mode,
-    secret,
+    secret: Secret::new(secret),
     port,
-    database_url,
+    bind_uds,
+    database_url: Secret::new(database_url),
+    analytics_database_url: analytics_database_url.map(Secret::new),
     timeout,
+    max_concurrency,
+    request_buffer,
+    health_pool_degraded_pct,
+    metrics_interval_secs,
+    max_header_count,
+    max_header_bytes,
+    max_decompressed_bytes,
+    sla_ms,
+    trace_sample_rate,
+    http_connect_timeout_secs,
+    http_request_timeout_secs,
+    https_proxy: https_proxy.map(Secret::new),
+    no_proxy,
+    trailing_slash,
+    security_headers,
+    session,
     cors_origins,
     log_dir,
+    log_filter,
+    backup_dir,
+    cache_snapshot_path,
+    cache_memory_limit_mb,
+    cache_low_water_entries,
+    admin_token: admin_token.map(Secret::new),
+    api_keys: api_keys.into_iter().map(Secret::new).collect(),
+    webhook_secrets: webhook_secrets
+      .into_iter()
+      .map(|(source, secret)| (source, Secret::new(secret)))
+      .collect(),
+    db_adaptive_acquire,
+    db_pre_ping_secs,
+    log_sql,
+    api_docs,
+  }
+}
+
+/// Returns why `secret` is unsafe for signing JWTs, or `None` if it looks acceptable.
+///
+/// Rejects known placeholder values and anything whose estimated entropy is
+/// below [`MIN_SECRET_ENTROPY_BITS`] (e.g. `"00000000"`, short words).
+pub fn secret_weakness(secret: &str) -> Option<String> {
+  let normalized = secret.trim().to_lowercase();
+  if WEAK_SECRETS.contains(&normalized.as_str()) {
+    return Some("SECRET_IS_PLACEHOLDER".to_string());
+  }
+
+  let bits = estimate_entropy_bits(secret);
+  if bits < MIN_SECRET_ENTROPY_BITS {
+    return Some(format!(
+      "SECRET_LOW_ENTROPY estimated={bits:.0}bits min={MIN_SECRET_ENTROPY_BITS:.0}bits"
+    ));
   }
+
+  None
+}
+
+/// Estimates total entropy as `length × Shannon entropy of the character distribution`.
+fn estimate_entropy_bits(value: &str) -> f64 {
+  let mut counts: HashMap<char, usize> = HashMap::new();
+  for c in value.chars() {
+    *counts.entry(c).or_default() += 1;
+  }
+
+  let len = value.chars().count() as f64;
+  let per_char: f64 = counts
+    .values()
+    .map(|&n| {
+      let p = n as f64 / len;
+      -p * p.log2()
+    })
+    .sum();
+
+  per_char * len
+}
+
+/// [`SecurityHeadersConfig::for_env`], with each header overridden by its
+/// variable: `off` (or empty) drops it, any other value replaces it.
+fn security_headers(
+  resolver: &Resolver,
+  mode: &AppEnv,
+) -> SecurityHeadersConfig {
+  let header = |name: &str, default: Option<String>| match resolver.var(name) {
+    Err(_) => default,
+    Ok(value) if value.trim().is_empty() || value.trim().eq_ignore_ascii_case("off") => None,
+    Ok(value) => {
+      axum::http::HeaderValue::from_str(value.trim())
+        .unwrap_or_else(|_| panic!("ENV_{name}_INVALID"));
+      Some(value.trim().to_string())
+    }
+  };
+  let defaults = SecurityHeadersConfig::for_env(mode);
+  SecurityHeadersConfig {
+    content_type_options: header("X_CONTENT_TYPE_OPTIONS", defaults.content_type_options),
+    frame_options: header("X_FRAME_OPTIONS", defaults.frame_options),
+    referrer_policy: header("REFERRER_POLICY", defaults.referrer_policy),
+    content_security_policy: header("CONTENT_SECURITY_POLICY", defaults.content_security_policy),
+    hsts: header("HSTS", defaults.hsts),
+  }
+}
+
+/// [`SessionConfig::for_env`], with each setting overridden by its variable.
+/// `SameSite=None` without `Secure` is refused, as browsers drop such cookies.
+fn session_config(
+  resolver: &Resolver,
+  mode: &AppEnv,
+) -> SessionConfig {
+  let flag = |name: &str, default: bool| match resolver.var(name) {
+    Err(_) => default,
+    Ok(value) => match value.to_lowercase().as_str() {
+      "1" | "true" => true,
+      "0" | "false" => false,
+      _ => panic!("ENV_{name}_INVALID"),
+    },
+  };
+  let defaults = SessionConfig::for_env(mode);
+  let config = SessionConfig {
+    idle_timeout_secs: resolver
+      .var("SESSION_IDLE_TIMEOUT_SECS")
+      .map_or(Some(defaults.idle_timeout_secs), |v| v.parse::<u64>().ok())
+      .filter(|secs| *secs > 0)
+      .expect("ENV_SESSION_IDLE_TIMEOUT_SECS_INVALID"),
+    http_only: flag("SESSION_COOKIE_HTTP_ONLY", defaults.http_only),
+    secure: flag("SESSION_COOKIE_SECURE", defaults.secure),
+    same_site: resolver
+      .var("SESSION_COOKIE_SAMESITE")
+      .map_or(Ok(defaults.same_site), |v| v.parse::<SameSite>())
+      .expect("ENV_SESSION_COOKIE_SAMESITE_INVALID"),
+  };
+  if config.same_site == SameSite::None && !config.secure {
+    panic!("ENV_SESSION_COOKIE_SAMESITE_INVALID: SameSite=None requires SESSION_COOKIE_SECURE");
+  }
+  config
 }
 
 /// Ensures required runtime directories exist, creating them if necessary.
 pub fn ensure_directories(env: &Environment) {
-  let dirs = [env.log_dir.as_str(), "data", "public", "public/uploads"];
+  let dirs = [
+    env.log_dir.as_str(),
+    env.backup_dir.as_str(),
+    "data",
+    "public",
+    "public/uploads",
+  ];
 
   for dir in dirs {
     if !std::path::Path::new(dir).exists() {
@@ -56,22 +662,54 @@ pub fn ensure_directories(env: &Environment) {
   }
 }
 
-/// Initialise tracing/logging for the given environment.
+/// Initialise tracing/logging for the given environment. Idempotent.
+///
+/// Production logs JSON to stdout and a daily file in `LOG_DIR` through a
+/// non-blocking writer. Its [`WorkerGuard`] is held in a static for the rest
+/// of the process, so it cannot be dropped early (which would stop the file
+/// writer and lose logs); the returned reference is informational.
 ///
-/// Returns an `Option<WorkerGuard>` that **must be kept alive** for the
-/// entire process lifetime (assign it to a variable in `main`). Dropping it
-/// early flushes and stops the non-blocking file writer, causing log loss.
-pub fn init_logging(env: &Environment) -> Option<WorkerGuard> {
+/// Only the first call installs anything; later calls (several test apps in
+/// one process, a library embedding the app) return the guard from the first
+/// call, even if `env` differs. A subscriber installed by someone else first
+/// is left in place instead of panicking with "global default already set".
+pub fn init_logging(env: &Environment) -> Option<&'static WorkerGuard> {
+  init_logging_for(&env.mode, &env.log_dir, env.log_filter.as_deref())
+}
+
+fn init_logging_for(
+  mode: &AppEnv,
+  log_dir: &str,
+  log_filter: Option<&str>,
+) -> Option<&'static WorkerGuard> {
+  static LOG_GUARD: OnceLock<Option<WorkerGuard>> = OnceLock::new();
+  LOG_GUARD
+    .get_or_init(|| install_subscriber(mode, log_dir, log_filter))
+    .as_ref()
+}
+
+fn install_subscriber(
+  mode: &AppEnv,
+  log_dir: &str,
+  log_filter: Option<&str>,
+) -> Option<WorkerGuard> {
   use tracing_subscriber::prelude::*;
 
-  match env.mode {
+  let startup_filter = log_filter
+    .filter(|directives| EnvFilter::try_new(directives).is_ok())
+    .map(str::to_string)
+    .unwrap_or_else(|| match mode {
+      AppEnv::Production => "info".to_string(),
+      _ => "debug".to_string(),
+    });
+  let (filter, handle) = reload::Layer::new(EnvFilter::new(&startup_filter));
+  let registry = tracing_subscriber::registry().with(filter);
+
+  let (installed, guard) = match mode {
     AppEnv::Production => {
-      let file_appender = tracing_appender::rolling::daily(&env.log_dir, "app.log");
+      let file_appender = tracing_appender::rolling::daily(log_dir, "app.log");
       let (non_blocking_file, guard) = tracing_appender::non_blocking(file_appender);
 
-      let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
-
       let file_layer = tracing_subscriber::fmt::layer()
         .json()
         .with_ansi(false)
@@ -82,24 +720,170 @@ pub fn init_logging(env: &Environment) -> Option<WorkerGuard> {
         .with_ansi(false)
         .with_writer(std::io::stdout);
 
-      tracing_subscriber::registry()
-        .with(env_filter)
+      let installed = registry
         .with(file_layer)
         .with(stdout_layer)
-        .init();
-
-      Some(guard)
+        .try_init()
+        .is_ok();
+      (installed, Some(guard))
     }
     _ => {
-      tracing_subscriber::fmt()
-        .pretty()
-        .with_env_filter(
-          tracing_subscriber::EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("debug")),
-        )
-        .init();
-
-      None
+      let installed = registry
+        .with(tracing_subscriber::fmt::layer().pretty())
+        .try_init()
+        .is_ok();
+      (installed, None)
     }
+  };
+
+  if !installed {
+    return None;
+  }
+  let _ = LOG_FILTER.set(LogFilter {
+    handle,
+    startup: startup_filter,
+  });
+  guard
+}
+
+/// Filter of the subscriber installed by [`init_logging`], and the
+/// directives it started with (`Environment::log_filter` or the mode default).
+struct LogFilter {
+  handle: reload::Handle<EnvFilter, Registry>,
+  startup: String,
+}
+
+static LOG_FILTER: OnceLock<LogFilter> = OnceLock::new();
+
+/// Replaces the log filter with `directives` (`RUST_LOG` syntax), or restores
+/// the startup filter for `None`. Follows `RuntimeConfig::log_level`, so the
+/// level can change on `SIGHUP` without a restart.
+///
+/// Fails if `directives` do not parse, or if logging was not initialised by
+/// [`init_logging`].
+pub fn set_log_filter(directives: Option<&str>) -> anyhow::Result<()> {
+  let Some(log_filter) = LOG_FILTER.get() else {
+    anyhow::bail!("LOG_FILTER_NOT_INSTALLED");
+  };
+  let directives = directives.unwrap_or(&log_filter.startup);
+  let filter =
+    EnvFilter::try_new(directives).map_err(|e| anyhow::anyhow!("LOG_FILTER_INVALID: {}", e))?;
+  log_filter
+    .handle
+    .reload(filter)
+    .map_err(|e| anyhow::anyhow!("LOG_FILTER_RELOAD_FAILED: {}", e))?;
+  tracing::info!(filter = directives, "LOG_FILTER_CHANGED");
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rejects_placeholder_secrets() {
+    assert!(secret_weakness("changeme").is_some());
+    assert!(secret_weakness("Super-Secret-Axum-Starter").is_some());
+  }
+
+  #[test]
+  fn rejects_low_entropy_secrets() {
+    assert!(secret_weakness("00000000000000000000000000000000").is_some());
+    assert!(secret_weakness("abcabcabcabcabcabcabcabcabcabcab").is_some());
+  }
+
+  #[test]
+  fn accepts_random_secrets() {
+    assert!(secret_weakness("q8ZtVn3Yw1KpR6xLc2HsJ9dFb4MgT7Ae0UoWiNy5").is_none());
+    assert!(
+      secret_weakness("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08").is_none()
+    );
+  }
+
+  #[test]
+  fn resolver_prefers_env_then_dotenv_then_constant_file() {
+    let map = |pairs: &[(&str, &str)]| {
+      pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>()
+    };
+    let dotenv =
+      parse_dotenv("# local overrides\nexport PORT=4000\nTIMEOUT=\"60\"\nSLA_MS=''\n").unwrap();
+    let constant = parse_constant_env(
+      "[file_types]\nimage = []\n\n[env]\nPORT = 5000\nTIMEOUT = 90\nLOG_DIR = \"/var/log/app\"\nAPI_DOCS = false",
+    )
+    .unwrap();
+    let resolver = Resolver::new(map(&[("PORT", "3000")]), dotenv, constant);
+
+    assert_eq!(resolver.var("PORT").unwrap(), "3000");
+    assert_eq!(resolver.var("TIMEOUT").unwrap(), "60");
+    assert_eq!(resolver.var("SLA_MS").unwrap(), "");
+    assert_eq!(resolver.var("LOG_DIR").unwrap(), "/var/log/app");
+    assert_eq!(resolver.var("API_DOCS").unwrap(), "false");
+    assert_eq!(resolver.var("BACKUP_DIR"), Err(VarError::NotPresent));
+
+    assert_eq!(resolver.source("PORT"), Some(ConfigSource::Env));
+    assert_eq!(resolver.source("TIMEOUT"), Some(ConfigSource::DotEnv));
+    assert_eq!(resolver.source("LOG_DIR"), Some(ConfigSource::ConstantFile));
+    assert_eq!(resolver.source("BACKUP_DIR"), Some(ConfigSource::Default));
+    assert_eq!(resolver.source("NEVER_READ"), None);
+
+    assert!(parse_dotenv("NOT A PAIR").is_err());
+    assert!(parse_constant_env("[env]\nPORTS = [1, 2]").is_err());
+  }
+
+  #[test]
+  fn load_environment_records_a_source_for_every_setting() {
+    let env = HashMap::from([
+      (
+        "SECRET".to_string(),
+        "q8ZtVn3Yw1KpR6xLc2HsJ9dFb4MgT7Ae0UoWiNy5".to_string(),
+      ),
+      ("DATABASE_URL".to_string(), "sqlite://:memory:".to_string()),
+    ]);
+    let dotenv = HashMap::from([("RUST_LOG".to_string(), "axum_starter=trace".to_string())]);
+    let constant = HashMap::from([("PORT".to_string(), "8081".to_string())]);
+    let resolver = Resolver::new(env, dotenv, constant);
+
+    let environment = load_environment_with(&resolver);
+    assert_eq!(environment.port, 8081);
+    assert_eq!(
+      environment.log_filter.as_deref(),
+      Some("axum_starter=trace")
+    );
+    assert_eq!(resolver.source("RUST_LOG"), Some(ConfigSource::DotEnv));
+    assert_eq!(resolver.source("SECRET"), Some(ConfigSource::Env));
+    assert_eq!(resolver.source("PORT"), Some(ConfigSource::ConstantFile));
+    assert_eq!(resolver.source("TIMEOUT"), Some(ConfigSource::Default));
+  }
+
+  #[test]
+  fn shipped_env_files_require_secret_from_the_deploy_environment() {
+    // `run.sh start` / `start:staging` export these files into the process
+    // environment before exec-ing the binary.
+    for source in [
+      include_str!("../.env.production"),
+      include_str!("../.env.staging"),
+    ] {
+      let env = parse_dotenv(source).unwrap();
+      let resolver = Resolver::new(env, HashMap::new(), HashMap::new());
+      let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        load_environment_with(&resolver)
+      }))
+      .unwrap_err();
+      let message = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap();
+      assert!(message.starts_with("SECRET_REQUIRED"), "{message}");
+    }
+  }
+
+  #[test]
+  fn shipped_local_env_file_has_an_acceptable_secret() {
+    let env = parse_dotenv(include_str!("../.env.local")).unwrap();
+    assert_eq!(secret_weakness(&env["SECRET"]), None);
   }
 }
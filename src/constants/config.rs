@@ -4,6 +4,21 @@ pub const CONFIG_CONSTANT: &str = "./config/constant.toml";
 
 // Global Constants
 pub const CACHE_TIMEOUT: u64 = 3600; // 1 hour default cache
+pub const CACHE_PURGE_INTERVAL_SECS: u64 = 60; // how often expired cache entries are freed
+pub const CACHE_MEMORY_CHECK_INTERVAL_SECS: u64 = 15; // how often RSS is compared to CACHE_MEMORY_LIMIT_MB
+pub const CACHE_MEMORY_EVICT_COOLDOWN_SECS: u64 = 300; // RSS lags behind frees, so no second eviction this soon
+pub const OVERLOAD_RETRY_AFTER_SECS: u64 = 1; // Retry-After sent when shedding load
+/// Mode of the socket file created for `BIND_UDS`: owner and group read/write,
+/// so a reverse proxy in the socket's group can connect.
+pub const UDS_PERMISSIONS: u32 = 0o660;
+/// Name prefixes of the runtime's async worker threads and of its blocking
+/// pool threads (mostly `DBSqlite` work); see `utils::runtime`.
+pub const WORKER_THREAD_NAME: &str = "axum-worker";
+pub const BLOCKING_THREAD_NAME: &str = "db-blocking";
+/// Path prefixes never reported by the SLA breach logger.
+pub const SLA_EXCLUDED_PATHS: [&str; 2] = ["/health", "/metrics"];
+/// Columns whose bound values are masked in `LOG_SQL` output (matched as substrings).
+pub const SQL_LOG_REDACTED_COLUMNS: [&str; 3] = ["password", "token", "secret"];
 pub const METHOD_ALLOW: [Method; 4] = [Method::GET, Method::POST, Method::PUT, Method::DELETE];
 pub const HEADER_ALLOW: [HeaderName; 2] = [header::CONTENT_TYPE, header::ACCEPT];
 pub const CORS_WHITELIST: [&str; 2] = ["http://localhost:5000", "http://localhost:8080"];
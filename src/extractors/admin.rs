@@ -0,0 +1,36 @@
+use crate::{models::AppState, services::HttpError, utils::encrypt::constant_time_eq};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::sync::Arc;
+
+/// Header carrying the admin token.
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// Guard for operational endpoints, checked against `ADMIN_TOKEN`.
+///
+/// Declaring `_admin: AdminToken` protects a handler. When no `ADMIN_TOKEN`
+/// is configured the route answers `404`, so admin endpoints are invisible
+/// unless explicitly enabled; a missing or wrong token answers `403`.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminToken;
+
+impl FromRequestParts<Arc<AppState>> for AdminToken {
+  type Rejection = HttpError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &Arc<AppState>,
+  ) -> Result<Self, Self::Rejection> {
+    let expected = state.env.admin_token.as_ref().ok_or(HttpError::ERR404)?;
+    let provided = parts
+      .headers
+      .get(ADMIN_TOKEN_HEADER)
+      .and_then(|v| v.to_str().ok())
+      .ok_or(HttpError::ERR044)?;
+
+    if !constant_time_eq(provided.as_bytes(), expected.expose_secret().as_bytes()) {
+      tracing::warn!("ADMIN_TOKEN_REJECTED");
+      return Err(HttpError::ERR044);
+    }
+    Ok(AdminToken)
+  }
+}
@@ -0,0 +1,107 @@
+use crate::{
+  models::AppState,
+  services::HttpError,
+  utils::{Secret, encrypt::constant_time_eq},
+};
+use axum::{
+  extract::FromRequestParts,
+  http::{HeaderMap, header, request::Parts},
+};
+use std::sync::Arc;
+
+/// Header carrying a static API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Guard for machine-to-machine endpoints authenticated with static keys
+/// instead of JWTs, checked against `API_KEYS`.
+///
+/// The key is read from `X-API-Key`, or else from `Authorization: Bearer
+/// <key>`. It is compared in constant time against every configured key, so
+/// several keys can be valid at once while one is being rotated out. A
+/// missing or unknown key answers `401`; with no `API_KEYS` configured every
+/// request is rejected.
+///
+/// The index of the matching key is kept so handlers can log which key was
+/// used without logging the key itself.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKey {
+  /// Position of the matching key in `API_KEYS`.
+  pub index: usize,
+}
+
+impl FromRequestParts<Arc<AppState>> for ApiKey {
+  type Rejection = HttpError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &Arc<AppState>,
+  ) -> Result<Self, Self::Rejection> {
+    let provided = provided_key(&parts.headers).ok_or(HttpError::ERR021)?;
+
+    match matching_key(provided, &state.env.api_keys) {
+      Some(index) => Ok(ApiKey { index }),
+      None => {
+        tracing::warn!("API_KEY_REJECTED");
+        Err(HttpError::ERR021)
+      }
+    }
+  }
+}
+
+/// `X-API-Key`, else the token of `Authorization: Bearer`.
+fn provided_key(headers: &HeaderMap) -> Option<&str> {
+  headers
+    .get(API_KEY_HEADER)
+    .and_then(|v| v.to_str().ok())
+    .or_else(|| {
+      headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    })
+}
+
+/// Index of the key equal to `provided`. Every key is compared, so the time
+/// taken does not depend on which one matched.
+fn matching_key(
+  provided: &str,
+  keys: &[Secret<String>],
+) -> Option<usize> {
+  keys.iter().enumerate().fold(None, |found, (i, key)| {
+    let matches = constant_time_eq(provided.as_bytes(), key.expose_secret().as_bytes());
+    found.or(matches.then_some(i))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::http::HeaderValue;
+
+  #[test]
+  fn reads_x_api_key_then_bearer() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(provided_key(&headers), None);
+
+    headers.insert(
+      header::AUTHORIZATION,
+      HeaderValue::from_static("Bearer from-bearer"),
+    );
+    assert_eq!(provided_key(&headers), Some("from-bearer"));
+
+    headers.insert(API_KEY_HEADER, HeaderValue::from_static("from-header"));
+    assert_eq!(provided_key(&headers), Some("from-header"));
+  }
+
+  #[test]
+  fn accepts_any_configured_key_for_rotation() {
+    let keys = vec![
+      Secret::new("old-key".to_string()),
+      Secret::new("new-key".to_string()),
+    ];
+    assert_eq!(matching_key("old-key", &keys), Some(0));
+    assert_eq!(matching_key("new-key", &keys), Some(1));
+    assert_eq!(matching_key("new-ke", &keys), None);
+    assert_eq!(matching_key("anything", &[]), None);
+  }
+}
@@ -26,7 +26,7 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
       .and_then(|v| v.strip_prefix("Bearer "))
       .ok_or(HttpError::ERR022)?;
 
-    let (user_id, email) = decode_token(token, state.env.secret.as_bytes())?;
+    let (user_id, email) = decode_token(token, state.env.secret.expose_secret().as_bytes())?;
 
     Ok(AuthUser { user_id, email })
   }
@@ -1,14 +1,24 @@
-use crate::{services::HttpError, utils::validation::format_validation_errors};
+use crate::{
+  middlewares,
+  models::bounded_vec,
+  services::{HttpError, JsonRejectionKind, metrics},
+  utils::validation::format_validation_errors,
+};
 use axum::{
   Json,
   body::Body,
-  extract::{FromRequest, Request},
+  extract::{FromRequest, Request, rejection::JsonRejection},
 };
 use serde::de::DeserializeOwned;
 use std::ops::Deref;
 use validator::Validate;
 
-// Define the extractor struct
+/// JSON body extractor that validates the payload with [`Validate`].
+///
+/// Malformed bodies are rejected with `ERR033` and counted per matched route
+/// and [`JsonRejectionKind`] (see [`metrics::json_rejections`]), which shows
+/// which clients send broken requests to which endpoints. An array over a
+/// [`BoundedVec`](crate::models::BoundedVec) limit is `ERR046` (422) instead.
 pub struct BodyJson<T>(pub T);
 
 // Implement Deref for easy access to the inner value
@@ -30,9 +40,11 @@ where
     req: Request<Body>,
     state: &S,
   ) -> Result<Self, Self::Rejection> {
-    let Json(value) = Json::<T>::from_request(req, state)
-      .await
-      .map_err(|e| HttpError::ERR033(e.to_string()))?;
+    let route = middlewares::matched_route(&req).map(str::to_string);
+    let Json(value) = Json::<T>::from_request(req, state).await.map_err(|e| {
+      metrics::record_json_rejection(route.as_deref(), rejection_kind(&e));
+      json_rejection_error(&e)
+    })?;
 
     value
       .validate()
@@ -42,6 +54,26 @@ where
   }
 }
 
+/// `ERR046` when the bounded array limit tripped, `ERR033` otherwise.
+fn json_rejection_error(rejection: &JsonRejection) -> HttpError {
+  if let JsonRejection::JsonDataError(e) = rejection {
+    let text = e.body_text();
+    if let Some((_, detail)) = text.split_once(bounded_vec::TOO_MANY_ITEMS) {
+      return HttpError::ERR046(detail.to_string());
+    }
+  }
+  HttpError::ERR033(rejection.to_string())
+}
+
+pub(super) fn rejection_kind(rejection: &JsonRejection) -> JsonRejectionKind {
+  match rejection {
+    JsonRejection::MissingJsonContentType(_) => JsonRejectionKind::MissingContentType,
+    JsonRejection::JsonSyntaxError(_) => JsonRejectionKind::Syntax,
+    JsonRejection::JsonDataError(_) => JsonRejectionKind::Data,
+    _ => JsonRejectionKind::Body,
+  }
+}
+
 // --- Unit Tests ---
 #[cfg(test)]
 mod tests {
@@ -100,7 +132,7 @@ mod tests {
       .unwrap();
 
     let response = app.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
   }
 
   #[tokio::test]
@@ -132,4 +164,38 @@ mod tests {
     let response = app.oneshot(request).await.unwrap();
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
   }
+
+  #[tokio::test]
+  async fn rejections_are_counted_by_route_and_kind() {
+    let app = Router::new().route("/counted/{id}", post(test_handler));
+    let count = |kind| {
+      metrics::json_rejections()
+        .into_iter()
+        .find(|c| c.route == "/counted/{id}" && c.kind == kind)
+        .map_or(0, |c| c.count)
+    };
+
+    for (id, content_type, body) in [
+      ("1", "text/plain", r#"{}"#),
+      ("2", "application/json", r#"{"username": "#),
+      ("3", "application/json", r#"{"username": "testuser"}"#),
+    ] {
+      let request = Request::builder()
+        .method("POST")
+        .uri(format!("/counted/{id}"))
+        .header("content-type", content_type)
+        .body(Body::from(body))
+        .unwrap();
+      app.clone().oneshot(request).await.unwrap();
+    }
+
+    assert_eq!(count(JsonRejectionKind::MissingContentType), 1);
+    assert_eq!(count(JsonRejectionKind::Syntax), 1);
+    assert_eq!(count(JsonRejectionKind::Data), 1);
+    assert!(
+      metrics::json_rejections()
+        .iter()
+        .all(|c| !c.route.starts_with("/counted/1"))
+    );
+  }
 }
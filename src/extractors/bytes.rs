@@ -0,0 +1,220 @@
+use crate::services::HttpError;
+use axum::{
+  body::{Body, Bytes, HttpBody},
+  extract::{FromRequest, Request},
+  http::header,
+};
+use std::{future::poll_fn, ops::Deref, pin::Pin};
+
+/// axum's own default body limit.
+const DEFAULT_MAX_SIZE: usize = 2 * 1024 * 1024;
+
+/// Limits applied by [`GuardedBytes`]. Attach it to a route with
+/// `.route_layer(Extension(config))`; routes without one get the defaults
+/// (2 MiB, any content type).
+#[derive(Debug, Clone)]
+pub struct BytesGuardConfig {
+  pub max_size: usize,
+  /// Accepted media types (`application/json`, `image/png`), compared without
+  /// parameters such as `charset`. `None` accepts any, including none.
+  pub allowed_content_types: Option<Vec<String>>,
+}
+
+impl Default for BytesGuardConfig {
+  fn default() -> Self {
+    Self {
+      max_size: DEFAULT_MAX_SIZE,
+      allowed_content_types: None,
+    }
+  }
+}
+
+impl BytesGuardConfig {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn max_size(
+    mut self,
+    size: usize,
+  ) -> Self {
+    self.max_size = size;
+    self
+  }
+
+  pub fn allowed_content_types(
+    mut self,
+    types: Vec<String>,
+  ) -> Self {
+    self.allowed_content_types = Some(types);
+    self
+  }
+
+  /// `ERR415` unless `content_type` is allowed.
+  fn check_content_type(
+    &self,
+    content_type: Option<&str>,
+  ) -> Result<(), HttpError> {
+    let Some(allowed) = &self.allowed_content_types else {
+      return Ok(());
+    };
+    let essence = content_type
+      .and_then(|value| value.split(';').next())
+      .map(str::trim)
+      .unwrap_or_default();
+    if allowed.iter().any(|t| t.eq_ignore_ascii_case(essence)) {
+      Ok(())
+    } else {
+      Err(HttpError::ERR415(allowed.join(", ")))
+    }
+  }
+}
+
+/// Raw request body, read only if it passes the route's
+/// [`BytesGuardConfig`]: a disallowed `Content-Type` is rejected with `ERR415`
+/// before any of the body is read, and a body over `max_size` with `ERR413`.
+/// A declared `Content-Length` over the limit is refused up front; otherwise
+/// reading stops at the first chunk that crosses it, so at most `max_size`
+/// bytes are ever buffered.
+///
+/// ```rust,ignore
+/// .route(
+///   "/webhooks/stripe",
+///   post(receive).route_layer(Extension(
+///     BytesGuardConfig::new()
+///       .max_size(64 * 1024)
+///       .allowed_content_types(vec!["application/json".into()]),
+///   )),
+/// )
+///
+/// async fn receive(GuardedBytes(body): GuardedBytes) -> Result<impl IntoResponse, HttpError>
+/// ```
+pub struct GuardedBytes(pub Bytes);
+
+impl Deref for GuardedBytes {
+  type Target = Bytes;
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<S> FromRequest<S> for GuardedBytes
+where
+  S: Send + Sync,
+{
+  type Rejection = HttpError;
+
+  async fn from_request(
+    req: Request<Body>,
+    _state: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let config = req
+      .extensions()
+      .get::<BytesGuardConfig>()
+      .cloned()
+      .unwrap_or_default();
+
+    let headers = req.headers();
+    config.check_content_type(
+      headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok()),
+    )?;
+    let declared = headers
+      .get(header::CONTENT_LENGTH)
+      .and_then(|v| v.to_str().ok())
+      .and_then(|v| v.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > config.max_size) {
+      return Err(HttpError::ERR413(config.max_size));
+    }
+
+    let mut body = req.into_body();
+    let mut buf = Vec::with_capacity(declared.unwrap_or(0));
+    while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+      let frame = frame.map_err(|e| HttpError::ERR033(e.to_string()))?;
+      if let Ok(data) = frame.into_data() {
+        if buf.len() + data.len() > config.max_size {
+          return Err(HttpError::ERR413(config.max_size));
+        }
+        buf.extend_from_slice(&data);
+      }
+    }
+
+    Ok(GuardedBytes(Bytes::from(buf)))
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  };
+
+  fn config() -> BytesGuardConfig {
+    BytesGuardConfig::new()
+      .max_size(8)
+      .allowed_content_types(vec!["application/json".to_string()])
+  }
+
+  async fn extract(
+    content_type: &str,
+    body: Body,
+  ) -> Result<Bytes, HttpError> {
+    let mut req = Request::builder()
+      .method("POST")
+      .header("content-type", content_type)
+      .body(body)
+      .unwrap();
+    req.extensions_mut().insert(config());
+    GuardedBytes::from_request(req, &()).await.map(|b| b.0)
+  }
+
+  #[tokio::test]
+  async fn accepts_allowed_type_within_limit() {
+    let bytes = extract("application/json; charset=utf-8", Body::from("{}"))
+      .await
+      .unwrap();
+    assert_eq!(&bytes[..], b"{}");
+  }
+
+  #[tokio::test]
+  async fn rejects_wrong_content_type() {
+    let err = extract("text/plain", Body::from("{}")).await.unwrap_err();
+    assert!(matches!(err, HttpError::ERR415(_)));
+    assert_eq!(err.status(), axum::http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+  }
+
+  #[tokio::test]
+  async fn stops_reading_once_over_the_limit() {
+    // Streamed without Content-Length: 5 bytes per chunk, limit 8.
+    let polled = Arc::new(AtomicUsize::new(0));
+    let counter = polled.clone();
+    let chunks = (0..100).map(move |_| {
+      counter.fetch_add(1, Ordering::SeqCst);
+      Ok::<_, std::io::Error>(Bytes::from_static(b"12345"))
+    });
+    let body = Body::from_stream(stream_iter(chunks));
+
+    let err = extract("application/json", body).await.unwrap_err();
+    assert!(matches!(err, HttpError::ERR413(8)));
+    assert_eq!(polled.load(Ordering::SeqCst), 2);
+  }
+
+  /// Minimal `Stream` over an iterator, so the test needs no extra crates.
+  fn stream_iter<I: Iterator + Unpin>(iter: I) -> impl futures_core::Stream<Item = I::Item> {
+    struct Iter<I>(I);
+    impl<I: Iterator + Unpin> futures_core::Stream for Iter<I> {
+      type Item = I::Item;
+      fn poll_next(
+        mut self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+      ) -> std::task::Poll<Option<I::Item>> {
+        std::task::Poll::Ready(self.0.next())
+      }
+    }
+    Iter(iter)
+  }
+}
@@ -0,0 +1,194 @@
+use axum::{
+  extract::FromRequestParts,
+  http::{HeaderValue, Method, StatusCode, header, request::Parts},
+  response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::convert::Infallible;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `If-Modified-Since` of a `GET`/`HEAD` request (RFC 7232 §3.3).
+///
+/// Holds `None` when the header is absent, is not a valid HTTP-date, the
+/// method is not `GET`/`HEAD`, or `If-None-Match` is also sent (which takes
+/// precedence). Never rejects, so it is safe on any route.
+///
+/// ```rust,ignore
+/// async fn get_user(
+///   since: IfModifiedSince,
+///   PathParam(id): PathParam<i32>,
+/// ) -> Result<Response, HttpError> {
+///   let user = ...;
+///   if let Some(not_modified) = since.not_modified_if_unchanged(user.updated_at.into()) {
+///     return Ok(not_modified);
+///   }
+///   Ok(HttpResponse::ok(user, "USER_FOUND").into_response())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IfModifiedSince(pub Option<SystemTime>);
+
+impl IfModifiedSince {
+  /// A `304 Not Modified` carrying `Last-Modified` when the resource has not
+  /// changed since the client's copy; `None` when the full response should
+  /// be sent.
+  ///
+  /// HTTP-dates have whole-second precision, so `last_modified` is truncated
+  /// to the second before comparing; a resource modified at the exact second
+  /// the client holds counts as unchanged.
+  pub fn not_modified_if_unchanged(
+    &self,
+    last_modified: SystemTime,
+  ) -> Option<Response> {
+    let since = self.0?;
+    if unix_secs(last_modified) > unix_secs(since) {
+      return None;
+    }
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    if let Ok(value) = HeaderValue::from_str(&format_http_date(last_modified)) {
+      response.headers_mut().insert(header::LAST_MODIFIED, value);
+    }
+    Some(response)
+  }
+}
+
+impl<S> FromRequestParts<S> for IfModifiedSince
+where
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    _state: &S,
+  ) -> Result<Self, Self::Rejection> {
+    if !matches!(parts.method, Method::GET | Method::HEAD)
+      || parts.headers.contains_key(header::IF_NONE_MATCH)
+    {
+      return Ok(Self(None));
+    }
+    let since = parts
+      .headers
+      .get(header::IF_MODIFIED_SINCE)
+      .and_then(|v| v.to_str().ok())
+      .and_then(parse_http_date);
+    Ok(Self(since))
+  }
+}
+
+/// Parses an RFC 7231 §7.1.1.1 HTTP-date: the preferred IMF-fixdate
+/// (`Sun, 06 Nov 1994 08:49:37 GMT`) and the obsolete RFC 850
+/// (`Sunday, 06-Nov-94 08:49:37 GMT`) and asctime
+/// (`Sun Nov  6 08:49:37 1994`) forms, all of which recipients must accept.
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+  const FORMATS: [&str; 3] = [
+    "%a, %d %b %Y %H:%M:%S GMT",
+    "%A, %d-%b-%y %H:%M:%S GMT",
+    "%a %b %e %H:%M:%S %Y",
+  ];
+  let value = value.trim();
+  FORMATS
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .map(|naive| naive.and_utc().into())
+}
+
+/// Formats `time` as an IMF-fixdate, the form to send in `Last-Modified`.
+pub fn format_http_date(time: SystemTime) -> String {
+  DateTime::<Utc>::from(time)
+    .format("%a, %d %b %Y %H:%M:%S GMT")
+    .to_string()
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0)
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::http::Request;
+  use std::time::Duration;
+
+  /// `Sun, 06 Nov 1994 08:49:37 GMT`
+  const EXAMPLE_SECS: u64 = 784_111_777;
+
+  fn example() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(EXAMPLE_SECS)
+  }
+
+  async fn extract(request: Request<()>) -> IfModifiedSince {
+    let (mut parts, _) = request.into_parts();
+    IfModifiedSince::from_request_parts(&mut parts, &())
+      .await
+      .unwrap()
+  }
+
+  #[test]
+  fn parses_all_three_http_date_forms() {
+    for value in [
+      "Sun, 06 Nov 1994 08:49:37 GMT",
+      "Sunday, 06-Nov-94 08:49:37 GMT",
+      "Sun Nov  6 08:49:37 1994",
+    ] {
+      assert_eq!(parse_http_date(value), Some(example()), "{value}");
+    }
+    assert_eq!(parse_http_date("yesterday"), None);
+    assert_eq!(parse_http_date("1994-11-06T08:49:37Z"), None);
+    assert_eq!(format_http_date(example()), "Sun, 06 Nov 1994 08:49:37 GMT");
+  }
+
+  #[test]
+  fn equal_timestamp_is_not_modified() {
+    let since = IfModifiedSince(Some(example()));
+    let response = since.not_modified_if_unchanged(example()).unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(
+      response.headers()[header::LAST_MODIFIED],
+      "Sun, 06 Nov 1994 08:49:37 GMT"
+    );
+
+    // Sub-second changes within the same second are not visible to HTTP-dates.
+    let within_second = example() + Duration::from_millis(900);
+    assert!(since.not_modified_if_unchanged(within_second).is_some());
+    assert!(
+      since
+        .not_modified_if_unchanged(example() + Duration::from_secs(1))
+        .is_none()
+    );
+    assert!(
+      IfModifiedSince(None)
+        .not_modified_if_unchanged(example())
+        .is_none()
+    );
+  }
+
+  #[tokio::test]
+  async fn ignores_invalid_dates_and_non_get_requests() {
+    let request = |method: Method, value: &str| {
+      Request::builder()
+        .method(method)
+        .header(header::IF_MODIFIED_SINCE, value)
+        .body(())
+        .unwrap()
+    };
+
+    let valid = "Sun, 06 Nov 1994 08:49:37 GMT";
+    assert_eq!(
+      extract(request(Method::GET, valid)).await.0,
+      Some(example())
+    );
+    assert_eq!(extract(request(Method::GET, "not a date")).await.0, None);
+    assert_eq!(extract(request(Method::POST, valid)).await.0, None);
+
+    let mut with_etag = request(Method::GET, valid);
+    with_etag
+      .headers_mut()
+      .insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"v1\""));
+    assert_eq!(extract(with_etag).await.0, None);
+  }
+}
@@ -1,9 +1,23 @@
+pub mod admin;
+pub mod api_key;
 pub mod auth;
 pub mod body;
+pub mod bytes;
+pub mod conditional;
 pub mod formdata;
+pub mod pagination;
+pub mod patch;
 pub mod path;
+pub mod session;
 
+pub use admin::AdminToken;
+pub use api_key::ApiKey;
 pub use auth::AuthUser;
 pub use body::BodyJson;
+pub use bytes::{BytesGuardConfig, GuardedBytes};
+pub use conditional::IfModifiedSince;
 pub use formdata::{FileValidationConfig, MultipartFile, MultipartForm, MultipartFormWithConfig};
+pub use pagination::Pagination;
+pub use patch::Patch;
 pub use path::PathParam;
+pub use session::Session;
@@ -0,0 +1,137 @@
+use crate::{models::PaginationQuery, services::HttpError};
+use axum::{
+  extract::{FromRequestParts, Query},
+  http::request::Parts,
+};
+use serde::Deserialize;
+use std::ops::Deref;
+
+/// Raw `page` / `limit` values, kept as text so a malformed number can be
+/// reported per field instead of as one opaque deserialization error.
+#[derive(Deserialize)]
+struct RawPagination {
+  page: Option<String>,
+  limit: Option<String>,
+}
+
+/// Validated `page` and `limit` query parameters for list endpoints.
+///
+/// Absent (or empty) parameters take the [`PaginationQuery`] defaults.
+/// Present ones must be whole numbers with `page >= 1` and
+/// `1 <= limit <= PaginationQuery::MAX_LIMIT`; anything else is rejected with
+/// `ERR041` naming the parameter, rather than silently clamped. Other query
+/// parameters (filters) are ignored, so this combines with `Query<Filter>`:
+///
+/// ```rust,ignore
+/// async fn list(
+///   Pagination(pagination): Pagination,
+///   Query(filter): Query<UserQuery>,
+/// ) -> Result<impl IntoResponse, HttpError>
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pagination(pub PaginationQuery);
+
+impl Deref for Pagination {
+  type Target = PaginationQuery;
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<S> FromRequestParts<S> for Pagination
+where
+  S: Send + Sync,
+{
+  type Rejection = HttpError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let Query(raw) = Query::<RawPagination>::from_request_parts(parts, state)
+      .await
+      .map_err(|e| HttpError::ERR041(e.body_text()))?;
+    let defaults = PaginationQuery::default();
+    let page = parse_param("page", raw.page, defaults.page, 1..=u32::MAX)?;
+    let limit = parse_param(
+      "limit",
+      raw.limit,
+      defaults.limit,
+      1..=PaginationQuery::MAX_LIMIT,
+    )?;
+    Ok(Pagination(PaginationQuery { page, limit }))
+  }
+}
+
+fn parse_param(
+  name: &str,
+  value: Option<String>,
+  default: u32,
+  range: std::ops::RangeInclusive<u32>,
+) -> Result<u32, HttpError> {
+  let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+    return Ok(default);
+  };
+  let expected = if *range.end() == u32::MAX {
+    format!("{name} must be a whole number >= {}", range.start())
+  } else {
+    format!(
+      "{name} must be a whole number from {} to {}",
+      range.start(),
+      range.end()
+    )
+  };
+  value
+    .trim()
+    .parse::<u32>()
+    .ok()
+    .filter(|n| range.contains(n))
+    .ok_or(HttpError::ERR041(expected))
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::http::Request;
+
+  async fn extract(query: &str) -> Result<PaginationQuery, HttpError> {
+    let (mut parts, _) = Request::builder()
+      .uri(format!("/items?{query}"))
+      .body(())
+      .unwrap()
+      .into_parts();
+    Pagination::from_request_parts(&mut parts, &())
+      .await
+      .map(|Pagination(p)| p)
+  }
+
+  fn rejected_field(result: Result<PaginationQuery, HttpError>) -> String {
+    match result {
+      Err(HttpError::ERR041(detail)) => detail,
+      other => panic!("expected ERR041, got {other:?}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn absent_params_use_defaults() {
+    let p = extract("username=ada").await.unwrap();
+    assert_eq!((p.page, p.limit), (1, 10));
+    let p = extract("page=3&limit=100").await.unwrap();
+    assert_eq!((p.page, p.limit), (3, 100));
+  }
+
+  #[tokio::test]
+  async fn rejects_out_of_range_and_non_numeric_values() {
+    assert!(rejected_field(extract("page=-1").await).starts_with("page "));
+    assert!(rejected_field(extract("page=0").await).starts_with("page "));
+    assert!(rejected_field(extract("limit=0").await).starts_with("limit "));
+    assert!(rejected_field(extract("limit=101").await).starts_with("limit "));
+    assert!(rejected_field(extract("limit=abc").await).starts_with("limit "));
+    assert!(rejected_field(extract("page=1.5").await).starts_with("page "));
+    assert_eq!(
+      HttpError::ERR041(String::new()).status(),
+      axum::http::StatusCode::BAD_REQUEST
+    );
+  }
+}
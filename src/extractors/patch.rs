@@ -0,0 +1,200 @@
+use super::body::rejection_kind;
+use crate::{
+  middlewares,
+  services::{HttpError, metrics},
+  utils::validation::format_validation_errors,
+};
+use axum::{
+  Json,
+  body::Body,
+  extract::{FromRequest, Request},
+};
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
+use validator::Validate;
+
+/// Partial-update body for PATCH endpoints, following JSON Merge Patch
+/// (RFC 7396): keys that are absent leave the column unchanged, `null`
+/// clears it, and a value sets it.
+///
+/// Accepts `application/merge-patch+json` as well as `application/json`. The
+/// document must be a JSON object (a bare value would replace the whole
+/// resource, which no endpoint supports) and is otherwise handled like
+/// [`BodyJson`](super::BodyJson): deserialized, validated, rejections counted.
+///
+/// The DTO encodes the three cases in the field type:
+///
+/// | Column       | Field type          | absent        | `null`            | value              |
+/// |--------------|---------------------|---------------|-------------------|--------------------|
+/// | `NOT NULL`   | `Option<T>`         | `None`        | `None`            | `Some(v)`          |
+/// | `NULL`-able  | `Option<Option<T>>` | `None`        | `Some(None)`      | `Some(Some(v))`    |
+///
+/// Nullable fields need `#[serde(default, deserialize_with =
+/// "serde_helpers::nullable")]`, otherwise `null` is read as absent. Diesel's
+/// `AsChangeset` already skips `None` and writes `Some(None)` as `NULL`, so a
+/// changeset struct with the same field types only touches supplied columns:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize, Validate)]
+/// pub struct UpdateProfileRequest {
+///   pub display_name: Option<String>,
+///   #[serde(default, deserialize_with = "serde_helpers::nullable")]
+///   pub bio: Option<Option<String>>,
+/// }
+///
+/// #[derive(AsChangeset)]
+/// #[diesel(table_name = profiles)]
+/// struct ProfileChangeset {
+///   display_name: Option<String>,
+///   bio: Option<Option<String>>,
+///   updated_at: String,
+/// }
+/// ```
+///
+/// An all-`None` changeset is a diesel error, so always include a column
+/// that is set on every update (e.g. `updated_at`).
+pub struct Patch<T>(pub T);
+
+impl<T> Deref for Patch<T> {
+  type Target = T;
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<S, T> FromRequest<S> for Patch<T>
+where
+  S: Send + Sync,
+  T: DeserializeOwned + Validate + Send,
+{
+  type Rejection = HttpError;
+
+  async fn from_request(
+    req: Request<Body>,
+    state: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let route = middlewares::matched_route(&req).map(str::to_string);
+    let Json(document) = Json::<serde_json::Value>::from_request(req, state)
+      .await
+      .map_err(|e| {
+        metrics::record_json_rejection(route.as_deref(), rejection_kind(&e));
+        HttpError::ERR033(e.to_string())
+      })?;
+
+    if !document.is_object() {
+      return Err(HttpError::ERR033(
+        "Merge patch document must be a JSON object".to_string(),
+      ));
+    }
+    let value: T =
+      serde_json::from_value(document).map_err(|e| HttpError::ERR033(e.to_string()))?;
+
+    value
+      .validate()
+      .map_err(|e| HttpError::ERR034(format_validation_errors(&e)))?;
+
+    Ok(Patch(value))
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::serde_helpers;
+  use diesel::{connection::SimpleConnection, prelude::*, sqlite::SqliteConnection};
+  use serde::Deserialize;
+
+  diesel::table! {
+    profiles (id) {
+      id -> Integer,
+      display_name -> Text,
+      bio -> Nullable<Text>,
+    }
+  }
+
+  #[derive(Debug, Deserialize, Validate)]
+  struct UpdateProfile {
+    #[validate(length(min = 1))]
+    display_name: Option<String>,
+    #[serde(default, deserialize_with = "serde_helpers::nullable")]
+    bio: Option<Option<String>>,
+  }
+
+  #[derive(AsChangeset)]
+  #[diesel(table_name = profiles)]
+  struct ProfileChangeset {
+    display_name: Option<String>,
+    bio: Option<Option<String>>,
+  }
+
+  async fn extract(
+    content_type: &str,
+    body: &str,
+  ) -> Result<UpdateProfile, HttpError> {
+    let req = Request::builder()
+      .method("PATCH")
+      .header("content-type", content_type)
+      .body(Body::from(body.to_string()))
+      .unwrap();
+    Patch::<UpdateProfile>::from_request(req, &())
+      .await
+      .map(|Patch(dto)| dto)
+  }
+
+  /// Applies `body` to a row with `display_name = "ada"`, `bio = "hi"`.
+  async fn apply(body: &str) -> (String, Option<String>) {
+    let dto = extract("application/merge-patch+json", body).await.unwrap();
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn
+      .batch_execute(
+        "CREATE TABLE profiles (id INTEGER PRIMARY KEY, display_name TEXT NOT NULL, bio TEXT);
+         INSERT INTO profiles VALUES (1, 'ada', 'hi');",
+      )
+      .unwrap();
+    // `display_name` is always sent in these cases so the changeset is never empty.
+    diesel::update(profiles::table.find(1))
+      .set(&ProfileChangeset {
+        display_name: dto.display_name,
+        bio: dto.bio,
+      })
+      .execute(&mut conn)
+      .unwrap();
+    profiles::table
+      .select((profiles::display_name, profiles::bio))
+      .first(&mut conn)
+      .unwrap()
+  }
+
+  #[tokio::test]
+  async fn absent_null_and_value_map_to_unchanged_cleared_and_set() {
+    assert_eq!(
+      apply(r#"{"display_name": "ada"}"#).await,
+      ("ada".to_string(), Some("hi".to_string()))
+    );
+    assert_eq!(
+      apply(r#"{"display_name": "ada", "bio": null}"#).await,
+      ("ada".to_string(), None)
+    );
+    assert_eq!(
+      apply(r#"{"display_name": "grace", "bio": "hello"}"#).await,
+      ("grace".to_string(), Some("hello".to_string()))
+    );
+  }
+
+  #[tokio::test]
+  async fn rejects_non_objects_and_invalid_fields() {
+    assert!(matches!(
+      extract("application/json", r#"["bio"]"#).await,
+      Err(HttpError::ERR033(_))
+    ));
+    assert!(matches!(
+      extract("application/json", r#"{"display_name": ""}"#).await,
+      Err(HttpError::ERR034(_))
+    ));
+    assert!(matches!(
+      extract("text/plain", "{}").await,
+      Err(HttpError::ERR033(_))
+    ));
+  }
+}
@@ -0,0 +1,90 @@
+use crate::{
+  models::AppState,
+  services::{
+    HttpError,
+    session::{SESSION_COOKIE, SessionStore},
+  },
+};
+use axum::{
+  extract::FromRequestParts,
+  http::{HeaderMap, header, request::Parts},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Server-side session named by the `session` cookie (see
+/// [`SessionStore`]), with its data deserialized as `T`.
+///
+/// Each request that loads the session restarts its idle timeout. A missing
+/// cookie, or a session that does not exist or has expired, answers `401`;
+/// data that is not a `T` is a server error (`500`).
+///
+/// ```rust,ignore
+/// pub async fn me(session: Session<UserSession>) -> impl IntoResponse {
+///   HttpResponse::ok(session.data.user_id, "OK")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Session<T = Value> {
+  /// Session ID, e.g. for [`SessionStore::destroy`] on logout.
+  pub id: String,
+  pub data: T,
+}
+
+impl<T> FromRequestParts<Arc<AppState>> for Session<T>
+where
+  T: DeserializeOwned + Send,
+{
+  type Rejection = HttpError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &Arc<AppState>,
+  ) -> Result<Self, Self::Rejection> {
+    let id = cookie(&parts.headers, SESSION_COOKIE).ok_or(HttpError::ERR021)?;
+    let sessions = SessionStore::from_state(state);
+    let data = sessions.get::<T>(id).await?.ok_or(HttpError::ERR021)?;
+    sessions.touch(id).await;
+    Ok(Session {
+      id: id.to_string(),
+      data,
+    })
+  }
+}
+
+/// Value of cookie `name` across every `Cookie` header.
+fn cookie<'a>(
+  headers: &'a HeaderMap,
+  name: &str,
+) -> Option<&'a str> {
+  headers
+    .get_all(header::COOKIE)
+    .iter()
+    .filter_map(|v| v.to_str().ok())
+    .flat_map(|v| v.split(';'))
+    .filter_map(|pair| pair.trim().split_once('='))
+    .find(|(key, _)| *key == name)
+    .map(|(_, value)| value)
+    .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::http::HeaderValue;
+
+  #[test]
+  fn finds_the_cookie_among_others() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(cookie(&headers, "session"), None);
+
+    headers.append(header::COOKIE, HeaderValue::from_static("theme=dark"));
+    headers.append(
+      header::COOKIE,
+      HeaderValue::from_static("lang=en; session=abc123"),
+    );
+    assert_eq!(cookie(&headers, "session"), Some("abc123"));
+    assert_eq!(cookie(&headers, "sess"), None);
+  }
+}
@@ -0,0 +1,159 @@
+//! Translation tables for localized error messages.
+//!
+//! Each locale is a flat JSON object (`key -> message`) under `src/i18n/locales/`,
+//! embedded into the binary at compile time. Keys are the stable identifiers
+//! returned by [`HttpError::key`](crate::services::HttpError::key), e.g.
+//! `"INVALID_CREDENTIALS"`.
+//!
+//! The active locale is resolved per request from `Accept-Language` by the
+//! [`locale`](crate::middlewares::locale) layer. Lookups fall back to
+//! [`DEFAULT_LOCALE`] and finally to the key itself, so a missing translation
+//! never breaks a response.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Locale used when the request has no supported `Accept-Language`.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Embedded locale files as `(language, json)` pairs.
+const LOCALE_FILES: [(&str, &str); 2] = [
+  ("en", include_str!("locales/en.json")),
+  ("id", include_str!("locales/id.json")),
+];
+
+/// `lang -> (key -> message)`, parsed once on first use.
+static TRANSLATIONS: LazyLock<HashMap<&'static str, HashMap<String, String>>> =
+  LazyLock::new(|| {
+    LOCALE_FILES
+      .iter()
+      .map(|(lang, json)| {
+        let table: HashMap<String, String> = serde_json::from_str(json)
+          .unwrap_or_else(|e| panic!("{} '{lang}': {e}", "I18N_LOCALE_INVALID"));
+        (*lang, table)
+      })
+      .collect()
+  });
+
+tokio::task_local! {
+  static CURRENT_LOCALE: Locale;
+}
+
+/// Language resolved for the current request, e.g. `Locale("id")`.
+///
+/// Inserted into request extensions by the locale layer, so handlers can read
+/// it with `Extension<Locale>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale(pub &'static str);
+
+impl Default for Locale {
+  fn default() -> Self {
+    Locale(DEFAULT_LOCALE)
+  }
+}
+
+impl Locale {
+  /// Picks the best supported language from an `Accept-Language` header value.
+  ///
+  /// Entries are ordered by their `q` weight (default `1.0`); only the primary
+  /// subtag is matched, so `id-ID` resolves to `id`. Entries with `q=0` are
+  /// ignored. Returns [`Locale::default`] when nothing matches.
+  pub fn from_accept_language(header: &str) -> Self {
+    let mut candidates: Vec<(&str, f32)> = header
+      .split(',')
+      .filter_map(|entry| {
+        let mut parts = entry.trim().split(';');
+        let tag = parts.next()?.trim();
+        let q = parts
+          .find_map(|p| p.trim().strip_prefix("q="))
+          .and_then(|q| q.parse::<f32>().ok())
+          .unwrap_or(1.0);
+        (!tag.is_empty() && q > 0.0).then_some((tag, q))
+      })
+      .collect();
+
+    // Stable sort keeps header order for equal weights.
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    candidates
+      .into_iter()
+      .find_map(|(tag, _)| {
+        let primary = tag.split('-').next().unwrap_or(tag).to_ascii_lowercase();
+        TRANSLATIONS
+          .get_key_value(primary.as_str())
+          .map(|(lang, _)| Locale(lang))
+      })
+      .unwrap_or_default()
+  }
+
+  /// Runs `fut` with this locale as the task's current locale.
+  pub async fn scope<F: Future>(
+    self,
+    fut: F,
+  ) -> F::Output {
+    CURRENT_LOCALE.scope(self, fut).await
+  }
+
+  /// Locale of the request being handled, or the default outside a request.
+  pub fn current() -> Self {
+    CURRENT_LOCALE.try_with(|l| *l).unwrap_or_default()
+  }
+}
+
+/// Resolves `key` in `locale`, falling back to English and then to the key itself.
+pub fn translate(
+  locale: Locale,
+  key: &str,
+) -> String {
+  [locale.0, DEFAULT_LOCALE]
+    .iter()
+    .find_map(|lang| TRANSLATIONS.get(lang)?.get(key))
+    .cloned()
+    .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn picks_highest_weighted_supported_language() {
+    let locale = Locale::from_accept_language("fr-FR,fr;q=0.9,id;q=0.8,en;q=0.7");
+    assert_eq!(locale, Locale("id"));
+  }
+
+  #[test]
+  fn matches_primary_subtag() {
+    assert_eq!(Locale::from_accept_language("id-ID"), Locale("id"));
+  }
+
+  #[test]
+  fn falls_back_to_english() {
+    assert_eq!(Locale::from_accept_language("de, fr;q=0.5"), Locale("en"));
+    assert_eq!(Locale::from_accept_language(""), Locale("en"));
+    assert_eq!(Locale::from_accept_language("id;q=0"), Locale("en"));
+  }
+
+  #[test]
+  fn translate_falls_back_to_english_then_key() {
+    assert_eq!(
+      translate(Locale("id"), "INVALID_CREDENTIALS"),
+      "Email atau kata sandi salah"
+    );
+    assert_eq!(
+      translate(Locale("xx"), "INVALID_CREDENTIALS"),
+      "Invalid email or password"
+    );
+    assert_eq!(translate(Locale("id"), "UNKNOWN_KEY"), "UNKNOWN_KEY");
+  }
+
+  #[test]
+  fn every_locale_covers_english_keys() {
+    let english = &TRANSLATIONS[DEFAULT_LOCALE];
+    for (lang, table) in TRANSLATIONS.iter() {
+      for key in english.keys() {
+        assert!(table.contains_key(key), "{lang} is missing {key}");
+      }
+    }
+  }
+}
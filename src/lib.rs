@@ -3,6 +3,8 @@
 pub mod config;
 pub mod constants;
 pub mod extractors;
+pub mod i18n;
+pub mod middlewares;
 pub mod models;
 pub mod modules;
 pub mod schemas;
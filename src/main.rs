@@ -1,21 +1,305 @@
-use axum_starter::{config, models::AppState, server::AppServer, services::DBSqlite};
-use std::sync::Arc;
+use axum_starter::{
+  config,
+  constants::{
+    CACHE_MEMORY_CHECK_INTERVAL_SECS, CACHE_MEMORY_EVICT_COOLDOWN_SECS, CACHE_PURGE_INTERVAL_SECS,
+    CONFIG_CONSTANT,
+  },
+  models::{AppState, Environment},
+  server::AppServer,
+  services::{
+    Cache, ConnectionCheck, DBSqlite, DBSqliteConfig, DbUrl, Metrics, SessionStore, metrics,
+    spawn_pre_ping,
+  },
+  utils::{
+    file_types, memory, runtime,
+    runtime_config::{self, RuntimeConfig},
+    scheduler::Scheduler,
+    tasks::BackgroundTasks,
+  },
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-#[tokio::main]
-async fn main() {
-  let env = config::load_environment();
+/// Upper bound for each `probe` step.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() {
+  // Before the runtime starts, so a panic in any thread or task is logged
+  runtime::install_panic_hook();
+  runtime::build()
+    .expect("RUNTIME_BUILD_FAILURE")
+    .block_on(run());
+}
+
+async fn run() {
+  let resolver = config::Resolver::from_process();
+  let env = config::load_environment_with(&resolver);
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let db_config = DBSqliteConfig {
+    log_sql: env.log_sql,
+    connection_check: env
+      .db_pre_ping_secs
+      .map_or(ConnectionCheck::OnCheckout, |secs| {
+        ConnectionCheck::PrePing(Duration::from_secs(secs))
+      }),
+    ..Default::default()
+  };
+  // `cargo run -- probe` checks config and database, then exits 0 / 1
+  if args.first().map(String::as_str) == Some("probe") {
+    let healthy = probe(&env, db_config).await;
+    std::process::exit(if healthy { 0 } else { 1 });
+  }
   config::init_logging(&env);
+  resolver.log_sources();
   config::ensure_directories(&env);
+  // Log level, rate limit and upload allowlist from constant.toml, reloaded
+  // on SIGHUP and published to followers (see utils::runtime_config)
+  let runtime_config =
+    RuntimeConfig::load(Path::new(CONFIG_CONSTANT)).expect("RUNTIME_CONFIG_INVALID");
   // Create DB connection pool
-  let db = DBSqlite::new(&env.database_url).expect("DATABASE_POOL_FAILURE");
-  // Run pending migrations
-  db.run_migrations().expect("DATABASE_MIGRATION_FAILURE");
+  let db = DBSqlite::with_config(env.database_url.expose_secret(), &db_config)
+    .expect("DATABASE_POOL_FAILURE")
+    .adaptive_acquire(env.db_adaptive_acquire);
+  // `cargo run -- migrate ...` runs a one-off migration command and exits
+  if args.first().map(String::as_str) == Some("migrate") {
+    migrate(&env, &db, &args[1..]);
+    return;
+  }
+  // Run pending migrations before accepting traffic. A shutdown signal
+  // meanwhile does not interrupt the migration in progress: the rest are
+  // skipped and the process exits once it has committed.
+  let stop = Arc::new(AtomicBool::new(false));
+  let watcher = tokio::spawn({
+    let stop = stop.clone();
+    async move {
+      AppServer::shutdown_signal().await;
+      tracing::warn!("MIGRATION_SHUTDOWN_REQUESTED");
+      stop.store(true, Ordering::SeqCst);
+    }
+  });
+  let migrating = db.clone();
+  let migration_stop = stop.clone();
+  tokio::task::spawn_blocking(move || migrating.run_migrations_until(&migration_stop))
+    .await
+    .expect("DATABASE_MIGRATION_PANICKED")
+    .expect("DATABASE_MIGRATION_FAILURE");
+  watcher.abort();
+  if stop.load(Ordering::SeqCst) {
+    tracing::info!("SHUTDOWN_BEFORE_SERVING");
+    return;
+  }
+  // Optional reporting database; only used by handlers that ask for it
+  let analytics_db = env.analytics_database_url.as_ref().map(|url| {
+    DBSqlite::with_config(url.expose_secret(), &db_config).expect("ANALYTICS_DATABASE_POOL_FAILURE")
+  });
   // Log Start
-  tracing::info!(mode = %env.mode, port = env.port, "SERVER_STARTED");
+  let database = DbUrl::parse(env.database_url.expose_secret()).map(|url| url.to_string());
+  tracing::info!(
+    mode = %env.mode,
+    port = env.port,
+    database = database.as_deref().unwrap_or("unparsed"),
+    "SERVER_STARTED"
+  );
   // Create App State
-  let app_state = Arc::new(AppState { env, db });
+  let metrics_interval = Duration::from_secs(env.metrics_interval_secs);
+  // The one app-wide cache; see `Cache` for when another is warranted
+  let cache = Cache::global().clone();
+  let cache_snapshot = env.cache_snapshot_path.clone().map(PathBuf::from);
+  if let Some(path) = &cache_snapshot {
+    match cache.load_snapshot(path).await {
+      Ok(entries) => tracing::info!(entries, "CACHE_SNAPSHOT_LOADED"),
+      Err(e) => tracing::warn!(error = %e, "CACHE_SNAPSHOT_LOAD_FAILURE"),
+    }
+  }
+  // Kept out of `cache` so memory-pressure eviction and the snapshot skip them
+  let sessions = SessionStore::isolated(env.session.clone());
+  let app_state = Arc::new(AppState {
+    env,
+    db,
+    analytics_db,
+    cache: cache.clone(),
+    sessions,
+    metrics: Metrics::default(),
+    runtime_config: tokio::sync::watch::channel(runtime_config).0,
+  });
+  // Long-lived background tasks, stopped and awaited on graceful shutdown
+  let mut tasks = BackgroundTasks::new();
+  metrics::spawn_sampler(
+    &mut tasks,
+    app_state.metrics.clone(),
+    app_state.db.clone(),
+    app_state.cache.clone(),
+    metrics_interval,
+  );
+  // Both pools share `db_config`, so both need sweeping under pre-ping
+  spawn_pre_ping(&mut tasks, "db_pre_ping", app_state.db.clone());
+  if let Some(analytics_db) = &app_state.analytics_db {
+    spawn_pre_ping(&mut tasks, "analytics_db_pre_ping", analytics_db.clone());
+  }
+  runtime_config::spawn_reloader(
+    &mut tasks,
+    CONFIG_CONSTANT.into(),
+    app_state.runtime_config.clone(),
+  );
+  runtime_config::spawn_follower(
+    &mut tasks,
+    "log_level_follower",
+    app_state.runtime_config.subscribe(),
+    |config| config::set_log_filter(config.log_level.as_deref()),
+  );
+  file_types::spawn_follower(&mut tasks, app_state.runtime_config.subscribe());
+  // Periodic jobs; runs of the same job never overlap (see utils::scheduler)
+  let mut scheduler = Scheduler::new();
+  let purge_cache = app_state.cache.clone();
+  let purge_sessions = app_state.sessions.clone();
+  scheduler.add_job(
+    "cache_purge",
+    Duration::from_secs(CACHE_PURGE_INTERVAL_SECS),
+    move || {
+      let cache = purge_cache.clone();
+      let sessions = purge_sessions.clone();
+      async move {
+        let removed = cache.purge_expired().await;
+        let sessions_removed = sessions.purge_expired().await;
+        tracing::debug!(removed, sessions_removed, "CACHE_PURGED");
+      }
+    },
+  );
+  // Best-effort: RSS is only measured on Linux (see utils::memory)
+  if let Some(limit_mb) = app_state.env.cache_memory_limit_mb {
+    if memory::resident_bytes().is_none() {
+      tracing::warn!("CACHE_MEMORY_MONITOR_UNSUPPORTED");
+    }
+    let limit = limit_mb * 1024 * 1024;
+    let low_water = app_state.env.cache_low_water_entries;
+    let monitored_cache = app_state.cache.clone();
+    let gate = Arc::new(Mutex::new(memory::PressureGate::new(Duration::from_secs(
+      CACHE_MEMORY_EVICT_COOLDOWN_SECS,
+    ))));
+    scheduler.add_job(
+      "cache_memory_monitor",
+      Duration::from_secs(CACHE_MEMORY_CHECK_INTERVAL_SECS),
+      move || {
+        let cache = monitored_cache.clone();
+        let gate = gate.clone();
+        async move {
+          let Some(rss) = memory::resident_bytes() else {
+            return;
+          };
+          if !gate
+            .lock()
+            .unwrap()
+            .should_evict(rss, limit, Instant::now())
+          {
+            return;
+          }
+          let evicted = cache.evict_to(low_water).await;
+          tracing::warn!(rss, limit, evicted, "CACHE_MEMORY_PRESSURE");
+        }
+      },
+    );
+  }
+  scheduler.start(&mut tasks);
 
-  AppServer::serve(app_state)
-    .await
-    .expect("SERVER_FAIL_TO_START");
+  let served = AppServer::serve(app_state, tasks).await;
+  // Background tasks have stopped, so the cache no longer changes
+  if let Some(path) = &cache_snapshot {
+    match cache.save_snapshot(path).await {
+      Ok(entries) => tracing::info!(entries, "CACHE_SNAPSHOT_SAVED"),
+      Err(e) => tracing::error!(error = %e, "CACHE_SNAPSHOT_SAVE_FAILURE"),
+    }
+  }
+  served.expect("SERVER_FAIL_TO_START");
+}
+
+/// `migrate run | revert [--all]`. `revert` is refused outside `APP_ENV=local`.
+fn migrate(
+  env: &Environment,
+  db: &DBSqlite,
+  args: &[String],
+) {
+  let args: Vec<&str> = args.iter().map(String::as_str).collect();
+  let result = match args.as_slice() {
+    ["run"] => db.run_migrations().map(|_| Vec::new()),
+    ["revert"] => db.revert_last_migration(&env.mode).map(|v| vec![v]),
+    ["revert", "--all"] => db.revert_all(&env.mode),
+    _ => Err(anyhow::anyhow!(
+      "MIGRATE_USAGE: migrate run | migrate revert [--all]"
+    )),
+  };
+  match result {
+    Ok(reverted) => reverted.iter().for_each(|v| println!("reverted {v}")),
+    Err(e) => {
+      eprintln!("{e}");
+      std::process::exit(1);
+    }
+  }
+}
+
+/// `probe`: preflight for deploy pipelines. Config has already been loaded
+/// (invalid config aborts in `load_environment`); this connects to the
+/// database and runs its health check, printing one line per step. Returns
+/// whether every step passed. Nothing is migrated or served, and logging is
+/// not initialised so the report is the only output.
+async fn probe(
+  env: &Environment,
+  db_config: DBSqliteConfig,
+) -> bool {
+  let url = env.database_url.expose_secret().clone();
+  // Same redaction as the SERVER_STARTED banner
+  let redacted = DbUrl::parse(&url).map_or_else(|_| "unparsed".to_string(), |url| url.to_string());
+  println!("config    ok (mode={}, port={})", env.mode, env.port);
+
+  // A preflight must not create what it checks: a wrong path fails instead
+  // of leaving an empty database behind and reporting success.
+  let db_config = DBSqliteConfig {
+    create_parent_dir: false,
+    create_file: false,
+    ..db_config
+  };
+  // Building the pool blocks until the connection timeout when the database
+  // is unreachable; a preflight should fail fast instead.
+  let connect = tokio::task::spawn_blocking(move || DBSqlite::with_config(&url, &db_config));
+  let db = match tokio::time::timeout(PROBE_TIMEOUT, connect).await {
+    Ok(Ok(Ok(db))) => {
+      println!("database  ok ({redacted})");
+      db
+    }
+    Ok(Ok(Err(e))) => {
+      println!("database  FAILED ({redacted}): {e}");
+      return false;
+    }
+    Ok(Err(e)) => {
+      println!("database  FAILED ({redacted}): {e}");
+      return false;
+    }
+    Err(_) => {
+      println!(
+        "database  FAILED ({redacted}): no connection within {}s",
+        PROBE_TIMEOUT.as_secs()
+      );
+      return false;
+    }
+  };
+
+  let started = Instant::now();
+  match tokio::time::timeout(PROBE_TIMEOUT, db.health_check()).await {
+    Ok(Ok(())) => {
+      println!("health    ok ({} ms)", started.elapsed().as_millis());
+      true
+    }
+    Ok(Err(e)) => {
+      println!("health    FAILED: {e}");
+      false
+    }
+    Err(_) => {
+      println!(
+        "health    FAILED: no response within {}s",
+        PROBE_TIMEOUT.as_secs()
+      );
+      false
+    }
+  }
 }
@@ -0,0 +1,243 @@
+use crate::{
+  constants::{HEADER_ALLOW, METHOD_ALLOW},
+  middlewares::matched_route,
+  services::HttpError,
+};
+use axum::{
+  extract::Request,
+  http::{HeaderValue, Method, StatusCode, header},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use std::task::{Context, Poll};
+use tower::{
+  Layer, Service, ServiceExt,
+  util::{Either, Oneshot},
+};
+use tower_http::cors::{Any, CorsLayer};
+
+/// Default policy: only `CORS_ORIGINS` may call the API from a browser.
+///
+/// Applied by `AppRoutes::build` to every route group that does not bring its
+/// own policy, and by the server to the static-file fallback.
+pub fn restricted(origins: &[String]) -> CorsLayer {
+  let allowed: Vec<HeaderValue> = origins
+    .iter()
+    .filter_map(|o| o.parse::<HeaderValue>().ok())
+    .collect();
+  CorsLayer::new()
+    .allow_origin(allowed)
+    .allow_methods(METHOD_ALLOW)
+    .allow_headers(HEADER_ALLOW)
+}
+
+/// Any-origin policy for public endpoints (webhooks, public read APIs).
+///
+/// Credentials are never allowed with a wildcard origin, so cookies and
+/// `Authorization` from the browser are not exposed cross-site.
+pub fn any_origin() -> CorsLayer {
+  CorsLayer::new()
+    .allow_origin(Any)
+    .allow_methods(METHOD_ALLOW)
+    .allow_headers(HEADER_ALLOW)
+}
+
+/// Normalizes answers to browser preflights (`OPTIONS` with
+/// `Access-Control-Request-Method`), whichever `CorsLayer` produced them.
+///
+/// - A matched path gets `204 No Content` instead of `CorsLayer`'s `200`.
+/// - An unmatched path gets the JSON `ERR404` body with status `404`, instead
+///   of the fallback's `200`. Any CORS headers the fallback's layer set are
+///   kept, so the browser reports a rejected preflight rather than a missing
+///   `Access-Control-Allow-Origin`.
+///
+/// Must wrap the fallback as well as the routes, so the server applies it in
+/// its `route_layer`, after routing.
+pub async fn preflight(
+  req: Request,
+  next: Next,
+) -> Response {
+  if !is_preflight(&req) {
+    return next.run(req).await;
+  }
+  let matched = matched_route(&req).is_some();
+  let mut res = next.run(req).await;
+  if !matched {
+    let mut rejection = HttpError::ERR404.into_response();
+    for (name, value) in res.headers() {
+      if name.as_str().starts_with("access-control-") || name == header::VARY {
+        rejection.headers_mut().append(name, value.clone());
+      }
+    }
+    return rejection;
+  }
+  if res.status() == StatusCode::OK {
+    *res.status_mut() = StatusCode::NO_CONTENT;
+  }
+  res
+}
+
+/// `OPTIONS` with `Access-Control-Request-Method`.
+fn is_preflight<B>(req: &Request<B>) -> bool {
+  req.method() == Method::OPTIONS
+    && req
+      .headers()
+      .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Runs requests through the layer `L`, except CORS preflights, which go
+/// straight to the inner service.
+///
+/// The `CorsLayer` that answers a preflight is applied per route group, so
+/// it sits inside the server's rate and concurrency limits. A browser sends
+/// a preflight before the request it guards; throttling or shedding it
+/// fails that request with a CORS error instead of a readable `429` / `503`,
+/// so the server wraps its limits in this layer.
+#[derive(Clone, Debug)]
+pub struct SkipPreflight<L>(pub L);
+
+impl<L, S> Layer<S> for SkipPreflight<L>
+where
+  L: Layer<S>,
+  S: Clone,
+{
+  type Service = SkipPreflightService<L::Service, S>;
+
+  fn layer(
+    &self,
+    inner: S,
+  ) -> Self::Service {
+    SkipPreflightService {
+      limited: self.0.layer(inner.clone()),
+      direct: inner,
+    }
+  }
+}
+
+/// Service built by [`SkipPreflight`].
+#[derive(Clone, Debug)]
+pub struct SkipPreflightService<A, B> {
+  limited: A,
+  direct: B,
+}
+
+impl<A, B, ReqBody> Service<Request<ReqBody>> for SkipPreflightService<A, B>
+where
+  A: Service<Request<ReqBody>> + Clone,
+  B: Service<Request<ReqBody>, Response = A::Response, Error = A::Error> + Clone,
+{
+  type Response = A::Response;
+  type Error = A::Error;
+  type Future = Oneshot<Either<A, B>, Request<ReqBody>>;
+
+  // Readiness is checked by `oneshot` on whichever side the request takes,
+  // so the limits never hold back a preflight.
+  fn poll_ready(
+    &mut self,
+    _cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(
+    &mut self,
+    req: Request<ReqBody>,
+  ) -> Self::Future {
+    let service = if is_preflight(&req) {
+      Either::Right(self.direct.clone())
+    } else {
+      Either::Left(self.limited.clone())
+    };
+    service.oneshot(req)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{Method, header},
+    routing::post,
+  };
+  use tower::ServiceExt;
+
+  async fn preflight(
+    app: &Router,
+    path: &str,
+  ) -> Option<HeaderValue> {
+    let req = Request::builder()
+      .method(Method::OPTIONS)
+      .uri(path)
+      .header(header::ORIGIN, "https://partner.example")
+      .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+      .body(Body::empty())
+      .unwrap();
+    let res = app.clone().oneshot(req).await.unwrap();
+    res
+      .headers()
+      .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+      .cloned()
+  }
+
+  #[tokio::test]
+  async fn group_policy_overrides_default() {
+    let private = Router::new().route("/users", post(|| async {}));
+    let public = Router::new()
+      .route("/webhooks", post(|| async {}))
+      .layer(any_origin());
+    let app = private
+      .layer(restricted(&["http://localhost:3000".to_string()]))
+      .merge(public);
+
+    assert_eq!(preflight(&app, "/users").await, None);
+    assert_eq!(
+      preflight(&app, "/webhooks").await,
+      Some(HeaderValue::from_static("*"))
+    );
+  }
+
+  #[tokio::test]
+  async fn preflight_is_204_for_known_paths_and_404_for_unknown() {
+    // Same shape as the server: routes plus a CORS-wrapped fallback, with
+    // `preflight` layered over both.
+    let origins = ["https://partner.example".to_string()];
+    let app = Router::new()
+      .route("/users", post(|| async {}))
+      .layer(restricted(&origins))
+      .fallback_service(
+        tower::ServiceBuilder::new()
+          .layer(restricted(&origins))
+          .service(axum::routing::any(|| async { HttpError::ERR404 })),
+      )
+      .layer(axum::middleware::from_fn(super::preflight));
+
+    let send = |path: &'static str| {
+      let req = Request::builder()
+        .method(Method::OPTIONS)
+        .uri(path)
+        .header(header::ORIGIN, "https://partner.example")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .body(Body::empty())
+        .unwrap();
+      app.clone().oneshot(req)
+    };
+
+    let known = send("/users").await.unwrap();
+    assert_eq!(known.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+      known.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+      "https://partner.example"
+    );
+
+    let missing = send("/missing").await.unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+      missing.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+      "https://partner.example"
+    );
+    assert_eq!(missing.headers()[header::CONTENT_TYPE], "application/json");
+  }
+}
@@ -0,0 +1,164 @@
+use crate::services::HttpError;
+use axum::{
+  body::{Body, HttpBody},
+  extract::{Request, State},
+  http::header,
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use std::{future::poll_fn, pin::Pin};
+use tower::{layer::util::Stack, util::MapRequestLayer};
+use tower_http::decompression::{DecompressionBody, RequestDecompressionLayer};
+
+/// Most bytes a compressed request body may inflate to
+/// (`MAX_DECOMPRESSED_BYTES`, default 2 MiB).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecompressionLimit(pub usize);
+
+/// Set by [`accept_gzip`] on requests whose body arrives gzip-compressed;
+/// the decompression layer strips `Content-Encoding`, so this is how
+/// [`limit_decompressed`] recognises them.
+#[derive(Clone, Copy, Debug)]
+struct Compressed(DecompressionLimit);
+
+type IntoBody = fn(Request<DecompressionBody<Body>>) -> Request;
+
+/// Inflates `Content-Encoding: gzip` bodies before they reach extractors,
+/// handing them on as a plain [`Body`].
+///
+/// Only gzip is accepted; other encodings are turned away by [`accept_gzip`]
+/// first, so this layer never answers on its own.
+pub fn layer() -> Stack<MapRequestLayer<IntoBody>, RequestDecompressionLayer> {
+  let decompress = RequestDecompressionLayer::new()
+    .gzip(true)
+    .no_deflate()
+    .no_br()
+    .no_zstd();
+  let into_body: IntoBody = |req| req.map(Body::new);
+  Stack::new(MapRequestLayer::new(into_body), decompress)
+}
+
+/// Runs before [`layer`]: rejects bodies in an encoding other than gzip (or
+/// `identity`) with `415` ([`HttpError::ERR415`]), and marks gzip bodies for
+/// [`limit_decompressed`].
+pub async fn accept_gzip(
+  State(limit): State<DecompressionLimit>,
+  mut req: Request,
+  next: Next,
+) -> Response {
+  match req.headers().get(header::CONTENT_ENCODING) {
+    None => {}
+    Some(encoding) if encoding == "identity" => {}
+    Some(encoding) if encoding == "gzip" => {
+      req.extensions_mut().insert(Compressed(limit));
+    }
+    Some(_) => return HttpError::ERR415("Content-Encoding: gzip".to_string()).into_response(),
+  }
+  next.run(req).await
+}
+
+/// Runs after [`layer`]: reads a decompressed body into memory, refusing it
+/// with `413` ([`HttpError::ERR413`]) as soon as it inflates past the limit,
+/// so a small zip bomb never expands into more than `limit` bytes. A body that
+/// is not valid gzip gets `400` ([`HttpError::ERR033`]). Uncompressed bodies
+/// pass through untouched.
+pub async fn limit_decompressed(
+  req: Request,
+  next: Next,
+) -> Response {
+  let Some(&Compressed(DecompressionLimit(limit))) = req.extensions().get::<Compressed>() else {
+    return next.run(req).await;
+  };
+
+  let (parts, mut body) = req.into_parts();
+  let mut buf = Vec::new();
+  while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+    let frame = match frame {
+      Ok(frame) => frame,
+      Err(e) => return HttpError::ERR033(e.to_string()).into_response(),
+    };
+    if let Ok(data) = frame.into_data() {
+      if buf.len() + data.len() > limit {
+        tracing::warn!(
+          limit,
+          path = parts.uri.path(),
+          "REQUEST_DECOMPRESSED_TOO_LARGE"
+        );
+        return HttpError::ERR413(limit).into_response();
+      }
+      buf.extend_from_slice(&data);
+    }
+  }
+
+  next.run(Request::from_parts(parts, Body::from(buf))).await
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Bytes, http::StatusCode, middleware, routing::post};
+  use flate2::{Compression, write::GzEncoder};
+  use std::io::Write;
+  use tower::{ServiceBuilder, ServiceExt};
+
+  fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+  }
+
+  async fn send(
+    encoding: Option<&str>,
+    body: Vec<u8>,
+  ) -> (StatusCode, Bytes) {
+    let limit = DecompressionLimit(1024);
+    let app: Router = Router::new()
+      .route("/", post(|body: Bytes| async move { body }))
+      .layer(
+        ServiceBuilder::new()
+          .layer(middleware::from_fn_with_state(limit, accept_gzip))
+          .layer(layer())
+          .layer(middleware::from_fn(limit_decompressed)),
+      );
+    let mut req = Request::post("/");
+    if let Some(encoding) = encoding {
+      req = req.header(header::CONTENT_ENCODING, encoding);
+    }
+    let res = app
+      .oneshot(req.body(Body::from(body)).unwrap())
+      .await
+      .unwrap();
+    let status = res.status();
+    (
+      status,
+      axum::body::to_bytes(res.into_body(), usize::MAX)
+        .await
+        .unwrap(),
+    )
+  }
+
+  #[tokio::test]
+  async fn inflates_gzip_bodies_within_the_limit() {
+    let (status, body) = send(Some("gzip"), gzip(br#"{"name":"ada"}"#)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], br#"{"name":"ada"}"#);
+
+    let (status, body) = send(None, b"plain".to_vec()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(&body[..], b"plain");
+  }
+
+  #[tokio::test]
+  async fn rejects_bombs_corrupt_and_unsupported_bodies() {
+    // 1 MiB of zeros compresses to about 1 KiB
+    let (status, _) = send(Some("gzip"), gzip(&vec![0; 1 << 20])).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (status, _) = send(Some("gzip"), b"not gzip".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(Some("br"), b"x".to_vec()).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+  }
+}
@@ -0,0 +1,102 @@
+use crate::services::HttpError;
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+/// Request header budget enforced by [`header_limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaderLimits {
+  /// Most header fields accepted (`MAX_HEADER_COUNT`, default 64).
+  pub max_count: usize,
+  /// Most bytes across all header names and values (`MAX_HEADER_BYTES`,
+  /// default 16 KiB).
+  pub max_bytes: usize,
+}
+
+impl HeaderLimits {
+  fn exceeded_by(
+    &self,
+    req: &Request,
+  ) -> bool {
+    let headers = req.headers();
+    if headers.len() > self.max_count {
+      return true;
+    }
+    let bytes: usize = headers
+      .iter()
+      .map(|(name, value)| name.as_str().len() + value.len())
+      .sum();
+    bytes > self.max_bytes
+  }
+}
+
+/// Rejects requests over the header budget with `431 Request Header Fields
+/// Too Large` ([`HttpError::ERR431`]) before any handler or extractor runs.
+///
+/// Hyper keeps its own connection-level caps (100 header fields, ~400 KiB
+/// read buffer) and answers a bare `431` beyond them, so limits above those
+/// have no effect. This layer keeps the usual error envelope for anything
+/// that passes hyper but is still larger than the app wants to handle, such
+/// as oversized cookies or `Accept-Encoding` lists.
+pub async fn header_limits(
+  State(limits): State<HeaderLimits>,
+  req: Request,
+  next: Next,
+) -> Response {
+  if limits.exceeded_by(&req) {
+    tracing::warn!(
+      count = req.headers().len(),
+      path = req.uri().path(),
+      "REQUEST_HEADERS_TOO_LARGE"
+    );
+    return HttpError::ERR431.into_response();
+  }
+  next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Body, http::StatusCode, middleware, routing::get};
+  use tower::ServiceExt;
+
+  async fn status_for(req: Request) -> StatusCode {
+    let limits = HeaderLimits {
+      max_count: 4,
+      max_bytes: 64,
+    };
+    let app: Router = Router::new()
+      .route("/", get(|| async {}))
+      .layer(middleware::from_fn_with_state(limits, header_limits));
+    app.oneshot(req).await.unwrap().status()
+  }
+
+  #[tokio::test]
+  async fn rejects_too_many_or_too_large_headers() {
+    let ok = Request::get("/")
+      .header("accept-encoding", "gzip")
+      .body(Body::empty())
+      .unwrap();
+    assert_eq!(status_for(ok).await, StatusCode::OK);
+
+    let mut many = Request::get("/");
+    for i in 0..5 {
+      many = many.header(format!("x-h{i}"), "1");
+    }
+    assert_eq!(
+      status_for(many.body(Body::empty()).unwrap()).await,
+      StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+    );
+
+    let large = Request::get("/")
+      .header("accept-encoding", "gzip, ".repeat(20))
+      .body(Body::empty())
+      .unwrap();
+    assert_eq!(
+      status_for(large).await,
+      StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+    );
+  }
+}
@@ -0,0 +1,22 @@
+use crate::i18n::Locale;
+use axum::{extract::Request, http::header::ACCEPT_LANGUAGE, middleware::Next, response::Response};
+
+/// Resolves the request language from `Accept-Language`.
+///
+/// The [`Locale`] is inserted into request extensions and set as the task's
+/// current locale while the rest of the stack runs, so `HttpError` responses
+/// are rendered in the caller's language.
+pub async fn locale(
+  mut req: Request,
+  next: Next,
+) -> Response {
+  let locale = req
+    .headers()
+    .get(ACCEPT_LANGUAGE)
+    .and_then(|v| v.to_str().ok())
+    .map(Locale::from_accept_language)
+    .unwrap_or_default();
+
+  req.extensions_mut().insert(locale);
+  locale.scope(next.run(req)).await
+}
@@ -1 +1,25 @@
+pub mod cors;
+pub mod decompression;
+pub mod header_limits;
+pub mod locale;
 pub mod logger;
+pub mod rate_limit;
+pub mod request_id;
+pub mod required_headers;
+pub mod route;
+pub mod security_headers;
+pub mod sla;
+pub mod trace_sampling;
+pub mod trailing_slash;
+
+pub use decompression::DecompressionLimit;
+pub use header_limits::{HeaderLimits, header_limits};
+pub use locale::locale;
+pub use rate_limit::{RateLimitStatus, RateLimiter, rate_limit};
+pub use request_id::request_id;
+pub use required_headers::{RequiredHeaders, require_headers};
+pub use route::{matched_route, route_or_path};
+pub use security_headers::{SecurityHeaders, security_headers};
+pub use sla::{SlaThreshold, sla_breach, sla_override};
+pub use trace_sampling::{TraceSampleRate, is_sampled, sample_trace};
+pub use trailing_slash::redirect_trailing_slash;
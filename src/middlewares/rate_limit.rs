@@ -0,0 +1,221 @@
+//! Fixed-window request rate limit answering `429` with `RateLimit` headers.
+//!
+//! The server-wide limit in `server.rs` (tower's `RateLimitLayer`) never
+//! rejects: requests over its rate wait in the buffer, and are shed with
+//! `503` once it is full. [`rate_limit`] is for budgets a client should be
+//! told about, attached to a route group with one [`RateLimiter`] shared by
+//! the group:
+//!
+//! ```rust,ignore
+//! Router::new()
+//!   .route("/auth/login", post(controller::login))
+//!   .route_layer(middleware::from_fn_with_state(
+//!     RateLimiter::new(10, Duration::from_secs(60)),
+//!     middlewares::rate_limit,
+//!   ))
+//! ```
+//!
+//! Throttled responses carry the header fields of
+//! [draft-ietf-httpapi-ratelimit-headers-06](https://datatracker.ietf.org/doc/html/draft-ietf-httpapi-ratelimit-headers-06),
+//! the last revision with separate fields (later revisions fold them into a
+//! single structured `RateLimit` field), plus `Retry-After`:
+//!
+//! - `RateLimit-Limit`: requests allowed per window;
+//! - `RateLimit-Remaining`: requests left in the current window (`0`);
+//! - `RateLimit-Reset`: seconds until the window resets;
+//! - `Retry-After`: the same number of seconds, for clients that predate the
+//!   draft.
+
+use crate::{
+  services::HttpError,
+  utils::{SharedClock, SystemClock},
+};
+use axum::{
+  extract::{Request, State},
+  http::{HeaderName, header},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use std::{
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+static RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// State of the current window, as reported in the `RateLimit` headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitStatus {
+  pub limit: u32,
+  pub remaining: u32,
+  /// Whole seconds until the window resets, rounded up.
+  pub reset_secs: u64,
+}
+
+#[derive(Debug)]
+struct Window {
+  limit: u32,
+  length: Duration,
+  started: Instant,
+  used: u32,
+}
+
+/// Allows `limit` requests per `window`, shared by every clone.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+  state: Arc<Mutex<Window>>,
+  clock: SharedClock,
+}
+
+impl RateLimiter {
+  pub fn new(
+    limit: u32,
+    window: Duration,
+  ) -> Self {
+    Self::with_clock(limit, window, SystemClock::shared())
+  }
+
+  /// Limiter that allows everything until [`reconfigure`](Self::reconfigure)d.
+  pub fn unlimited() -> Self {
+    Self::new(u32::MAX, Duration::from_secs(1))
+  }
+
+  /// Limiter reading the time from `clock`, so tests can move to the next
+  /// window without sleeping.
+  pub fn with_clock(
+    limit: u32,
+    window: Duration,
+    clock: SharedClock,
+  ) -> Self {
+    let started = clock.now();
+    Self {
+      state: Arc::new(Mutex::new(Window {
+        limit,
+        length: window,
+        started,
+        used: 0,
+      })),
+      clock,
+    }
+  }
+
+  /// Switches every clone to `limit` requests per `window`, starting a fresh
+  /// window; a no-op when neither changes. Used to follow
+  /// `RuntimeConfig::rate_limit` on reload.
+  pub fn reconfigure(
+    &self,
+    limit: u32,
+    window: Duration,
+  ) {
+    let mut state = self.state.lock().unwrap();
+    if (state.limit, state.length) == (limit, window) {
+      return;
+    }
+    *state = Window {
+      limit,
+      length: window,
+      started: self.clock.now(),
+      used: 0,
+    };
+  }
+
+  /// Counts one request: `Ok` with the remaining budget when it is allowed,
+  /// `Err` with the window's state when it is over the limit.
+  pub fn acquire(&self) -> Result<RateLimitStatus, RateLimitStatus> {
+    let now = self.clock.now();
+    let mut window = self.state.lock().unwrap();
+    if now.duration_since(window.started) >= window.length {
+      window.started = now;
+      window.used = 0;
+    }
+    let reset = window.length - now.duration_since(window.started);
+    let allowed = window.used < window.limit;
+    if allowed {
+      window.used += 1;
+    }
+    let status = RateLimitStatus {
+      limit: window.limit,
+      remaining: window.limit - window.used,
+      reset_secs: reset.as_secs() + u64::from(reset.subsec_nanos() > 0),
+    };
+    if allowed { Ok(status) } else { Err(status) }
+  }
+}
+
+/// Rejects requests over the [`RateLimiter`]'s budget with `429`
+/// ([`HttpError::ERR429`]) and the headers described in the module docs.
+/// Allowed requests pass through untouched.
+pub async fn rate_limit(
+  State(limiter): State<RateLimiter>,
+  req: Request,
+  next: Next,
+) -> Response {
+  let status = match limiter.acquire() {
+    Ok(_) => return next.run(req).await,
+    Err(status) => status,
+  };
+  tracing::warn!(
+    limit = status.limit,
+    reset_secs = status.reset_secs,
+    path = req.uri().path(),
+    "RATE_LIMITED"
+  );
+  let headers = [
+    (RATELIMIT_LIMIT.clone(), status.limit.to_string()),
+    (RATELIMIT_REMAINING.clone(), status.remaining.to_string()),
+    (RATELIMIT_RESET.clone(), status.reset_secs.to_string()),
+    (header::RETRY_AFTER, status.reset_secs.max(1).to_string()),
+  ];
+  (headers, HttpError::ERR429).into_response()
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::MockClock;
+  use axum::{Router, body::Body, http::StatusCode, middleware, routing::get};
+  use tower::ServiceExt;
+
+  #[tokio::test]
+  async fn throttled_response_carries_ratelimit_headers() {
+    let clock = MockClock::new();
+    let limiter = RateLimiter::with_clock(2, Duration::from_secs(60), Arc::new(clock.clone()));
+    let app: Router = Router::new()
+      .route("/", get(|| async {}))
+      .layer(middleware::from_fn_with_state(limiter, rate_limit));
+    let send = || {
+      app
+        .clone()
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+    };
+
+    assert_eq!(send().await.unwrap().status(), StatusCode::OK);
+    clock.advance(Duration::from_millis(19_500));
+    assert_eq!(send().await.unwrap().status(), StatusCode::OK);
+
+    let throttled = send().await.unwrap();
+    assert_eq!(throttled.status(), StatusCode::TOO_MANY_REQUESTS);
+    let headers = throttled.headers();
+    assert_eq!(headers["ratelimit-limit"], "2");
+    assert_eq!(headers["ratelimit-remaining"], "0");
+    assert_eq!(headers["ratelimit-reset"], "41");
+    assert_eq!(headers[header::RETRY_AFTER], "41");
+
+    clock.advance(Duration::from_secs(41));
+    assert_eq!(send().await.unwrap().status(), StatusCode::OK);
+  }
+
+  #[test]
+  fn reconfigure_applies_to_every_clone() {
+    let limiter = RateLimiter::unlimited();
+    let clone = limiter.clone();
+    assert!((0..100).all(|_| clone.acquire().is_ok()));
+
+    limiter.reconfigure(1, Duration::from_secs(60));
+    assert!(clone.acquire().is_ok());
+    assert_eq!(clone.acquire().unwrap_err().limit, 1);
+  }
+}
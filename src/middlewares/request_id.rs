@@ -0,0 +1,23 @@
+use crate::utils::request_id::{RequestId, with_request_id};
+use axum::{extract::Request, middleware::Next, response::Response};
+
+/// Makes the request's `x-request-id` available as [`RequestId::current`]
+/// while the rest of the stack runs, so database work can be tagged with it.
+///
+/// Must sit inside `SetRequestIdLayer`, which guarantees the header is set.
+/// IDs failing [`RequestId::parse`] are not propagated.
+pub async fn request_id(
+  req: Request,
+  next: Next,
+) -> Response {
+  let id = req
+    .headers()
+    .get("x-request-id")
+    .and_then(|v| v.to_str().ok())
+    .and_then(RequestId::parse);
+
+  match id {
+    Some(id) => with_request_id(id, next.run(req)).await,
+    None => next.run(req).await,
+  }
+}
@@ -0,0 +1,85 @@
+use crate::services::HttpError;
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+/// Header names a route group refuses to run without; see [`require_headers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequiredHeaders(pub &'static [&'static str]);
+
+/// Rejects requests missing any of the [`RequiredHeaders`] with `400`
+/// ([`HttpError::ERR042`]) listing every missing name, before extractors or
+/// the handler run. A header that is present but empty counts as missing.
+///
+/// Attach with `route_layer` so unmatched paths still get `404`:
+///
+/// ```rust,ignore
+/// Router::new()
+///   .route("/reports", get(controller::list))
+///   .route_layer(middleware::from_fn_with_state(
+///     RequiredHeaders(&["X-Tenant-ID"]),
+///     middlewares::require_headers,
+///   ))
+/// ```
+pub async fn require_headers(
+  State(RequiredHeaders(required)): State<RequiredHeaders>,
+  req: Request,
+  next: Next,
+) -> Response {
+  let headers = req.headers();
+  let missing: Vec<&str> = required
+    .iter()
+    .copied()
+    .filter(|name| headers.get(*name).is_none_or(|value| value.is_empty()))
+    .collect();
+  if !missing.is_empty() {
+    return HttpError::ERR042(missing.join(", ")).into_response();
+  }
+  next.run(req).await
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Body, http::StatusCode, middleware, routing::get};
+  use tower::ServiceExt;
+
+  fn app() -> Router {
+    Router::new()
+      .route("/reports", get(|| async { "ok" }))
+      .route_layer(middleware::from_fn_with_state(
+        RequiredHeaders(&["X-Tenant-ID", "X-Region"]),
+        require_headers,
+      ))
+  }
+
+  #[tokio::test]
+  async fn missing_headers_short_circuit_with_their_names() {
+    let req = Request::get("/reports")
+      .header("x-region", "eu")
+      .body(Body::empty())
+      .unwrap();
+    let res = app().oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(body.to_string().contains("X-Tenant-ID"));
+    assert!(!body.to_string().contains("X-Region"));
+  }
+
+  #[tokio::test]
+  async fn present_headers_pass_through() {
+    let req = Request::get("/reports")
+      .header("x-tenant-id", "acme")
+      .header("x-region", "eu")
+      .body(Body::empty())
+      .unwrap();
+    let res = app().oneshot(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+  }
+}
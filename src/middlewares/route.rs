@@ -0,0 +1,62 @@
+use axum::{extract::MatchedPath, http::Request};
+
+/// Route template a request matched (`/users/{id}` rather than `/users/42`),
+/// or `None` when no route matched (static files, 404s).
+///
+/// Available to anything layered with `Router::layer`, which runs after
+/// routing. Metrics label with this and map `None` to a fixed value, so the
+/// label set stays bounded by the router's routes.
+pub fn matched_route<B>(req: &Request<B>) -> Option<&str> {
+  req
+    .extensions()
+    .get::<MatchedPath>()
+    .map(MatchedPath::as_str)
+}
+
+/// [`matched_route`], falling back to the raw path when no route matched.
+/// Used for the request span, the access log and `SLA_BREACH`, where an
+/// unmatched path is still worth seeing.
+pub fn route_or_path<B>(req: &Request<B>) -> &str {
+  matched_route(req).unwrap_or_else(|| req.uri().path())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+  };
+  use tower::ServiceExt;
+
+  async fn echo_route(
+    req: Request,
+    next: Next,
+  ) -> Response {
+    let route = route_or_path(&req).to_string();
+    let mut res = next.run(req).await;
+    res.headers_mut().insert("x-route", route.parse().unwrap());
+    res
+  }
+
+  async fn route_for(uri: &str) -> String {
+    let app: Router = Router::new()
+      .route("/users/{id}", get(|| async {}))
+      .layer(middleware::from_fn(echo_route));
+    let res = app
+      .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    res.headers()["x-route"].to_str().unwrap().to_string()
+  }
+
+  #[tokio::test]
+  async fn labels_by_template_and_falls_back_to_path() {
+    assert_eq!(route_for("/users/42").await, "/users/{id}");
+    assert_eq!(route_for("/nope/42").await, "/nope/42");
+  }
+}
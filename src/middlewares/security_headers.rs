@@ -0,0 +1,139 @@
+use crate::models::SecurityHeadersConfig;
+use axum::{
+  extract::{Request, State},
+  http::{HeaderName, HeaderValue, header},
+  middleware::Next,
+  response::Response,
+};
+use std::sync::Arc;
+
+/// Header values applied by [`security_headers`], parsed once at startup from
+/// [`SecurityHeadersConfig`].
+#[derive(Clone, Debug)]
+pub struct SecurityHeaders {
+  always: Arc<Vec<(HeaderName, HeaderValue)>>,
+  hsts: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+  /// Panics on a value that is not a valid header value; `load_environment`
+  /// has already rejected those.
+  pub fn new(config: &SecurityHeadersConfig) -> Self {
+    let value = |v: &Option<String>| {
+      v.as_deref()
+        .map(|v| HeaderValue::from_str(v).expect("SECURITY_HEADER_INVALID"))
+    };
+    let always = [
+      (header::X_CONTENT_TYPE_OPTIONS, &config.content_type_options),
+      (header::X_FRAME_OPTIONS, &config.frame_options),
+      (header::REFERRER_POLICY, &config.referrer_policy),
+      (
+        header::CONTENT_SECURITY_POLICY,
+        &config.content_security_policy,
+      ),
+    ]
+    .into_iter()
+    .filter_map(|(name, v)| value(v).map(|v| (name, v)))
+    .collect();
+    Self {
+      always: Arc::new(always),
+      hsts: value(&config.hsts),
+    }
+  }
+}
+
+/// Adds the configured security headers to every response, leaving any a
+/// handler set itself (e.g. a looser CSP on one page) untouched.
+///
+/// `Strict-Transport-Security` is only sent on requests that arrived over
+/// HTTPS: this server speaks plain HTTP, so that means a TLS-terminating proxy
+/// in front set `X-Forwarded-Proto: https`. Browsers ignore HSTS over plain
+/// HTTP, and sending it from a local `http://` setup would pin the host.
+pub async fn security_headers(
+  State(config): State<SecurityHeaders>,
+  req: Request,
+  next: Next,
+) -> Response {
+  let over_https = req
+    .headers()
+    .get("x-forwarded-proto")
+    .is_some_and(|proto| proto.as_bytes().eq_ignore_ascii_case(b"https"));
+
+  let mut res = next.run(req).await;
+  let headers = res.headers_mut();
+  for (name, value) in config.always.iter() {
+    headers.entry(name).or_insert_with(|| value.clone());
+  }
+  if let Some(hsts) = config.hsts.as_ref().filter(|_| over_https) {
+    headers
+      .entry(header::STRICT_TRANSPORT_SECURITY)
+      .or_insert_with(|| hsts.clone());
+  }
+  res
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::AppEnv;
+  use axum::{Router, body::Body, middleware, routing::get};
+  use tower::ServiceExt;
+
+  async fn headers_for(
+    config: SecurityHeadersConfig,
+    req: Request,
+  ) -> axum::http::HeaderMap {
+    let app: Router = Router::new()
+      .route("/", get(|| async {}))
+      .route(
+        "/framed",
+        get(|| async { ([(header::X_FRAME_OPTIONS, "SAMEORIGIN")], "") }),
+      )
+      .layer(middleware::from_fn_with_state(
+        SecurityHeaders::new(&config),
+        security_headers,
+      ));
+    app.oneshot(req).await.unwrap().headers().clone()
+  }
+
+  #[tokio::test]
+  async fn production_defaults_with_hsts_only_over_https() {
+    let config = SecurityHeadersConfig::for_env(&AppEnv::Production);
+    let plain = headers_for(
+      config.clone(),
+      Request::get("/").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(plain[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(plain[header::X_FRAME_OPTIONS], "DENY");
+    assert_eq!(plain[header::REFERRER_POLICY], "no-referrer");
+    assert!(plain.contains_key(header::CONTENT_SECURITY_POLICY));
+    assert!(!plain.contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+    let https = Request::get("/")
+      .header("x-forwarded-proto", "https")
+      .body(Body::empty())
+      .unwrap();
+    let https = headers_for(config.clone(), https).await;
+    assert_eq!(
+      https[header::STRICT_TRANSPORT_SECURITY],
+      "max-age=31536000; includeSubDomains"
+    );
+
+    let framed = headers_for(config, Request::get("/framed").body(Body::empty()).unwrap()).await;
+    assert_eq!(framed[header::X_FRAME_OPTIONS], "SAMEORIGIN");
+  }
+
+  #[tokio::test]
+  async fn disabled_headers_are_left_out() {
+    let config = SecurityHeadersConfig {
+      frame_options: None,
+      ..SecurityHeadersConfig::for_env(&AppEnv::Local)
+    };
+    let headers = headers_for(config, Request::get("/").body(Body::empty()).unwrap()).await;
+    assert!(!headers.contains_key(header::X_FRAME_OPTIONS));
+    assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY));
+    assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+  }
+}
@@ -0,0 +1,130 @@
+use super::route_or_path;
+use crate::constants::SLA_EXCLUDED_PATHS;
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::Response,
+};
+use std::time::{Duration, Instant};
+
+/// Response-time budget for a request; exceeding it logs `SLA_BREACH`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlaThreshold(pub Duration);
+
+impl SlaThreshold {
+  pub fn from_millis(ms: u64) -> Self {
+    Self(Duration::from_millis(ms))
+  }
+}
+
+/// Logs requests slower than their SLA with `tracing::warn!("SLA_BREACH")`.
+///
+/// The state is the global threshold (`SLA_MS`). A route can override it with
+/// [`sla_override`]; the override travels back on the response, so it wins
+/// over the global value. Paths under [`SLA_EXCLUDED_PATHS`] (health probes,
+/// metrics scrapes) are never reported. Runs alongside the access log rather
+/// than replacing it, so breaches can be alerted on by event name.
+pub async fn sla_breach(
+  State(default): State<SlaThreshold>,
+  req: Request,
+  next: Next,
+) -> Response {
+  if is_excluded(req.uri().path()) {
+    return next.run(req).await;
+  }
+
+  let method = req.method().clone();
+  let path = req.uri().path().to_string();
+  let route = route_or_path(&req).to_string();
+  let started = Instant::now();
+
+  let res = next.run(req).await;
+
+  let elapsed = started.elapsed();
+  let threshold = res
+    .extensions()
+    .get::<SlaThreshold>()
+    .copied()
+    .unwrap_or(default);
+  if elapsed > threshold.0 {
+    tracing::warn!(
+      %method,
+      path,
+      route,
+      status = res.status().as_u16(),
+      duration_ms = elapsed.as_millis() as u64,
+      threshold_ms = threshold.0.as_millis() as u64,
+      "SLA_BREACH"
+    );
+  }
+  res
+}
+
+/// Per-route SLA override for [`sla_breach`]. Attach with `route_layer`:
+///
+/// ```rust,ignore
+/// .route(
+///   "/reports",
+///   get(controller::report).route_layer(middleware::from_fn_with_state(
+///     SlaThreshold::from_millis(5_000),
+///     middlewares::sla_override,
+///   )),
+/// )
+/// ```
+pub async fn sla_override(
+  State(threshold): State<SlaThreshold>,
+  req: Request,
+  next: Next,
+) -> Response {
+  let mut res = next.run(req).await;
+  res.extensions_mut().insert(threshold);
+  res
+}
+
+fn is_excluded(path: &str) -> bool {
+  SLA_EXCLUDED_PATHS.iter().any(|prefix| {
+    path
+      .strip_prefix(prefix)
+      .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Body, middleware, routing::get};
+  use tower::ServiceExt;
+
+  #[test]
+  fn excludes_health_and_metrics_paths() {
+    assert!(is_excluded("/health"));
+    assert!(is_excluded("/health/ready"));
+    assert!(is_excluded("/metrics"));
+    assert!(!is_excluded("/healthcheck-report"));
+    assert!(!is_excluded("/users/me"));
+  }
+
+  #[tokio::test]
+  async fn route_override_is_attached_to_response() {
+    let threshold = SlaThreshold::from_millis(5_000);
+    let app: Router = Router::new()
+      .route(
+        "/slow",
+        get(|| async {}).route_layer(middleware::from_fn_with_state(threshold, sla_override)),
+      )
+      .route("/fast", get(|| async {}));
+
+    let slow = app
+      .clone()
+      .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    let fast = app
+      .oneshot(Request::get("/fast").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(slow.extensions().get::<SlaThreshold>(), Some(&threshold));
+    assert_eq!(fast.extensions().get::<SlaThreshold>(), None);
+  }
+}
@@ -0,0 +1,107 @@
+use super::route_or_path;
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::Response,
+};
+use std::{
+  hash::{DefaultHasher, Hash, Hasher},
+  time::Instant,
+};
+
+/// Fraction of requests traced in full (`TRACE_SAMPLE_RATE`, `0.0` to `1.0`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceSampleRate(pub f64);
+
+impl TraceSampleRate {
+  /// Whether the request with `request_id` is traced. Decided from a hash of
+  /// the ID rather than at random, so a request retried with the same
+  /// `x-request-id` gets the same decision.
+  pub fn samples(
+    &self,
+    request_id: &str,
+  ) -> bool {
+    if self.0 >= 1.0 {
+      return true;
+    }
+    let mut hasher = DefaultHasher::new();
+    request_id.hash(&mut hasher);
+    (hasher.finish() as f64 / u64::MAX as f64) < self.0
+  }
+}
+
+/// Set by [`sample_trace`] on requests that are not traced.
+#[derive(Clone, Copy, Debug)]
+struct Unsampled;
+
+/// Whether [`sample_trace`] picked this request for a full trace; `true` when
+/// the middleware is not installed.
+pub fn is_sampled<B>(req: &Request<B>) -> bool {
+  req.extensions().get::<Unsampled>().is_none()
+}
+
+/// Head-based trace sampling: decides once, as the request comes in, whether
+/// it gets a `REQUEST` span and the trace layer's events.
+///
+/// Sampled requests pass straight through. For the others the trace layer
+/// creates no span (see [`is_sampled`]), and this middleware writes the
+/// access log line itself: the same `ON_RESPONSE` event, with the method,
+/// route template and request ID as fields instead of span context. Must sit
+/// inside `SetRequestIdLayer` and outside the trace layer.
+pub async fn sample_trace(
+  State(rate): State<TraceSampleRate>,
+  mut req: Request,
+  next: Next,
+) -> Response {
+  let request_id = req
+    .headers()
+    .get("x-request-id")
+    .and_then(|v| v.to_str().ok())
+    .unwrap_or("unknown")
+    .to_string();
+  if rate.samples(&request_id) {
+    return next.run(req).await;
+  }
+
+  req.extensions_mut().insert(Unsampled);
+  let method = req.method().clone();
+  let route = route_or_path(&req).to_string();
+  let started = Instant::now();
+
+  let res = next.run(req).await;
+
+  tracing::info!(
+    %method,
+    route,
+    request_id,
+    status = res.status().as_u16(),
+    latency_ms = started.elapsed().as_millis() as u64,
+    "ON_RESPONSE"
+  );
+  res
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample_rate_bounds_and_proportion() {
+    let ids: Vec<String> = (0..10_000).map(|i| format!("req-{i}")).collect();
+    let sampled = |rate: f64| {
+      ids
+        .iter()
+        .filter(|id| TraceSampleRate(rate).samples(id))
+        .count()
+    };
+    assert_eq!(sampled(1.0), ids.len());
+    assert_eq!(sampled(0.0), 0);
+    let one_percent = sampled(0.01);
+    assert!((50..=150).contains(&one_percent), "{one_percent}");
+    assert_eq!(
+      TraceSampleRate(0.5).samples("abc"),
+      TraceSampleRate(0.5).samples("abc")
+    );
+  }
+}
@@ -0,0 +1,73 @@
+use axum::{
+  extract::Request,
+  http::{StatusCode, Uri, header::LOCATION},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+/// Redirects `/users/` to `/users` with `308 Permanent Redirect`.
+///
+/// `308` (unlike `301`/`302`) requires clients to repeat the same method and
+/// body, so a `POST /users/` is re-sent as `POST /users` rather than turned
+/// into a body-less `GET`. Some older clients and proxies still mishandle it
+/// and the body is sent twice, which is why rewriting is the default mode
+/// (see `TrailingSlash` in `models::environment`).
+///
+/// The target is always a path on this host: leading slashes are collapsed.
+pub async fn redirect_trailing_slash(
+  req: Request,
+  next: Next,
+) -> Response {
+  let path = req.uri().path();
+  if path.len() > 1 && path.ends_with('/') {
+    // Leading slashes collapse to one: `//evil.com` (or `/\evil.com`, which
+    // browsers read the same way) would be a protocol-relative redirect to
+    // another host.
+    let trimmed = path.trim_end_matches('/').trim_start_matches(['/', '\\']);
+    let trimmed = format!("/{trimmed}");
+    let location = match req.uri().query() {
+      Some(query) => format!("{trimmed}?{query}"),
+      None => trimmed,
+    };
+    if location.parse::<Uri>().is_ok() {
+      return (StatusCode::PERMANENT_REDIRECT, [(LOCATION, location)]).into_response();
+    }
+  }
+  next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Body, middleware, routing::get};
+  use tower::ServiceExt;
+
+  async fn location(path: &str) -> Option<String> {
+    let app = Router::new()
+      .route("/users", get(|| async {}))
+      .layer(middleware::from_fn(redirect_trailing_slash));
+    let res = app
+      .oneshot(Request::get(path).body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+    res
+      .headers()
+      .get(LOCATION)
+      .map(|v| v.to_str().unwrap().to_string())
+  }
+
+  #[tokio::test]
+  async fn redirects_to_the_trimmed_path_with_query() {
+    assert_eq!(
+      location("/users/?page=2").await.as_deref(),
+      Some("/users?page=2")
+    );
+    assert_eq!(location("/users").await, None);
+  }
+
+  #[tokio::test]
+  async fn never_redirects_to_another_host() {
+    assert_eq!(location("//evil.com/").await.as_deref(), Some("/evil.com"));
+    assert_eq!(location("/\\evil.com/").await.as_deref(), Some("/evil.com"));
+  }
+}
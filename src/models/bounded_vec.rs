@@ -0,0 +1,157 @@
+//! Array bodies with a hard item limit enforced while parsing.
+
+use serde::{
+  Deserialize, Deserializer, Serialize,
+  de::{Error, SeqAccess, Visitor},
+};
+use std::{fmt, marker::PhantomData, ops::Deref};
+use validator::{Validate, ValidationErrors};
+
+/// A `Vec<T>` that fails to deserialize once the input holds more than `MAX`
+/// items.
+///
+/// A byte limit alone does not bound memory for array bodies: a few MiB of
+/// `[{},{},...]` expands into millions of structs before validation could
+/// reject them. This stops reading at item `MAX + 1`, and never reserves more
+/// than `MAX` slots whatever length the input claims. With [`BodyJson`] an
+/// over-limit body is rejected with `422` (`ERR046`) naming the limit:
+///
+/// ```rust,ignore
+/// async fn import(
+///   BodyJson(items): BodyJson<BoundedVec<NewProperty, 1000>>,
+/// ) -> Result<impl IntoResponse, HttpError>
+/// ```
+///
+/// Items are validated like a `Vec<T>` field.
+///
+/// [`BodyJson`]: crate::extractors::BodyJson
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct BoundedVec<T, const MAX: usize>(Vec<T>);
+
+impl<T, const MAX: usize> BoundedVec<T, MAX> {
+  pub fn into_inner(self) -> Vec<T> {
+    self.0
+  }
+}
+
+impl<T, const MAX: usize> Deref for BoundedVec<T, MAX> {
+  type Target = [T];
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<T, const MAX: usize> IntoIterator for BoundedVec<T, MAX> {
+  type Item = T;
+  type IntoIter = std::vec::IntoIter<T>;
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.into_iter()
+  }
+}
+
+impl<T: Validate, const MAX: usize> Validate for BoundedVec<T, MAX> {
+  fn validate(&self) -> Result<(), ValidationErrors> {
+    self.0.validate()
+  }
+}
+
+impl<'de, T: Deserialize<'de>, const MAX: usize> Deserialize<'de> for BoundedVec<T, MAX> {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_seq(BoundedVisitor::<T, MAX>(PhantomData))
+  }
+}
+
+/// Prefix of the over-limit deserialize error, which [`BodyJson`] looks for to
+/// answer `ERR046` instead of a generic malformed-body `ERR033`.
+///
+/// [`BodyJson`]: crate::extractors::BodyJson
+pub(crate) const TOO_MANY_ITEMS: &str = "TOO_MANY_ITEMS: ";
+
+struct BoundedVisitor<T, const MAX: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const MAX: usize> Visitor<'de> for BoundedVisitor<T, MAX> {
+  type Value = BoundedVec<T, MAX>;
+
+  fn expecting(
+    &self,
+    f: &mut fmt::Formatter,
+  ) -> fmt::Result {
+    write!(f, "an array of at most {MAX} items")
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(
+    self,
+    mut seq: A,
+  ) -> Result<Self::Value, A::Error> {
+    let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(MAX));
+    while let Some(item) = seq.next_element()? {
+      if items.len() == MAX {
+        return Err(A::Error::custom(format_args!(
+          "{TOO_MANY_ITEMS}at most {MAX} items"
+        )));
+      }
+      items.push(item);
+    }
+    Ok(BoundedVec(items))
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::extractors::BodyJson;
+  use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{StatusCode, header},
+    routing::post,
+  };
+  use tower::ServiceExt;
+
+  #[derive(Deserialize, Validate)]
+  struct Item {
+    #[validate(range(min = 0))]
+    id: i64,
+  }
+
+  async fn post_items(body: &str) -> (StatusCode, String) {
+    let app: Router = Router::new().route(
+      "/items",
+      post(
+        |BodyJson(items): BodyJson<BoundedVec<Item, 3>>| async move {
+          items.iter().map(|item| item.id).sum::<i64>().to_string()
+        },
+      ),
+    );
+    let req = Request::post("/items")
+      .header(header::CONTENT_TYPE, "application/json")
+      .body(Body::from(body.to_string()))
+      .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let status = res.status();
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    (status, String::from_utf8_lossy(&body).into_owned())
+  }
+
+  #[tokio::test]
+  async fn accepts_at_limit_and_rejects_over_limit() {
+    let (status, body) = post_items(r#"[{"id":1},{"id":2},{"id":3}]"#).await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "6"));
+
+    let (status, body) = post_items(r#"[{"id":1},{"id":2},{"id":3},{"id":4}]"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.contains("ERR046|"), "{body}");
+    assert!(body.contains("at most 3 items"), "{body}");
+
+    let (status, _) = post_items(r#"[{"id":1},"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = post_items(r#"[{"id":-1}]"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  }
+}
@@ -0,0 +1,210 @@
+//! Mapping Rust enums to status columns without a proc macro.
+//!
+//! Each variant has one database spelling, listed once in
+//! [`DbEnum::VARIANTS`]; `as_db_str`/`from_db_str` are derived from that
+//! table, and the diesel impls are a few lines of copy-paste that delegate to
+//! them. A value the enum does not know is a deserialization error
+//! ([`UnknownVariant`]), never a silent default.
+//!
+//! # SQLite (`TEXT` + `CHECK`)
+//!
+//! ```sql
+//! status TEXT NOT NULL CHECK (status IN ('draft', 'active', 'sold'))
+//! ```
+//!
+//! The column is `Text` in `schema.rs`; [`PropertyStatus`] below is the
+//! template: derive `AsExpression` and `FromSqlRow` with
+//! `sql_type = Text`, implement [`DbEnum`], then copy its `ToSql`/`FromSql`
+//! impls and change the type name.
+//!
+//! # Postgres (native enum)
+//!
+//! ```sql
+//! CREATE TYPE property_status AS ENUM ('draft', 'active', 'sold');
+//! ```
+//!
+//! `diesel print-schema` emits a marker type for the enum; declare it by hand
+//! when writing the schema yourself. Postgres sends enum values as their label
+//! text in the binary protocol, so the impls read and write the same strings:
+//!
+//! ```rust,ignore
+//! #[derive(diesel::sql_types::SqlType, diesel::query_builder::QueryId)]
+//! #[diesel(postgres_type(name = "property_status"))]
+//! pub struct PropertyStatusType;
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+//! #[diesel(sql_type = PropertyStatusType)]
+//! pub enum PropertyStatus { Draft, Active, Sold }
+//!
+//! impl ToSql<PropertyStatusType, Pg> for PropertyStatus {
+//!   fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+//!     out.write_all(self.as_db_str().as_bytes())?;
+//!     Ok(IsNull::No)
+//!   }
+//! }
+//!
+//! impl FromSql<PropertyStatusType, Pg> for PropertyStatus {
+//!   fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+//!     Ok(Self::from_db_str(std::str::from_utf8(value.as_bytes())?)?)
+//!   }
+//! }
+//! ```
+//!
+//! The Postgres round trip is tested in `services::pg_types` against
+//! `TEST_POSTGRES_URL`.
+
+use diesel::{
+  deserialize::{self, FromSql, FromSqlRow},
+  expression::AsExpression,
+  serialize::{self, IsNull, Output, ToSql},
+  sql_types::Text,
+  sqlite::{Sqlite, SqliteValue},
+};
+
+/// A database value that matches none of the enum's variants.
+#[derive(Debug, thiserror::Error)]
+#[error("DB_ENUM_UNKNOWN_VARIANT: {value:?} is not a valid {name}")]
+pub struct UnknownVariant {
+  pub name: &'static str,
+  pub value: String,
+}
+
+/// An enum stored as one of a fixed set of strings.
+pub trait DbEnum: Copy + PartialEq + 'static {
+  /// Used in [`UnknownVariant`] messages.
+  const NAME: &'static str;
+  /// Every variant with its database spelling.
+  const VARIANTS: &'static [(Self, &'static str)];
+
+  fn as_db_str(self) -> &'static str {
+    Self::VARIANTS
+      .iter()
+      .find(|(variant, _)| *variant == self)
+      .map(|(_, value)| *value)
+      .expect("DB_ENUM_VARIANT_UNLISTED")
+  }
+
+  fn from_db_str(value: &str) -> Result<Self, UnknownVariant> {
+    Self::VARIANTS
+      .iter()
+      .find(|(_, spelling)| *spelling == value)
+      .map(|(variant, _)| *variant)
+      .ok_or_else(|| UnknownVariant {
+        name: Self::NAME,
+        value: value.to_string(),
+      })
+  }
+}
+
+/// Listing state of a property; the reference implementation for SQLite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum PropertyStatus {
+  Draft,
+  Active,
+  Sold,
+}
+
+impl DbEnum for PropertyStatus {
+  const NAME: &'static str = "PropertyStatus";
+  const VARIANTS: &'static [(Self, &'static str)] = &[
+    (Self::Draft, "draft"),
+    (Self::Active, "active"),
+    (Self::Sold, "sold"),
+  ];
+}
+
+impl ToSql<Text, Sqlite> for PropertyStatus {
+  fn to_sql<'b>(
+    &'b self,
+    out: &mut Output<'b, '_, Sqlite>,
+  ) -> serialize::Result {
+    out.set_value(self.as_db_str());
+    Ok(IsNull::No)
+  }
+}
+
+impl FromSql<Text, Sqlite> for PropertyStatus {
+  fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+    let text = <String as FromSql<Text, Sqlite>>::from_sql(value)?;
+    Ok(Self::from_db_str(&text)?)
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use diesel::{connection::SimpleConnection, dsl::sql, prelude::*};
+
+  diesel::table! {
+    properties (id) {
+      id -> Integer,
+      status -> Text,
+    }
+  }
+
+  #[test]
+  fn round_trips_through_sqlite_text() {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn
+      .batch_execute(
+        "CREATE TABLE properties (
+           id INTEGER PRIMARY KEY,
+           status TEXT NOT NULL CHECK (status IN ('draft', 'active', 'sold'))
+         );",
+      )
+      .unwrap();
+
+    for (id, (status, _)) in (1..).zip(PropertyStatus::VARIANTS) {
+      diesel::insert_into(properties::table)
+        .values((properties::id.eq(id), properties::status.eq(status)))
+        .execute(&mut conn)
+        .unwrap();
+    }
+    let stored: Vec<String> = properties::table
+      .select(sql::<Text>("status"))
+      .order(properties::id)
+      .load(&mut conn)
+      .unwrap();
+    assert_eq!(stored, ["draft", "active", "sold"]);
+
+    let sold: Vec<i32> = properties::table
+      .filter(properties::status.eq(PropertyStatus::Sold))
+      .select(properties::id)
+      .load(&mut conn)
+      .unwrap();
+    assert_eq!(sold, [3]);
+
+    let loaded: Vec<PropertyStatus> = properties::table
+      .select(properties::status)
+      .order(properties::id)
+      .load(&mut conn)
+      .unwrap();
+    assert_eq!(
+      loaded,
+      [
+        PropertyStatus::Draft,
+        PropertyStatus::Active,
+        PropertyStatus::Sold
+      ]
+    );
+  }
+
+  #[test]
+  fn unknown_values_fail_to_load() {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    let err = diesel::select(sql::<Text>("'rented'"))
+      .get_result::<PropertyStatus>(&mut conn)
+      .unwrap_err();
+    // Diesel wraps the FromSql error (and its Display) differently across
+    // versions, so look for it in the source chain.
+    let unknown = std::iter::successors(Some(&err as &(dyn std::error::Error + 'static)), |e| {
+      e.source()
+    })
+    .find_map(|e| e.downcast_ref::<UnknownVariant>())
+    .expect("UnknownVariant in the error chain");
+    assert_eq!(unknown.name, "PropertyStatus");
+    assert_eq!(unknown.value, "rented");
+  }
+}
@@ -1,4 +1,8 @@
-use crate::services::DBSqlite;
+use crate::services::{Cache, DBSqlite, Metrics, SessionStore};
+use crate::utils::Secret;
+use crate::utils::runtime_config::RuntimeConfig;
+use std::collections::HashMap;
+use tokio::sync::watch;
 
 /// Deployment environment the application is running in.
 #[derive(Clone, Debug)]
@@ -37,23 +41,264 @@ impl std::str::FromStr for AppEnv {
   }
 }
 
+/// How requests with a trailing slash (`/users/`) are matched to routes (`/users`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailingSlash {
+  /// Strip the slash before routing; the client never sees a redirect. Default.
+  Rewrite,
+  /// Answer `308 Permanent Redirect` to the slash-less URL.
+  Redirect,
+  /// Leave paths untouched; `/users/` is a 404.
+  Off,
+}
+
+impl std::str::FromStr for TrailingSlash {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "rewrite" => Ok(TrailingSlash::Rewrite),
+      "redirect" => Ok(TrailingSlash::Redirect),
+      "off" => Ok(TrailingSlash::Off),
+      _ => Err(format!("INVALID_TRAILING_SLASH {}", s)),
+    }
+  }
+}
+
+/// Values of the security headers added to every response; `None` leaves the
+/// header out.
+///
+/// Each is read from its own variable, where `off` disables the header, and
+/// otherwise defaults per [`AppEnv`] (see [`SecurityHeadersConfig::for_env`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityHeadersConfig {
+  /// `X-Content-Type-Options` (`X_CONTENT_TYPE_OPTIONS`).
+  pub content_type_options: Option<String>,
+  /// `X-Frame-Options` (`X_FRAME_OPTIONS`).
+  pub frame_options: Option<String>,
+  /// `Referrer-Policy` (`REFERRER_POLICY`).
+  pub referrer_policy: Option<String>,
+  /// `Content-Security-Policy` (`CONTENT_SECURITY_POLICY`).
+  pub content_security_policy: Option<String>,
+  /// `Strict-Transport-Security` (`HSTS`); only sent on requests that
+  /// arrived over HTTPS.
+  pub hsts: Option<String>,
+}
+
+impl SecurityHeadersConfig {
+  /// Defaults, stricter towards production:
+  ///
+  /// - All environments: `X-Content-Type-Options: nosniff` and
+  ///   `X-Frame-Options: DENY`.
+  /// - Local: `Referrer-Policy: strict-origin-when-cross-origin`; no CSP or
+  ///   HSTS, so dev tools and plain `http://` work.
+  /// - Staging: as local, plus a CSP limited to `'self'` that still lets
+  ///   Swagger UI load (inline styles, `data:` images) and a one-day HSTS.
+  /// - Production: `Referrer-Policy: no-referrer`, a CSP of `default-src
+  ///   'self'` without framing, plugins or `<base>`, and a one-year HSTS
+  ///   including subdomains.
+  pub fn for_env(mode: &AppEnv) -> Self {
+    let (referrer_policy, content_security_policy, hsts) = match mode {
+      AppEnv::Local => ("strict-origin-when-cross-origin", None, None),
+      AppEnv::Staging => (
+        "strict-origin-when-cross-origin",
+        Some(
+          "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline'; frame-ancestors 'none'",
+        ),
+        Some("max-age=86400"),
+      ),
+      AppEnv::Production => (
+        "no-referrer",
+        Some("default-src 'self'; frame-ancestors 'none'; object-src 'none'; base-uri 'none'"),
+        Some("max-age=31536000; includeSubDomains"),
+      ),
+    };
+    Self {
+      content_type_options: Some("nosniff".to_string()),
+      frame_options: Some("DENY".to_string()),
+      referrer_policy: Some(referrer_policy.to_string()),
+      content_security_policy: content_security_policy.map(str::to_string),
+      hsts: hsts.map(str::to_string),
+    }
+  }
+}
+
+/// `SameSite` attribute of the session cookie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+  /// Never sent on cross-site requests, including links from other sites.
+  Strict,
+  /// Sent on top-level navigations from other sites, not on embedded or
+  /// `fetch` requests. Default.
+  Lax,
+  /// Always sent; browsers require `Secure` with it.
+  None,
+}
+
+impl SameSite {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SameSite::Strict => "Strict",
+      SameSite::Lax => "Lax",
+      SameSite::None => "None",
+    }
+  }
+}
+
+impl std::str::FromStr for SameSite {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "strict" => Ok(SameSite::Strict),
+      "lax" => Ok(SameSite::Lax),
+      "none" => Ok(SameSite::None),
+      _ => Err(format!("INVALID_SAME_SITE {}", s)),
+    }
+  }
+}
+
+/// Server-side session settings, used by `SessionStore` and the `Session`
+/// extractor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionConfig {
+  /// Seconds a session survives without a request (`SESSION_IDLE_TIMEOUT_SECS`).
+  pub idle_timeout_secs: u64,
+  /// `HttpOnly` on the cookie, hiding it from scripts (`SESSION_COOKIE_HTTP_ONLY`).
+  pub http_only: bool,
+  /// `Secure` on the cookie, so it is only sent over HTTPS (`SESSION_COOKIE_SECURE`).
+  pub secure: bool,
+  /// `SameSite` on the cookie (`SESSION_COOKIE_SAMESITE`).
+  pub same_site: SameSite,
+}
+
+impl SessionConfig {
+  /// Defaults: a 30-minute idle timeout, `HttpOnly` and `SameSite=Lax`
+  /// everywhere, and `Secure` outside local, where plain `http://` is used.
+  pub fn for_env(mode: &AppEnv) -> Self {
+    Self {
+      idle_timeout_secs: 30 * 60,
+      http_only: true,
+      secure: !matches!(mode, AppEnv::Local),
+      same_site: SameSite::Lax,
+    }
+  }
+}
+
 /// Runtime configuration loaded from environment variables at startup.
 #[derive(Debug, Clone)]
 pub struct Environment {
   /// Active deployment environment (local / staging / production).
   pub mode: AppEnv,
   /// JWT signing secret.
-  pub secret: String,
+  pub secret: Secret<String>,
   /// TCP port the HTTP server listens on.
   pub port: u16,
+  /// Unix socket path to listen on instead of `port` (`BIND_UDS`); Unix only.
+  pub bind_uds: Option<String>,
   /// Database connection URL.
-  pub database_url: String,
+  pub database_url: Secret<String>,
+  /// Separate database for reporting queries (`ANALYTICS_DATABASE_URL`); see [`AppState::analytics_db`].
+  pub analytics_database_url: Option<Secret<String>>,
   /// Request timeout in seconds.
   pub timeout: u64,
+  /// Maximum requests handled concurrently; excess requests get `503`.
+  pub max_concurrency: usize,
+  /// Requests queued in front of the rate limiter (`REQUEST_BUFFER`); when
+  /// full, new requests get `503` instead of waiting.
+  pub request_buffer: usize,
+  /// Pool usage (percent of max size) at which readiness reports `degraded`.
+  pub health_pool_degraded_pct: u8,
+  /// Seconds between metrics gauge samples.
+  pub metrics_interval_secs: u64,
+  /// Most request header fields accepted (`MAX_HEADER_COUNT`); more get `431`.
+  pub max_header_count: usize,
+  /// Most bytes across request header names and values (`MAX_HEADER_BYTES`); more get `431`.
+  pub max_header_bytes: usize,
+  /// Most bytes a gzip request body may inflate to (`MAX_DECOMPRESSED_BYTES`); more get `413`.
+  pub max_decompressed_bytes: usize,
+  /// Default response-time SLA in milliseconds; slower requests log `SLA_BREACH`.
+  pub sla_ms: u64,
+  /// Fraction of requests traced in full (`TRACE_SAMPLE_RATE`, default `0.01`
+  /// in production, `1.0` elsewhere); the rest only get the access log line.
+  pub trace_sample_rate: f64,
+  /// Connect timeout for outbound HTTP in seconds (`HTTP_CONNECT_TIMEOUT`).
+  pub http_connect_timeout_secs: u64,
+  /// Timeout per outbound HTTP attempt, body included, in seconds (`HTTP_REQUEST_TIMEOUT`).
+  pub http_request_timeout_secs: u64,
+  /// Proxy for outbound `https://` calls (`HTTPS_PROXY`); may contain credentials.
+  pub https_proxy: Option<Secret<String>>,
+  /// Hosts that bypass `https_proxy` (`NO_PROXY`, comma-separated).
+  pub no_proxy: Option<String>,
+  /// Trailing-slash handling applied before routing.
+  pub trailing_slash: TrailingSlash,
+  /// Security headers added to every response.
+  pub security_headers: SecurityHeadersConfig,
   /// Allowed CORS origins.
   pub cors_origins: Vec<String>,
   /// Directory where log files are written.
   pub log_dir: String,
+  /// Log filter directives (`RUST_LOG`), resolved like every other setting;
+  /// `None` (or invalid directives) uses `info` in production, `debug` elsewhere.
+  pub log_filter: Option<String>,
+  /// Directory where `POST /admin/backup` writes database copies.
+  pub backup_dir: String,
+  /// File the cache is saved to on shutdown and reloaded from on startup (`CACHE_SNAPSHOT_PATH`).
+  pub cache_snapshot_path: Option<String>,
+  /// Process RSS above which the cache is shrunk (`CACHE_MEMORY_LIMIT_MB`); no monitor when unset.
+  pub cache_memory_limit_mb: Option<u64>,
+  /// Entries the cache is shrunk to when over `cache_memory_limit_mb` (`CACHE_LOW_WATER_ENTRIES`).
+  pub cache_low_water_entries: usize,
+  /// Token required by admin endpoints (`X-Admin-Token`); admin routes are disabled when unset.
+  pub admin_token: Option<Secret<String>>,
+  /// Static keys accepted by the `ApiKey` extractor (`API_KEYS`, comma-separated); several allow rotation.
+  pub api_keys: Vec<Secret<String>>,
+  /// HMAC secret per webhook source (`WEBHOOK_SECRETS`, `source:secret,...`); other sources get `404`.
+  pub webhook_secrets: Vec<(String, Secret<String>)>,
+  /// Shrink the DB acquire timeout as the pool fills (`DB_ADAPTIVE_ACQUIRE`).
+  pub db_adaptive_acquire: bool,
+  /// Ping idle DB connections every N seconds instead of testing each one on
+  /// checkout (`DB_PRE_PING_SECS`); `None` keeps the checkout test.
+  pub db_pre_ping_secs: Option<u64>,
+  /// Session idle timeout and cookie flags (`SESSION_*`), defaulting per mode.
+  pub session: SessionConfig,
+  /// Log every SQL statement at debug level (`LOG_SQL`); always `false` outside local mode.
+  pub log_sql: bool,
+  /// Serve Swagger UI at `/docs` and the spec at `/openapi.json` (`API_DOCS`).
+  pub api_docs: bool,
+}
+
+impl Environment {
+  /// Local-mode settings for tests: every variable at its default (as
+  /// [`load_environment_with`](crate::config::load_environment_with) resolves
+  /// it), a fixed strong `SECRET` and an in-memory SQLite `DATABASE_URL`. The
+  /// process environment, `.env` and `constant.toml` are not read. Override
+  /// what a test needs with struct update syntax:
+  ///
+  /// ```rust
+  /// use axum_starter::models::Environment;
+  ///
+  /// let env = Environment {
+  ///   port: 0,
+  ///   ..Environment::for_test()
+  /// };
+  /// assert_eq!(env.port, 0);
+  /// ```
+  pub fn for_test() -> Self {
+    let env = [
+      ("APP_ENV", "local"),
+      ("SECRET", "q8ZtVn3Yw1KpR6xLc2HsJ9dFb4MgT7Ae0UoWiNy5"),
+      ("DATABASE_URL", ":memory:"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    crate::config::load_environment_with(&crate::config::Resolver::new(
+      env,
+      HashMap::new(),
+      HashMap::new(),
+    ))
+  }
 }
 
 /// Shared application state injected into every handler via Axum's `State` extractor.
@@ -63,4 +308,21 @@ pub struct AppState {
   pub env: Environment,
   /// SQLite database connection pool.
   pub db: DBSqlite,
+  /// Pool for the analytics database, when `ANALYTICS_DATABASE_URL` is set.
+  ///
+  /// Nothing is routed here automatically: a report handler that should not
+  /// load the primary reads from this pool explicitly, and decides itself
+  /// whether to fall back to `db` when it is `None`. No migrations are run
+  /// against it.
+  pub analytics_db: Option<DBSqlite>,
+  /// Shared in-memory cache; in the running app this is `Cache::global()`.
+  pub cache: Cache,
+  /// Server-side sessions, on a cache of their own so that evicting or
+  /// snapshotting `cache` never touches them.
+  pub sessions: SessionStore,
+  /// Gauges refreshed by the metrics sampler.
+  pub metrics: Metrics,
+  /// Settings reloaded on `SIGHUP`; subsystems follow them through
+  /// `subscribe()` (see `utils::runtime_config`).
+  pub runtime_config: watch::Sender<RuntimeConfig>,
 }
@@ -1,5 +1,9 @@
+pub mod bounded_vec;
+pub mod db_enum;
 pub mod environment;
 pub mod pagination;
+pub mod serde_helpers;
 
+pub use bounded_vec::BoundedVec;
 pub use environment::*;
 pub use pagination::*;
@@ -41,8 +41,11 @@ impl<T: ToSchema> PaginatedResponse<T> {
 
 /// Query parameters for paginated list endpoints.
 /// Defaults to page 1, limit 10; limit is capped at 100.
+///
+/// Extract it with [`Pagination`](crate::extractors::Pagination), which
+/// rejects out-of-range values instead of clamping them.
 #[derive(Debug, Clone, Deserialize, IntoParams)]
-#[serde(rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
 pub struct PaginationQuery {
   /// Page number to fetch (1-based, default: 1).
   #[param(default = 1)]
@@ -52,7 +55,16 @@ pub struct PaginationQuery {
   pub limit: u32,
 }
 
+impl Default for PaginationQuery {
+  fn default() -> Self {
+    Self { page: 1, limit: 10 }
+  }
+}
+
 impl PaginationQuery {
+  /// Largest `limit` a client may ask for.
+  pub const MAX_LIMIT: u32 = 100;
+
   /// Returns the offset for SQL queries: `(page - 1) * limit`.
   pub fn offset(&self) -> i64 {
     let page = self.page.max(1);
@@ -60,8 +72,8 @@ impl PaginationQuery {
     ((page - 1) * limit) as i64
   }
 
-  /// Returns the effective limit, capped at 100.
+  /// Returns the effective limit, capped at [`PaginationQuery::MAX_LIMIT`].
   pub fn effective_limit(&self) -> u32 {
-    self.limit.clamp(1, 100)
+    self.limit.clamp(1, Self::MAX_LIMIT)
   }
 }
@@ -0,0 +1,245 @@
+//! Serialization policy for dates and numbers in API DTOs.
+//!
+//! Database types serialize inconsistently by default: a diesel `Timestamp`
+//! becomes a naive `2024-01-01T12:00:00` without offset, `Text` columns return
+//! whatever was written (`to_rfc3339()` gives `+00:00`, SQLite's
+//! `CURRENT_TIMESTAMP` gives `2024-01-01 12:00:00`), and decimals come out as
+//! JSON numbers that clients parse into floats. Response DTOs therefore use
+//! these `#[serde(with = "...")]` modules:
+//!
+//! - **Timestamps** are RFC 3339 in UTC with a `Z` suffix and only as many
+//!   fractional digits as needed: `2024-01-01T12:00:00Z`,
+//!   `2024-01-01T12:00:00.250Z`.
+//!   - [`rfc3339`] for `DateTime<Utc>`;
+//!   - [`naive_utc`] for `NaiveDateTime` (diesel `Timestamp`), taken as UTC;
+//!   - [`timestamp_text`] for timestamps stored in `Text` columns.
+//! - **Decimals** are JSON strings (`"12.50"`) so no precision is lost to
+//!   IEEE floats; see [`decimal_string`].
+//!
+//! Deserializing accepts any RFC 3339 offset (converted to UTC) but only
+//! strings for decimals.
+//!
+//! PATCH DTOs use [`nullable`] for columns that may be cleared; see
+//! [`crate::extractors::Patch`].
+//!
+//! ```rust
+//! use axum_starter::models::serde_helpers;
+//! use chrono::{DateTime, Utc};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Invoice {
+//!   #[serde(with = "serde_helpers::rfc3339")]
+//!   issued_at: DateTime<Utc>,
+//!   #[serde(with = "serde_helpers::decimal_string")]
+//!   total: u64,
+//! }
+//! ```
+
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serializer, de::Error};
+use std::{fmt::Display, str::FromStr};
+
+/// Formats `value` per the policy above.
+pub fn format_timestamp(value: &DateTime<Utc>) -> String {
+  value.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Parses RFC 3339 with any offset, or a naive `YYYY-MM-DD[ T]HH:MM:SS[.f]`
+/// (as written by SQLite's `CURRENT_TIMESTAMP`) taken as UTC.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+  if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+    return Some(parsed.with_timezone(&Utc));
+  }
+  ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .map(|naive| naive.and_utc())
+}
+
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(
+  deserializer: D
+) -> Result<DateTime<Utc>, D::Error> {
+  let raw = String::deserialize(deserializer)?;
+  parse_timestamp(&raw).ok_or_else(|| D::Error::custom(format!("INVALID_TIMESTAMP: {raw}")))
+}
+
+/// `DateTime<Utc>` as RFC 3339 UTC.
+pub mod rfc3339 {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(
+    value: &DateTime<Utc>,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_timestamp(value))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D
+  ) -> Result<DateTime<Utc>, D::Error> {
+    deserialize_timestamp(deserializer)
+  }
+}
+
+/// `NaiveDateTime` (diesel `Timestamp`) as RFC 3339, taking the value as UTC.
+pub mod naive_utc {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(
+    value: &NaiveDateTime,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_timestamp(&value.and_utc()))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D
+  ) -> Result<NaiveDateTime, D::Error> {
+    deserialize_timestamp(deserializer).map(|t| t.naive_utc())
+  }
+}
+
+/// A timestamp kept as `String` (SQLite `Text` column), normalized to RFC
+/// 3339 UTC on the way out. Text that is not a timestamp fails serialization
+/// rather than leaking an inconsistent format.
+pub mod timestamp_text {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(
+    value: &str,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    let parsed = parse_timestamp(value)
+      .ok_or_else(|| serde::ser::Error::custom(format!("INVALID_TIMESTAMP: {value}")))?;
+    serializer.serialize_str(&format_timestamp(&parsed))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    deserialize_timestamp(deserializer).map(|t| format_timestamp(&t))
+  }
+}
+
+/// Decimals (`BigDecimal` for diesel `Numeric`, or any `Display + FromStr`
+/// number) as a JSON string. JSON numbers are refused when deserializing: by
+/// the time serde sees one it may already have been rounded through `f64`.
+pub mod decimal_string {
+  use super::*;
+
+  pub fn serialize<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+  }
+
+  pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+  where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+  {
+    let raw = String::deserialize(deserializer)?;
+    raw
+      .trim()
+      .parse()
+      .map_err(|e| D::Error::custom(format!("INVALID_DECIMAL: {raw}: {e}")))
+  }
+}
+
+/// Keeps "absent" and "null" apart for JSON Merge Patch fields:
+/// `#[serde(default, deserialize_with = "serde_helpers::nullable")]` on an
+/// `Option<Option<T>>` gives `None` when the key is missing (`default`),
+/// `Some(None)` for `null` and `Some(Some(v))` for a value. Without it serde
+/// folds `null` into the outer `None`.
+pub fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+  T: Deserialize<'de>,
+  D: Deserializer<'de>,
+{
+  Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+  use serde::Serialize;
+  use serde_json::json;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Dto {
+    #[serde(with = "rfc3339")]
+    at: DateTime<Utc>,
+    #[serde(with = "naive_utc")]
+    naive: NaiveDateTime,
+    #[serde(with = "timestamp_text")]
+    text: String,
+    #[serde(with = "decimal_string")]
+    amount: u128,
+  }
+
+  #[test]
+  fn round_trips_in_canonical_form() {
+    let at = Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap();
+    let dto = Dto {
+      at: at + chrono::Duration::milliseconds(250),
+      naive: at.naive_utc(),
+      text: "2024-03-31 01:30:00".to_string(),
+      // Past 2^53: a JSON number would be rounded by most clients.
+      amount: 9_007_199_254_740_993,
+    };
+
+    let value = serde_json::to_value(&dto).unwrap();
+    assert_eq!(
+      value,
+      json!({
+        "at": "2024-03-31T01:30:00.250Z",
+        "naive": "2024-03-31T01:30:00Z",
+        "text": "2024-03-31T01:30:00Z",
+        "amount": "9007199254740993",
+      })
+    );
+
+    let back: Dto = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(back.at, dto.at);
+    assert_eq!(back.naive, dto.naive);
+    assert_eq!(back.amount, dto.amount);
+    assert_eq!(serde_json::to_value(&back).unwrap(), value);
+  }
+
+  #[test]
+  fn offsets_are_converted_to_utc() {
+    let value = json!({
+      "at": "2024-03-31T03:30:00+02:00",
+      "naive": "2024-03-31T01:30:00Z",
+      "text": "2024-03-31T03:30:00+02:00",
+      "amount": "1",
+    });
+    let dto: Dto = serde_json::from_value(value).unwrap();
+    assert_eq!(format_timestamp(&dto.at), "2024-03-31T01:30:00Z");
+    assert_eq!(dto.text, "2024-03-31T01:30:00Z");
+  }
+
+  #[test]
+  fn rejects_numbers_and_garbage() {
+    let base = json!({
+      "at": "2024-01-01T00:00:00Z",
+      "naive": "2024-01-01T00:00:00Z",
+      "text": "2024-01-01T00:00:00Z",
+      "amount": "1",
+    });
+    let with = |key: &str, v: serde_json::Value| {
+      let mut value = base.clone();
+      value[key] = v;
+      serde_json::from_value::<Dto>(value)
+    };
+    assert!(with("amount", json!(1)).is_err());
+    assert!(with("amount", json!("1.5")).is_err());
+    assert!(with("at", json!("yesterday")).is_err());
+
+    #[derive(Serialize)]
+    struct Text(#[serde(with = "timestamp_text")] String);
+    assert!(serde_json::to_string(&Text("soon".to_string())).is_err());
+  }
+}
@@ -0,0 +1,54 @@
+use super::{model::BackupResponse, service};
+use crate::utils::export::csv_response;
+use crate::{
+  extractors::AdminToken,
+  models::AppState,
+  services::{HttpError, HttpErrorFormat, HttpResponse, HttpResponseFormat},
+};
+use axum::{
+  extract::State,
+  response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/admin/backup",
+    tag = "admin",
+    params(("x-admin-token" = String, Header, description = "Value of `ADMIN_TOKEN`")),
+    responses(
+        (status = 201, description = "Backup written", body = HttpResponseFormat<BackupResponse>),
+        (status = 403, description = "Missing or wrong admin token", body = HttpErrorFormat,
+            example = json!({"success": false, "message": "ERR044|Access to this resource is forbidden"})
+        ),
+        (status = 404, description = "Admin endpoints disabled (`ADMIN_TOKEN` unset)", body = HttpErrorFormat),
+        (status = 500, description = "Backup target not writable or SQLite failed", body = HttpErrorFormat)
+    )
+)]
+/// — write an online copy of the SQLite database into `BACKUP_DIR` and return its size.
+pub async fn backup(
+  State(state): State<Arc<AppState>>,
+  _admin: AdminToken,
+) -> Result<impl IntoResponse, HttpError> {
+  let backup = service::backup(&state).await?;
+  Ok(HttpResponse::created(backup, "BACKUP_CREATED"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/export/users",
+    tag = "admin",
+    params(("x-admin-token" = String, Header, description = "Value of `ADMIN_TOKEN`")),
+    responses(
+        (status = 200, description = "CSV of all users (`id,email,username,created_at`), streamed", content_type = "text/csv", body = String),
+        (status = 403, description = "Missing or wrong admin token", body = HttpErrorFormat),
+        (status = 404, description = "Admin endpoints disabled (`ADMIN_TOKEN` unset)", body = HttpErrorFormat)
+    )
+)]
+/// — download every user as CSV, streamed row by row instead of buffered.
+pub async fn export_users(
+  State(state): State<Arc<AppState>>,
+  _admin: AdminToken,
+) -> Response {
+  csv_response("users.csv", service::export_users(&state))
+}
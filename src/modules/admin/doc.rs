@@ -0,0 +1,15 @@
+use utoipa::{OpenApi, openapi};
+
+use super::{controller, model::BackupResponse};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(controller::backup, controller::export_users),
+    components(schemas(BackupResponse)),
+    tags((name = "admin", description = "Operational endpoints, enabled by `ADMIN_TOKEN`")),
+)]
+pub struct AdminApiDoc;
+
+pub fn build() -> openapi::OpenApi {
+  AdminApiDoc::openapi()
+}
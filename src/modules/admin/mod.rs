@@ -0,0 +1,14 @@
+pub mod controller;
+pub mod doc;
+pub mod model;
+pub mod service;
+
+use crate::models::AppState;
+use crate::modules::route_table::RouteTable;
+use std::sync::Arc;
+
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .post("/admin/backup", controller::backup)
+    .get("/admin/export/users", controller::export_users)
+}
@@ -0,0 +1,25 @@
+use crate::schemas::table::users;
+use diesel::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Database copy written by `POST /admin/backup`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResponse {
+  /// Path of the backup file on the server.
+  pub path: String,
+  /// Size of the backup file in bytes.
+  pub size_bytes: u64,
+}
+
+/// One line of the `GET /admin/export/users` CSV; the password hash is never selected.
+#[derive(Debug, Clone, Queryable, Selectable, Serialize)]
+#[diesel(table_name = users)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct UserExportRow {
+  pub id: String,
+  pub email: String,
+  pub username: String,
+  pub created_at: String,
+}
@@ -0,0 +1,44 @@
+use super::model::{BackupResponse, UserExportRow};
+use crate::{models::AppState, schemas::table::users, services::RowStream};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{connection::DefaultLoadingMode, prelude::*};
+use std::path::Path;
+
+/// Rows buffered between the query and the response body.
+const EXPORT_BUFFER_ROWS: usize = 256;
+
+/// Backs up the database into `BACKUP_DIR` as `backup-<UTC timestamp>.db`.
+pub async fn backup(state: &AppState) -> Result<BackupResponse> {
+  let file_name = format!("backup-{}.db", Utc::now().format("%Y%m%dT%H%M%S%.3fZ"));
+  let path = Path::new(&state.env.backup_dir).join(file_name);
+
+  state.db.backup_to(&path).await?;
+  let size_bytes = std::fs::metadata(&path)?.len();
+
+  Ok(BackupResponse {
+    path: path.to_string_lossy().into_owned(),
+    size_bytes,
+  })
+}
+
+/// Streams every user, oldest first, reading one row at a time from SQLite.
+///
+/// The connection is held until the client has read the whole export or
+/// disconnected.
+pub fn export_users(state: &AppState) -> RowStream<UserExportRow> {
+  let db = state.db.clone();
+  RowStream::spawn_blocking(EXPORT_BUFFER_ROWS, move |tx| {
+    let mut conn = db.get_connection()?;
+    let rows = users::table
+      .order(users::created_at.asc())
+      .select(UserExportRow::as_select())
+      .load_iter::<UserExportRow, DefaultLoadingMode>(&mut conn)?;
+    for row in rows {
+      if !tx.send(row?) {
+        break;
+      }
+    }
+    Ok(())
+  })
+}
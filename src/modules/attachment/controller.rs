@@ -4,16 +4,13 @@ use super::{
 };
 use crate::{
   constants::ALLOWED_MIME_TYPES,
-  extractors::{AuthUser, BodyJson, MultipartForm, PathParam},
-  models::{AppState, PaginatedResponse, PaginationQuery},
-  services::{HttpError, HttpErrorFormat, HttpResponse, HttpResponseFormat},
-  utils::{files, string::slugify_filename},
+  extractors::{AuthUser, MultipartForm, Pagination, Patch, PathParam},
+  models::{AppState, PaginatedResponse},
+  services::{Created, HttpError, HttpErrorFormat, HttpResponse, HttpResponseFormat},
+  utils::{file_types, files, string::slugify_filename, upload},
 };
-use axum::{
-  extract::{Query, State},
-  response::IntoResponse,
-};
-use std::{path::Path as FsPath, sync::Arc};
+use axum::{extract::State, response::IntoResponse};
+use std::sync::Arc;
 
 #[utoipa::path(
     post,
@@ -22,27 +19,29 @@ use std::{path::Path as FsPath, sync::Arc};
     security(("bearer_token" = [])),
     request_body(content_type = "multipart/form-data", content = inline(AttachmentUploadForm)),
     responses(
-        (status = 201, description = "File uploaded successfully", body = HttpResponseFormat<AttachmentResponse>),
+        (status = 201, description = "File uploaded successfully", body = HttpResponseFormat<AttachmentResponse>,
+            headers(("Location" = String, description = "Path of the new attachment, e.g. `/attachments/42`"))
+        ),
         (status = 400, description = "Invalid file or missing file", body = HttpErrorFormat,
             examples(
-                ("NO_FILE_PROVIDED" = (value = json!({"success": false, "message": "ERR024|NO_FILE_PROVIDED"}))),
-                ("EMPTY_FILE" = (value = json!({"success": false, "message": "ERR025|EMPTY_FILE"}))),
-                ("INVALID_FILE_TYPE" = (value = json!({"success": false, "message": "ERR026|INVALID_FILE_TYPE:allowed=image/jpeg, image/png, image/webp"}))),
-                ("INVALID_FILENAME" = (value = json!({"success": false, "message": "ERR027|INVALID_FILENAME"}))),
-                ("FILE_TOO_LARGE" = (value = json!({"success": false, "message": "ERR031|FILE_TOO_LARGE:max=10mb"}))),
-                ("INVALID_MULTIPART_DATA" = (value = json!({"success": false, "message": "ERR035|INVALID_MULTIPART_DATA:detail"})))
+                ("NO_FILE_PROVIDED" = (value = json!({"success": false, "message": "ERR024|No file was provided"}))),
+                ("EMPTY_FILE" = (value = json!({"success": false, "message": "ERR025|File is empty"}))),
+                ("INVALID_FILE_TYPE" = (value = json!({"success": false, "message": "ERR026|File type is not allowed:allowed=image/jpeg, image/png, image/webp"}))),
+                ("INVALID_FILENAME" = (value = json!({"success": false, "message": "ERR027|Filename is invalid"}))),
+                ("FILE_TOO_LARGE" = (value = json!({"success": false, "message": "ERR031|File is too large:max=10mb"}))),
+                ("INVALID_MULTIPART_DATA" = (value = json!({"success": false, "message": "ERR035|Invalid multipart data:detail"})))
             )
         ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         ),
         (status = 409, description = "File already exists", body = HttpErrorFormat,
             examples(
-                ("FILE_ALREADY_EXISTS" = (value = json!({"success": false, "message": "ERR029|FILE_ALREADY_EXISTS"})))
+                ("FILE_ALREADY_EXISTS" = (value = json!({"success": false, "message": "ERR029|File already exists"})))
             )
         )
     )
@@ -67,11 +66,9 @@ pub async fn upload(
     )));
   }
 
-  // Sanitize filename: strip directory components (path traversal) then slugify
-  let base_filename = FsPath::new(&file.filename)
-    .file_name()
-    .and_then(|n| n.to_str())
-    .ok_or(HttpError::ERR027)?;
+  // Sanitize filename: strip directory components, reject traversal, then slugify
+  let base_filename = upload::sanitize_filename(&file.filename).map_err(|_| HttpError::ERR027)?;
+  file_types::validate_file(base_filename)?;
   let sanitized_filename = slugify_filename(base_filename);
 
   let mime_type = file.content_type.clone();
@@ -94,10 +91,8 @@ pub async fn upload(
 
   let attachment = service::create(&state.db, new_attachment).await?;
 
-  Ok(HttpResponse::created(
-    AttachmentResponse::from(attachment),
-    "FILE_UPLOADED",
-  ))
+  let location = format!("/attachments/{}", attachment.id);
+  Ok(Created::new(location, AttachmentResponse::from(attachment)).message("FILE_UPLOADED"))
 }
 
 #[utoipa::path(
@@ -107,15 +102,18 @@ pub async fn upload(
     security(("bearer_token" = [])),
     params(
         ("page" = Option<u32>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u32>, Query, description = "Items per page (default: 20, max: 100)")
+        ("limit" = Option<u32>, Query, description = "Items per page (default: 10, max: 100)")
     ),
     responses(
         (status = 200, description = "Paginated list of user's attachments", body = HttpResponseFormat<PaginatedResponse<AttachmentResponse>>),
+        (status = 400, description = "Invalid `page` or `limit`", body = HttpErrorFormat,
+            example = json!({"success": false, "message": "ERR041|Invalid query parameter:limit must be a whole number from 1 to 100"})
+        ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         )
     )
@@ -124,7 +122,7 @@ pub async fn upload(
 pub async fn list(
   State(state): State<Arc<AppState>>,
   auth: AuthUser,
-  Query(pagination): Query<PaginationQuery>,
+  Pagination(pagination): Pagination,
 ) -> Result<impl IntoResponse, HttpError> {
   let result = service::find_by_user(&state.db, auth.user_id, pagination).await?;
   Ok(HttpResponse::ok(result, "OK"))
@@ -142,19 +140,19 @@ pub async fn list(
         (status = 200, description = "Attachment details", body = HttpResponseFormat<AttachmentResponse>),
         (status = 400, description = "Invalid path parameter", body = HttpErrorFormat,
             examples(
-                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|INVALID_PATH_PARAM:id"})))
+                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|Invalid path parameter:id"})))
             )
         ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         ),
         (status = 404, description = "Attachment not found", body = HttpErrorFormat,
             examples(
-                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|ATTACHMENT_NOT_FOUND"})))
+                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|Attachment not found"})))
             )
         )
     )
@@ -185,23 +183,27 @@ pub async fn get_by_id(
     request_body = UpdateAttachmentRequest,
     responses(
         (status = 200, description = "Attachment updated", body = HttpResponseFormat<AttachmentResponse>),
-        (status = 400, description = "Validation error", body = HttpErrorFormat,
+        (status = 400, description = "Invalid path parameter or body", body = HttpErrorFormat,
+            examples(
+                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|Invalid path parameter:id"}))),
+                ("INVALID_BODY_REQUEST" = (value = json!({"success": false, "message": "ERR033|Invalid request body:detail"})))
+            )
+        ),
+        (status = 422, description = "Validation error", body = HttpErrorFormat,
             examples(
-                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|INVALID_PATH_PARAM:id"}))),
-                ("INVALID_BODY_REQUEST" = (value = json!({"success": false, "message": "ERR033|INVALID_BODY_REQUEST:detail"}))),
-                ("INVALID_VALIDATION" = (value = json!({"success": false, "message": "ERR034|INVALID_VALIDATION:field|rule|message"})))
+                ("INVALID_VALIDATION" = (value = json!({"success": false, "message": "ERR034|Validation failed:field|rule|message"})))
             )
         ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         ),
         (status = 404, description = "Attachment not found", body = HttpErrorFormat,
             examples(
-                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|ATTACHMENT_NOT_FOUND"})))
+                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|Attachment not found"})))
             )
         )
     )
@@ -211,7 +213,7 @@ pub async fn update(
   State(state): State<Arc<AppState>>,
   auth: AuthUser,
   PathParam(id): PathParam<i32>,
-  BodyJson(body): BodyJson<UpdateAttachmentRequest>,
+  Patch(body): Patch<UpdateAttachmentRequest>,
 ) -> Result<impl IntoResponse, HttpError> {
   let attachment = service::update(&state.db, id, auth.user_id, body).await?;
 
@@ -233,19 +235,19 @@ pub async fn update(
         (status = 200, description = "Attachment deleted", body = HttpResponseFormat<AttachmentResponse>),
         (status = 400, description = "Invalid path parameter", body = HttpErrorFormat,
             examples(
-                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|INVALID_PATH_PARAM:id"})))
+                ("INVALID_PATH_PARAM" = (value = json!({"success": false, "message": "ERR032|Invalid path parameter:id"})))
             )
         ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         ),
         (status = 404, description = "Attachment not found", body = HttpErrorFormat,
             examples(
-                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|ATTACHMENT_NOT_FOUND"})))
+                ("ATTACHMENT_NOT_FOUND" = (value = json!({"success": false, "message": "ERR023|Attachment not found"})))
             )
         )
     )
@@ -4,18 +4,24 @@ pub mod model;
 pub mod repository;
 pub mod service;
 
+use crate::middlewares::{SlaThreshold, sla_override};
 use crate::models::AppState;
-use axum::{
-  Router,
-  routing::{delete, get, patch, post},
-};
+use crate::modules::route_table::RouteTable;
+use axum::{http::Method, middleware, routing::post};
 use std::sync::Arc;
 
-pub fn routes() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/attachments/upload", post(controller::upload))
-    .route("/attachments", get(controller::list))
-    .route("/attachments/{id}", get(controller::get_by_id))
-    .route("/attachments/{id}", patch(controller::update))
-    .route("/attachments/{id}", delete(controller::delete))
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .route(
+      Method::POST,
+      "/attachments/upload",
+      post(controller::upload).route_layer(middleware::from_fn_with_state(
+        SlaThreshold::from_millis(10_000),
+        sla_override,
+      )),
+    )
+    .get("/attachments", controller::list)
+    .get("/attachments/{id}", controller::get_by_id)
+    .patch("/attachments/{id}", controller::update)
+    .delete("/attachments/{id}", controller::delete)
 }
@@ -1,4 +1,4 @@
-use crate::schemas::table::attachments;
+use crate::{models::serde_helpers, schemas::table::attachments};
 use chrono::Utc;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -93,9 +93,13 @@ pub struct AttachmentResponse {
   pub mime_type: String,
   /// File size in bytes.
   pub size: i32,
-  /// ISO-8601 creation timestamp.
+  /// Creation timestamp, RFC 3339 UTC.
+  #[serde(with = "serde_helpers::timestamp_text")]
+  #[schema(format = DateTime)]
   pub created_at: String,
-  /// ISO-8601 last-updated timestamp.
+  /// Last-updated timestamp, RFC 3339 UTC.
+  #[serde(with = "serde_helpers::timestamp_text")]
+  #[schema(format = DateTime)]
   pub updated_at: String,
 }
 
@@ -114,7 +118,8 @@ impl From<Attachment> for AttachmentResponse {
   }
 }
 
-/// Request body for `PATCH /attachments/{id}` — all fields are optional.
+/// Request body for `PATCH /attachments/{id}` (JSON Merge Patch, see
+/// [`crate::extractors::Patch`]) — all fields are optional.
 #[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateAttachmentRequest {
@@ -122,3 +127,13 @@ pub struct UpdateAttachmentRequest {
   #[validate(length(min = 1, message = "Filename cannot be empty"))]
   pub filename: Option<String>,
 }
+
+/// Columns written by `PATCH /attachments/{id}`; `None` fields are left as is.
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = attachments)]
+pub struct AttachmentChangeset {
+  /// New filename, if supplied.
+  pub filename: Option<String>,
+  /// Always set, so the changeset is never empty.
+  pub updated_at: String,
+}
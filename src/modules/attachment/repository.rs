@@ -1,4 +1,4 @@
-use super::model::{Attachment, NewAttachment, UpdateAttachmentRequest};
+use super::model::{Attachment, AttachmentChangeset, NewAttachment, UpdateAttachmentRequest};
 use crate::{schemas::table::attachments, services::DBSqlite};
 use diesel::prelude::*;
 
@@ -114,20 +114,13 @@ pub async fn update(
       .filter(attachments::id.eq(id))
       .filter(attachments::user_id.eq(&user_id));
 
-    if let Some(filename) = &req.filename {
-      diesel::update(target)
-        .set((
-          attachments::filename.eq(filename),
-          attachments::updated_at.eq(&now),
-        ))
-        .execute(conn)
-        .map_err(|e| anyhow::anyhow!("DB_ERROR: {}", e))?;
-    } else {
-      diesel::update(target)
-        .set(attachments::updated_at.eq(&now))
-        .execute(conn)
-        .map_err(|e| anyhow::anyhow!("DB_ERROR: {}", e))?;
-    }
+    diesel::update(target)
+      .set(&AttachmentChangeset {
+        filename: req.filename,
+        updated_at: now,
+      })
+      .execute(conn)
+      .map_err(|e| anyhow::anyhow!("DB_ERROR: {}", e))?;
 
     attachments::table
       .filter(attachments::id.eq(id))
@@ -18,12 +18,12 @@ use std::sync::Arc;
     request_body = RegisterRequest,
     responses(
         (status = 201, description = "User registered successfully", body = HttpResponseFormat<AuthTokensResponse>),
-        (status = 400, description = "Validation error", body = HttpErrorFormat, examples(
-        ("INVALID_VALIDATION" = (value = json!({"success": false, "message": "ERR034|INVALID_VALIDATION:password|length|Password must be at least 8 characters|value=\"string\"|min=8"})))
+        (status = 422, description = "Validation error", body = HttpErrorFormat, examples(
+        ("INVALID_VALIDATION" = (value = json!({"success": false, "message": "ERR034|Validation failed:password|length|Password must be at least 8 characters|value=\"string\"|min=8"})))
         )),
         (status = 409, description = "Email already exists", body = HttpErrorFormat,
         examples(
-        ("EMAIL_ALREADY_EXISTS" = (value = json!({"success": false, "message": "ERR010|EMAIL_ALREADY_EXISTS"})))
+        ("EMAIL_ALREADY_EXISTS" = (value = json!({"success": false, "message": "ERR010|Email is already registered"})))
         ))
     )
 )]
@@ -35,7 +35,11 @@ pub async fn register(
   let (user, refresh_token) =
     service::register(&state.db, body.email, body.username, body.password).await?;
 
-  let tokens = service::build_tokens(&user, &refresh_token, state.env.secret.as_bytes())?;
+  let tokens = service::build_tokens(
+    &user,
+    &refresh_token,
+    state.env.secret.expose_secret().as_bytes(),
+  )?;
 
   Ok(HttpResponse::created(tokens, "REGISTERED"))
 }
@@ -49,7 +53,7 @@ pub async fn register(
         (status = 200, description = "Login successful", body = HttpResponseFormat<AuthTokensResponse>),
         (status = 401, description = "Invalid credentials", body = HttpErrorFormat,
             examples(
-                ("INVALID_CREDENTIALS" = (value = json!({"success": false, "message": "ERR013|INVALID_CREDENTIALS"})))
+                ("INVALID_CREDENTIALS" = (value = json!({"success": false, "message": "ERR013|Invalid email or password"})))
             )
         )
     )
@@ -61,7 +65,11 @@ pub async fn login(
 ) -> http_error::Result<impl IntoResponse> {
   let (user, refresh_token) = service::login(&state.db, body.email, body.password).await?;
 
-  let tokens = service::build_tokens(&user, &refresh_token, state.env.secret.as_bytes())?;
+  let tokens = service::build_tokens(
+    &user,
+    &refresh_token,
+    state.env.secret.expose_secret().as_bytes(),
+  )?;
 
   Ok(HttpResponse::ok(tokens, "OK"))
 }
@@ -75,7 +83,7 @@ pub async fn login(
         (status = 200, description = "Token refreshed", body = HttpResponseFormat<AuthTokensResponse>),
         (status = 401, description = "Invalid token", body = HttpErrorFormat,
             examples(
-                ("TOKEN_INVALID" = (value = json!({"success": false, "message": "ERR014|INVALID_REFRESH_TOKEN"})))
+                ("TOKEN_INVALID" = (value = json!({"success": false, "message": "ERR014|Refresh token is invalid"})))
             )
         )
     )
@@ -90,7 +98,11 @@ pub async fn refresh(
   let user =
     crate::modules::user::service::find_by_id(&state.db, new_refresh.user_id.clone()).await?;
 
-  let tokens = service::build_tokens(&user, &new_refresh, state.env.secret.as_bytes())?;
+  let tokens = service::build_tokens(
+    &user,
+    &new_refresh,
+    state.env.secret.expose_secret().as_bytes(),
+  )?;
 
   Ok(HttpResponse::ok(tokens, "OK"))
 }
@@ -5,12 +5,12 @@ pub mod repository;
 pub mod service;
 
 use crate::models::AppState;
-use axum::{Router, routing::post};
+use crate::modules::route_table::RouteTable;
 use std::sync::Arc;
 
-pub fn routes() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/auth/register", post(controller::register))
-    .route("/auth/login", post(controller::login))
-    .route("/auth/refresh", post(controller::refresh))
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .post("/auth/register", controller::register)
+    .post("/auth/login", controller::login)
+    .post("/auth/refresh", controller::refresh)
 }
@@ -13,8 +13,11 @@ pub struct RegisterRequest {
   /// Valid email address — used as the login identifier.
   #[validate(email(message = "Must be a valid email address"))]
   pub email: String,
-  /// Display name, minimum 3 characters.
-  #[validate(length(min = 3, message = "Username must be at least 3 characters"))]
+  /// Display name, minimum 3 characters, not all whitespace.
+  #[validate(
+    length(min = 3, message = "Username must be at least 3 characters"),
+    custom(function = "crate::utils::validation::non_blank")
+  )]
   pub username: String,
   /// Plain-text password, minimum 8 characters. Hashed before storage.
   #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
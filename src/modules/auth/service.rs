@@ -38,8 +38,7 @@ pub async fn register(
   password: String,
 ) -> Result<(User, RefreshToken), HttpError> {
   if user_service::find_by_email(db, &user_email)
-    .await
-    .map_err(HttpError::from)?
+    .await?
     .is_some()
   {
     return Err(HttpError::ERR010);
@@ -57,9 +56,7 @@ pub async fn register(
     updated_at: now.to_rfc3339(),
   };
 
-  let user = user_service::create(db, new_user)
-    .await
-    .map_err(HttpError::from)?;
+  let user = user_service::create(db, new_user).await?;
   let refresh = repository::insert(db, new_refresh_token_record(&user.id))
     .await
     .map_err(HttpError::from)?;
@@ -74,8 +71,7 @@ pub async fn login(
   password: String,
 ) -> Result<(User, RefreshToken), HttpError> {
   let user = user_service::find_by_email(db, &user_email)
-    .await
-    .map_err(HttpError::from)?
+    .await?
     .ok_or(HttpError::ERR013)?;
 
   let valid = encrypt::verify(&password, &user.password).map_err(|_| HttpError::ERR013)?;
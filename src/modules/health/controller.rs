@@ -1,8 +1,9 @@
-use crate::{
-  models::AppState,
-  services::{HttpError, HttpResponse},
+use super::{
+  model::{HealthReport, HealthStatus},
+  service,
 };
-use axum::{extract::State, response::IntoResponse};
+use crate::{models::AppState, services::HttpResponse};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
 use std::sync::Arc;
 
 #[utoipa::path(
@@ -23,16 +24,40 @@ pub async fn liveness() -> impl IntoResponse {
     path = "/health/ready",
     tag = "health",
     responses(
-        (status = 200, description = "Service is ready (DB reachable)"),
-        (status = 503, description = "Service unavailable (DB unreachable)")
+        (status = 200, description = "Service is ready (`healthy`) or serving near its limits (`degraded`)", body = HealthReport,
+            example = json!({
+                "success": true,
+                "message": "DEGRADED",
+                "data": {
+                    "status": "degraded",
+                    "components": [
+                        { "name": "database", "status": "degraded", "detail": "POOL_PRESSURE in_use=28 max=32 utilization=0.88" }
+                    ]
+                }
+            })
+        ),
+        (status = 503, description = "Service unavailable (a component is down)", body = HealthReport,
+            example = json!({
+                "success": false,
+                "message": "SERVICE_UNAVAILABLE",
+                "data": {
+                    "status": "down",
+                    "components": [
+                        { "name": "database", "status": "down", "detail": "DATABASE_UNREACHABLE" }
+                    ]
+                }
+            })
+        )
     )
 )]
-/// — Kubernetes readiness probe. Returns 200 if the database is reachable, 503 otherwise.
-pub async fn readiness(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, HttpError> {
-  state
-    .db
-    .health_check()
-    .await
-    .map_err(|_| HttpError::ERR503)?;
-  Ok(HttpResponse::ok(serde_json::Value::Null, "READY"))
+/// — Kubernetes readiness probe. Returns 200 when every component is healthy or degraded
+/// (message `READY` / `DEGRADED`), 503 when any component is down; the body lists each component.
+pub async fn readiness(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+  let report = service::report(&state).await;
+  let (status, message) = match report.status {
+    HealthStatus::Healthy => (StatusCode::OK, "READY"),
+    HealthStatus::Degraded => (StatusCode::OK, "DEGRADED"),
+    HealthStatus::Down => (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE"),
+  };
+  HttpResponse::new(message, status, Some(report))
 }
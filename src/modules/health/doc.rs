@@ -1,6 +1,9 @@
 use utoipa::{OpenApi, openapi};
 
-use super::controller;
+use super::{
+  controller,
+  model::{ComponentHealth, HealthReport, HealthStatus},
+};
 
 #[derive(utoipa::ToSchema)]
 pub struct HealthResponse {
@@ -11,7 +14,7 @@ pub struct HealthResponse {
 #[derive(OpenApi)]
 #[openapi(
     paths(controller::liveness, controller::readiness),
-    components(schemas(HealthResponse)),
+    components(schemas(HealthResponse, HealthReport, ComponentHealth, HealthStatus)),
     tags((name = "health", description = "Health check endpoints")),
 )]
 pub struct HealthApiDoc;
@@ -1,12 +1,14 @@
 pub mod controller;
 pub mod doc;
+pub mod model;
+pub mod service;
 
 use crate::models::AppState;
-use axum::{Router, routing::get};
+use crate::modules::route_table::RouteTable;
 use std::sync::Arc;
 
-pub fn routes() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/health/live", get(controller::liveness))
-    .route("/health/ready", get(controller::readiness))
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .get("/health/live", controller::liveness)
+    .get("/health/ready", controller::readiness)
 }
@@ -0,0 +1,94 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// State of a single health component or of the service as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+  /// Working normally.
+  Healthy,
+  /// Serving traffic but close to a limit; readiness still returns `200`.
+  Degraded,
+  /// Not able to serve traffic; readiness returns `503`.
+  Down,
+}
+
+/// Result of one [`HealthCheck`](super::service::HealthCheck).
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentHealth {
+  /// Component identifier, e.g. `"database"`.
+  pub name: String,
+  /// Component status.
+  pub status: HealthStatus,
+  /// Why the component is degraded or down (e.g. pool usage); omitted when healthy.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub detail: Option<String>,
+  /// What was checked, e.g. `sqlite://data/app.db`; passwords are never included.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub target: Option<String>,
+}
+
+impl ComponentHealth {
+  pub fn with_target(
+    mut self,
+    target: impl ToString,
+  ) -> Self {
+    self.target = Some(target.to_string());
+    self
+  }
+
+  pub fn healthy(name: &str) -> Self {
+    Self {
+      name: name.to_string(),
+      status: HealthStatus::Healthy,
+      detail: None,
+      target: None,
+    }
+  }
+
+  pub fn degraded(
+    name: &str,
+    detail: impl Into<String>,
+  ) -> Self {
+    Self {
+      name: name.to_string(),
+      status: HealthStatus::Degraded,
+      detail: Some(detail.into()),
+      target: None,
+    }
+  }
+
+  pub fn down(
+    name: &str,
+    detail: impl Into<String>,
+  ) -> Self {
+    Self {
+      name: name.to_string(),
+      status: HealthStatus::Down,
+      detail: Some(detail.into()),
+      target: None,
+    }
+  }
+}
+
+/// Aggregated readiness report returned by `/health/ready`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+  /// Worst status across all components.
+  pub status: HealthStatus,
+  /// Per-component results.
+  pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+  pub fn from_components(components: Vec<ComponentHealth>) -> Self {
+    let status = components
+      .iter()
+      .map(|c| c.status)
+      .max()
+      .unwrap_or(HealthStatus::Healthy);
+    Self { status, components }
+  }
+}
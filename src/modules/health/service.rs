@@ -0,0 +1,89 @@
+use super::model::{ComponentHealth, HealthReport};
+use crate::{
+  models::AppState,
+  services::{DBSqlite, DbUrl},
+};
+use std::future::Future;
+
+/// A dependency probed by the readiness endpoint.
+///
+/// Return [`HealthStatus::Degraded`](super::model::HealthStatus::Degraded)
+/// when the component still works but is near a limit, so dashboards can
+/// alert before it goes [`Down`](super::model::HealthStatus::Down).
+pub trait HealthCheck {
+  fn check(&self) -> impl Future<Output = ComponentHealth> + Send;
+}
+
+/// Database reachability plus connection-pool pressure.
+///
+/// `Down` when `SELECT 1` fails; `Degraded` when at least `degraded_pct`
+/// percent of the pool's maximum size is checked out.
+pub struct DatabaseCheck<'a> {
+  /// Component name in the report (`database`, `analytics_database`).
+  pub name: &'static str,
+  pub db: &'a DBSqlite,
+  pub degraded_pct: u8,
+  /// Reported as the component's `target`.
+  pub url: Option<DbUrl>,
+}
+
+impl HealthCheck for DatabaseCheck<'_> {
+  async fn check(&self) -> ComponentHealth {
+    let health = self.probe().await;
+    match &self.url {
+      Some(url) => health.with_target(url),
+      None => health,
+    }
+  }
+}
+
+impl DatabaseCheck<'_> {
+  async fn probe(&self) -> ComponentHealth {
+    let name = self.name;
+
+    if let Err(e) = self.db.health_check().await {
+      tracing::error!(component = name, error = %e, "HEALTH_DATABASE_DOWN");
+      return ComponentHealth::down(name, "DATABASE_UNREACHABLE");
+    }
+
+    let (total, idle) = self.db.pool_stats();
+    let in_use = total.saturating_sub(idle);
+    let max = self.db.pool_max_size();
+    if u64::from(in_use) * 100 >= u64::from(max) * u64::from(self.degraded_pct) {
+      let utilization = self.db.pool_utilization();
+      return ComponentHealth::degraded(
+        name,
+        format!("POOL_PRESSURE in_use={in_use} max={max} utilization={utilization:.2}"),
+      );
+    }
+
+    ComponentHealth::healthy(name)
+  }
+}
+
+/// Runs every readiness check and aggregates the worst status. The analytics
+/// database is only checked when one is configured.
+pub async fn report(state: &AppState) -> HealthReport {
+  let degraded_pct = state.env.health_pool_degraded_pct;
+  let database = DatabaseCheck {
+    name: "database",
+    db: &state.db,
+    degraded_pct,
+    url: DbUrl::parse(state.env.database_url.expose_secret()).ok(),
+  };
+  let mut components = vec![database.check().await];
+  if let Some(db) = &state.analytics_db {
+    let analytics = DatabaseCheck {
+      name: "analytics_database",
+      db,
+      degraded_pct,
+      url: state
+        .env
+        .analytics_database_url
+        .as_ref()
+        .and_then(|url| DbUrl::parse(url.expose_secret()).ok()),
+    };
+    components.push(analytics.check().await);
+  }
+  HealthReport::from_components(components)
+}
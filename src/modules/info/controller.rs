@@ -0,0 +1,60 @@
+use super::model::AppInfo;
+use crate::{
+  models::AppState,
+  services::{HttpResponse, HttpResponseFormat},
+};
+use axum::{
+  extract::{Request, State},
+  http::header,
+  response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+/// Landing page served to browsers on `/`.
+const LANDING_PAGE: &str = "public/index.html";
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "info",
+    responses(
+        (status = 200, description = "Service name, version and environment", body = HttpResponseFormat<AppInfo>,
+            example = json!({
+                "success": true,
+                "message": "OK",
+                "data": { "name": "axum-starter", "version": "0.1.0", "env": "production", "docs": "/docs" }
+            })
+        )
+    )
+)]
+/// — service identity for uptime checks and API clients. Unauthenticated and cheap (no DB).
+/// Browsers (`Accept: text/html`) get the landing page from `public/index.html` instead.
+pub async fn root(
+  State(state): State<Arc<AppState>>,
+  req: Request,
+) -> Response {
+  if wants_html(&req) {
+    return match ServeFile::new(LANDING_PAGE).oneshot(req).await {
+      Ok(page) => page.into_response(),
+      Err(never) => match never {},
+    };
+  }
+
+  let info = AppInfo {
+    name: env!("CARGO_PKG_NAME"),
+    version: env!("CARGO_PKG_VERSION"),
+    env: state.env.mode.to_string(),
+    docs: state.env.api_docs.then_some("/docs"),
+  };
+  HttpResponse::ok(info, "OK").into_response()
+}
+
+fn wants_html(req: &Request) -> bool {
+  req
+    .headers()
+    .get(header::ACCEPT)
+    .and_then(|v| v.to_str().ok())
+    .is_some_and(|accept| accept.contains("text/html"))
+}
@@ -0,0 +1,15 @@
+use utoipa::{OpenApi, openapi};
+
+use super::{controller, model::AppInfo};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(controller::root),
+    components(schemas(AppInfo)),
+    tags((name = "info", description = "Service identity")),
+)]
+pub struct InfoApiDoc;
+
+pub fn build() -> openapi::OpenApi {
+  InfoApiDoc::openapi()
+}
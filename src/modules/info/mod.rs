@@ -0,0 +1,11 @@
+pub mod controller;
+pub mod doc;
+pub mod model;
+
+use crate::models::AppState;
+use crate::modules::route_table::RouteTable;
+use std::sync::Arc;
+
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new().get("/", controller::root)
+}
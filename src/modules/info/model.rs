@@ -0,0 +1,19 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Service identity returned by `GET /`.
+///
+/// Built only from compile-time constants and the loaded environment, so it
+/// never touches the database or cache.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AppInfo {
+  /// Crate name, e.g. `"axum-starter"`.
+  pub name: &'static str,
+  /// Crate version, e.g. `"0.1.0"`.
+  pub version: &'static str,
+  /// `APP_ENV`: `local`, `staging` or `production`.
+  pub env: String,
+  /// Path of the Swagger UI; omitted when `API_DOCS` is off.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub docs: Option<&'static str>,
+}
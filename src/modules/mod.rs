@@ -1,14 +1,21 @@
+pub mod admin;
 pub mod attachment;
 pub mod auth;
 pub mod health;
+pub mod info;
+pub mod route_table;
 pub mod user;
+pub mod webhook;
 
-use crate::models::{AppEnv, AppState};
+pub use route_table::{DuplicateRoute, RouteTable};
+
+use crate::middlewares::cors;
+use crate::models::AppState;
+use crate::services::HttpErrorFormat;
 use axum::{
   Router,
   http::StatusCode,
   response::{IntoResponse, Response},
-  routing::get,
 };
 use std::sync::Arc;
 use utoipa::{
@@ -44,6 +51,7 @@ impl utoipa::Modify for SecurityAddon {
         version = "0.1.0",
         description = "A JWT-authenticated REST API starter built with Axum + Diesel"
     ),
+    components(schemas(HttpErrorFormat)),
     modifiers(&SecurityAddon),
 )]
 struct ApiDoc;
@@ -52,41 +60,61 @@ pub struct AppRoutes;
 
 impl AppRoutes {
   /// Build and seal the router with the given AppState.
-  /// Returns a plain `Router` (state already applied) ready to pass to `axum::serve`.
-  pub fn build(state: Arc<AppState>) -> Router {
-    let api_routes = Router::new().route("/", get(Self::ping));
+  /// Returns a plain `Router` (state already applied) ready to pass to `axum::serve`,
+  /// or [`DuplicateRoute`] when two modules register the same method and path,
+  /// or the same path under different parameter names.
+  ///
+  /// # CORS
+  ///
+  /// Each route is covered by exactly one `CorsLayer`. `Router::layer` only
+  /// wraps routes added before it, so the default policy
+  /// ([`cors::restricted`]) is applied to the groups above it, and groups
+  /// merged afterwards keep the layer they attached themselves, e.g.
+  /// `widget::routes().into_router()?.layer(cors::any_origin())`. A
+  /// group-specific policy therefore always wins; there is no merging of the two. Preflight
+  /// (`OPTIONS`) requests are answered by whichever layer covers the path;
+  /// the server's [`cors::preflight`] then turns that into `204`, or `404` for
+  /// unknown paths.
+  pub fn build(state: Arc<AppState>) -> Result<Router, DuplicateRoute> {
+    let api_routes = RouteTable::new().get("/", Self::ping);
 
-    let mut router: Router<Arc<AppState>> = Router::new()
+    let mut router: Router<Arc<AppState>> = RouteTable::new()
       .nest("/api", api_routes)
+      .merge(info::routes())
       .merge(health::routes())
       .merge(auth::routes())
       .merge(user::routes())
-      .merge(attachment::routes());
+      .merge(attachment::routes())
+      .merge(admin::routes())
+      .merge(webhook::routes())
+      .into_router()?
+      .layer(cors::restricted(&state.env.cors_origins));
+    // Routes merged below this line are not covered by the default policy and
+    // must attach their own `CorsLayer` (Swagger UI is same-origin only).
 
-    // Swagger UI only in non-production environments
+    // Swagger UI + spec, unless disabled with API_DOCS (off in production by default)
     if let Some(swagger) = Self::swagger(&state) {
       router = router.merge(swagger);
     }
 
-    router.with_state(state)
+    Ok(router.with_state(state))
   }
 
   fn swagger(state: &Arc<AppState>) -> Option<Router<Arc<AppState>>> {
-    if matches!(state.env.mode, AppEnv::Production) {
+    if !state.env.api_docs {
       return None;
     }
 
     let mut doc = ApiDoc::openapi();
+    doc.merge(info::doc::build());
     doc.merge(health::doc::build());
     doc.merge(auth::doc::build());
     doc.merge(user::doc::build());
     doc.merge(attachment::doc::build());
+    doc.merge(admin::doc::build());
+    doc.merge(webhook::doc::build());
 
-    Some(
-      SwaggerUi::new("/spec")
-        .url("/spec/openapi.json", doc)
-        .into(),
-    )
+    Some(SwaggerUi::new("/docs").url("/openapi.json", doc).into())
   }
 
   pub async fn ping() -> Response {
@@ -0,0 +1,302 @@
+//! Route registration that reports duplicates instead of panicking.
+//!
+//! `Router::route` panics when a method+path pair is registered twice, and
+//! merging two routers that both define it panics the same way, with a
+//! message from deep inside axum's router. Module routers are built with
+//! [`RouteTable`] instead: it records every `(method, path)` it is given and
+//! [`RouteTable::into_router`] returns a [`DuplicateRoute`] naming the pair,
+//! so `AppRoutes::build` fails with a clear error before the server starts.
+//!
+//! Parameter names are ignored when comparing, because axum also refuses
+//! `/users/{id}` next to `/users/{user_id}`. That conflict holds across
+//! methods too: a path may carry any number of methods, but only under one
+//! spelling of its parameters.
+
+use axum::{
+  Router,
+  handler::Handler,
+  http::Method,
+  routing::{self, MethodRouter},
+};
+use std::collections::BTreeMap;
+
+/// Two registrations of the same method and path, or of one path under
+/// different parameter names.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("ROUTE_DUPLICATE: {method} {path} conflicts with {existing_method} {existing}")]
+pub struct DuplicateRoute {
+  pub method: Method,
+  /// Path of the second registration.
+  pub path: String,
+  /// Method of the first registration.
+  pub existing_method: Method,
+  /// Path of the first registration (differs only in parameter names).
+  pub existing: String,
+}
+
+/// A [`Router`] plus the `(method, path)` pairs registered on it.
+pub struct RouteTable<S = ()> {
+  router: Router<S>,
+  /// `normalized path -> (path as registered, its methods in order)`.
+  registered: BTreeMap<String, (String, Vec<Method>)>,
+  duplicate: Option<DuplicateRoute>,
+}
+
+impl<S: Clone + Send + Sync + 'static> Default for RouteTable<S> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<S: Clone + Send + Sync + 'static> RouteTable<S> {
+  pub fn new() -> Self {
+    Self {
+      router: Router::new(),
+      registered: BTreeMap::new(),
+      duplicate: None,
+    }
+  }
+
+  /// Adds `method_router` for `method` on `path`. Use this when the method
+  /// router carries its own `route_layer`s; it must serve only `method`.
+  pub fn route(
+    mut self,
+    method: Method,
+    path: &str,
+    method_router: MethodRouter<S>,
+  ) -> Self {
+    if self.record(&method, path) {
+      self.router = self.router.route(path, method_router);
+    }
+    self
+  }
+
+  pub fn get<H, T>(
+    self,
+    path: &str,
+    handler: H,
+  ) -> Self
+  where
+    H: Handler<T, S>,
+    T: 'static,
+  {
+    self.route(Method::GET, path, routing::get(handler))
+  }
+
+  pub fn post<H, T>(
+    self,
+    path: &str,
+    handler: H,
+  ) -> Self
+  where
+    H: Handler<T, S>,
+    T: 'static,
+  {
+    self.route(Method::POST, path, routing::post(handler))
+  }
+
+  pub fn put<H, T>(
+    self,
+    path: &str,
+    handler: H,
+  ) -> Self
+  where
+    H: Handler<T, S>,
+    T: 'static,
+  {
+    self.route(Method::PUT, path, routing::put(handler))
+  }
+
+  pub fn patch<H, T>(
+    self,
+    path: &str,
+    handler: H,
+  ) -> Self
+  where
+    H: Handler<T, S>,
+    T: 'static,
+  {
+    self.route(Method::PATCH, path, routing::patch(handler))
+  }
+
+  pub fn delete<H, T>(
+    self,
+    path: &str,
+    handler: H,
+  ) -> Self
+  where
+    H: Handler<T, S>,
+    T: 'static,
+  {
+    self.route(Method::DELETE, path, routing::delete(handler))
+  }
+
+  /// Adds every route of `other`, checking them against this table.
+  pub fn merge(
+    self,
+    other: RouteTable<S>,
+  ) -> Self {
+    self.combine(other, "")
+  }
+
+  /// Adds every route of `other` under `prefix` (`Router::nest`).
+  pub fn nest(
+    self,
+    prefix: &str,
+    other: RouteTable<S>,
+  ) -> Self {
+    self.combine(other, prefix)
+  }
+
+  /// The router, or the first duplicate registration found.
+  pub fn into_router(self) -> Result<Router<S>, DuplicateRoute> {
+    match self.duplicate {
+      Some(duplicate) => Err(duplicate),
+      None => Ok(self.router),
+    }
+  }
+
+  fn combine(
+    mut self,
+    other: RouteTable<S>,
+    prefix: &str,
+  ) -> Self {
+    if let Some(duplicate) = other.duplicate {
+      self.duplicate.get_or_insert(duplicate);
+      return self;
+    }
+    let mut clean = true;
+    for (path, methods) in other.registered.values() {
+      let path = join(prefix, path);
+      for method in methods {
+        clean &= self.record(method, &path);
+      }
+    }
+    // Only hand the router to axum when nothing conflicts; a conflicting
+    // merge would panic inside axum.
+    if clean {
+      self.router = match prefix {
+        "" => self.router.merge(other.router),
+        prefix => self.router.nest(prefix, other.router),
+      };
+    }
+    self
+  }
+
+  /// Records `method path`; `false` (and the first duplicate kept) when the
+  /// pair was already registered, or the path was under other parameter
+  /// names.
+  fn record(
+    &mut self,
+    method: &Method,
+    path: &str,
+  ) -> bool {
+    let (existing, methods) = self
+      .registered
+      .entry(normalize(path))
+      .or_insert_with(|| (path.to_string(), Vec::new()));
+    let conflict = if existing != path {
+      methods.first()
+    } else {
+      methods.iter().find(|m| *m == method)
+    };
+    if let Some(existing_method) = conflict {
+      self.duplicate.get_or_insert(DuplicateRoute {
+        method: method.clone(),
+        path: path.to_string(),
+        existing_method: existing_method.clone(),
+        existing: existing.clone(),
+      });
+      return false;
+    }
+    methods.push(method.clone());
+    true
+  }
+}
+
+/// `prefix` + `path` as `Router::nest` would route it.
+fn join(
+  prefix: &str,
+  path: &str,
+) -> String {
+  match (prefix.trim_end_matches('/'), path) {
+    (prefix, "/") if !prefix.is_empty() => prefix.to_string(),
+    (prefix, path) => format!("{prefix}{path}"),
+  }
+}
+
+/// Replaces parameter names with `{}` (`{*}` for wildcards).
+fn normalize(path: &str) -> String {
+  path
+    .split('/')
+    .map(|segment| match segment.strip_prefix('{') {
+      Some(rest) if rest.starts_with('*') => "{*}",
+      Some(_) => "{}",
+      None => segment,
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn ok() {}
+
+  #[test]
+  fn duplicate_route_is_an_error_not_a_panic() {
+    let err = RouteTable::<()>::new()
+      .get("/foo", ok)
+      .post("/foo", ok)
+      .get("/foo", ok)
+      .into_router()
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "ROUTE_DUPLICATE: GET /foo conflicts with GET /foo"
+    );
+  }
+
+  #[test]
+  fn duplicates_across_merged_tables_and_param_names_are_found() {
+    let users = RouteTable::<()>::new().get("/users/{id}", ok);
+    let admin = RouteTable::new().get("/users/{user_id}", ok);
+    let err = users.merge(admin).into_router().unwrap_err();
+    assert_eq!(err.path, "/users/{user_id}");
+    assert_eq!(err.existing, "/users/{id}");
+
+    let api = RouteTable::<()>::new().get("/", ok);
+    assert!(
+      RouteTable::new()
+        .get("/", ok)
+        .nest("/api", api)
+        .into_router()
+        .is_ok()
+    );
+  }
+
+  #[test]
+  fn param_names_conflict_across_methods() {
+    let err = RouteTable::<()>::new()
+      .get("/users/{id}", ok)
+      .delete("/users/{user_id}", ok)
+      .into_router()
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "ROUTE_DUPLICATE: DELETE /users/{user_id} conflicts with GET /users/{id}"
+    );
+
+    let users = RouteTable::<()>::new().get("/users/{id}", ok);
+    let admin = RouteTable::new().delete("/users/{user_id}", ok);
+    assert!(users.merge(admin).into_router().is_err());
+
+    // Same spelling, several methods, across tables: fine
+    let users = RouteTable::<()>::new()
+      .get("/users/{id}", ok)
+      .put("/users/{id}", ok);
+    let admin = RouteTable::new().delete("/users/{id}", ok);
+    assert!(users.merge(admin).into_router().is_ok());
+  }
+}
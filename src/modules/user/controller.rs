@@ -3,7 +3,7 @@ use super::{
   service,
 };
 use crate::{
-  extractors::AuthUser,
+  extractors::{AuthUser, Pagination},
   models::{AppState, PaginatedResponse},
   services::{HttpError, HttpErrorFormat, HttpResponse, HttpResponseFormat},
 };
@@ -22,9 +22,9 @@ use std::sync::Arc;
         (status = 200, description = "Current user profile", body = HttpResponseFormat<UserResponse>),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         )
     )
@@ -51,11 +51,14 @@ pub async fn get_me(
     ),
     responses(
         (status = 200, description = "Paginated list of users", body = HttpResponseFormat<PaginatedResponse<UserResponse>>),
+        (status = 400, description = "Invalid `page` or `limit`", body = HttpErrorFormat,
+            example = json!({"success": false, "message": "ERR041|Invalid query parameter:limit must be a whole number from 1 to 100"})
+        ),
         (status = 401, description = "Unauthorized", body = HttpErrorFormat,
             examples(
-                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|MISSING_OR_INVALID_AUTHORIZATION_HEADER"}))),
-                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|EXPIRED_SIGNATURE"}))),
-                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|INVALID_TOKEN"})))
+                ("MISSING_OR_INVALID_AUTHORIZATION_HEADER" = (value = json!({"success": false, "message": "ERR022|Missing or invalid Authorization header"}))),
+                ("EXPIRED_SIGNATURE" = (value = json!({"success": false, "message": "ERR019|Access token has expired"}))),
+                ("INVALID_TOKEN" = (value = json!({"success": false, "message": "ERR018|Access token is invalid"})))
             )
         )
     )
@@ -63,8 +66,9 @@ pub async fn get_me(
 /// — returns a paginated list of users with optional username filter.
 pub async fn list(
   State(state): State<Arc<AppState>>,
+  Pagination(pagination): Pagination,
   Query(query): Query<UserQuery>,
 ) -> Result<HttpResponse<PaginatedResponse<UserResponse>>, HttpError> {
-  let result = service::find_all(&state.db, query).await?;
+  let result = service::find_all(&state.db, pagination, query).await?;
   Ok(HttpResponse::ok(result, "OK"))
 }
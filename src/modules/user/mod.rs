@@ -5,11 +5,11 @@ pub mod repository;
 pub mod service;
 
 use crate::models::AppState;
-use axum::{Router, routing::get};
+use crate::modules::route_table::RouteTable;
 use std::sync::Arc;
 
-pub fn routes() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/users", get(controller::list))
-    .route("/users/me", get(controller::get_me))
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .get("/users", controller::list)
+    .get("/users/me", controller::get_me)
 }
@@ -1,4 +1,4 @@
-use crate::{models::PaginationQuery, schemas::table::users};
+use crate::{models::serde_helpers, schemas::table::users};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -51,9 +51,13 @@ pub struct UserResponse {
   pub email: String,
   /// User's display name.
   pub username: String,
-  /// ISO-8601 creation timestamp.
+  /// Creation timestamp, RFC 3339 UTC.
+  #[serde(with = "serde_helpers::timestamp_text")]
+  #[schema(format = DateTime)]
   pub created_at: String,
-  /// ISO-8601 last-updated timestamp.
+  /// Last-updated timestamp, RFC 3339 UTC.
+  #[serde(with = "serde_helpers::timestamp_text")]
+  #[schema(format = DateTime)]
   pub updated_at: String,
 }
 
@@ -69,12 +73,10 @@ impl From<User> for UserResponse {
   }
 }
 
-/// Query parameters for `GET /users` — pagination plus an optional username filter.
+/// Filter parameters for `GET /users`; `page` and `limit` come from the
+/// [`Pagination`](crate::extractors::Pagination) extractor.
 #[derive(Debug, Clone, Deserialize)]
 pub struct UserQuery {
-  /// Shared pagination fields (`page`, `limit`).
-  #[serde(flatten)]
-  pub pagination: PaginationQuery,
   /// Filter results to users whose username contains this value.
   pub username: Option<String>,
 }
@@ -3,7 +3,7 @@ use super::{
   repository,
 };
 use crate::{
-  models::PaginatedResponse,
+  models::{PaginatedResponse, PaginationQuery},
   modules::user::model::{UserQuery, UserResponse},
   services::{DBSqlite, HttpError},
   utils::to_i64,
@@ -13,7 +13,9 @@ pub async fn find_by_id(
   db: &DBSqlite,
   uid: String,
 ) -> Result<User, HttpError> {
-  repository::find_by_id(db, uid).await.map_err(HttpError::from)
+  repository::find_by_id(db, uid)
+    .await
+    .map_err(HttpError::from)
 }
 
 pub async fn find_by_email(
@@ -29,15 +31,18 @@ pub async fn create(
   db: &DBSqlite,
   new_user: NewUser,
 ) -> Result<User, HttpError> {
-  repository::insert(db, new_user).await.map_err(HttpError::from)
+  repository::insert(db, new_user)
+    .await
+    .map_err(HttpError::from)
 }
 
 pub async fn find_all(
   db: &DBSqlite,
-  query: UserQuery,
+  pagination: PaginationQuery,
+  _query: UserQuery,
 ) -> Result<PaginatedResponse<UserResponse>, HttpError> {
-  let offset = query.pagination.offset();
-  let limit = query.pagination.effective_limit();
+  let offset = pagination.offset();
+  let limit = pagination.effective_limit();
 
   let (results, total) = super::repository::find_all(db, offset, to_i64(limit))
     .await
@@ -47,8 +52,8 @@ pub async fn find_all(
 
   Ok(PaginatedResponse::new(
     items,
-    query.pagination.page,
-    query.pagination.effective_limit(),
+    pagination.page,
+    pagination.effective_limit(),
     total,
   ))
 }
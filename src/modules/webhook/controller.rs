@@ -0,0 +1,77 @@
+use super::{
+  model::{WebhookEventStatus, WebhookReceipt},
+  service,
+};
+use crate::{
+  extractors::{ApiKey, GuardedBytes, PathParam},
+  models::AppState,
+  services::{Accepted, HttpError, HttpErrorFormat, HttpResponse, HttpResponseFormat},
+};
+use axum::{extract::State, http::HeaderMap, response::IntoResponse};
+use std::sync::Arc;
+
+/// Header carrying the hex HMAC-SHA256 of the raw body, optionally `sha256=`-prefixed.
+pub const SIGNATURE_HEADER: &str = "x-signature-256";
+
+#[utoipa::path(
+    post,
+    path = "/webhooks/{source}",
+    tag = "webhooks",
+    params(
+        ("source" = String, Path, description = "Sender, as configured in `WEBHOOK_SECRETS`"),
+        ("x-signature-256" = String, Header, description = "Hex HMAC-SHA256 of the raw body under the source's secret, optionally prefixed `sha256=`")
+    ),
+    request_body(content = Object, content_type = "application/json"),
+    responses(
+        (status = 202, description = "Event recorded in the outbox; `Location` is its status URL", body = HttpResponseFormat<WebhookReceipt>,
+            headers(("location" = String, description = "`/webhooks/events/{id}`"))
+        ),
+        (status = 400, description = "Body is not JSON", body = HttpErrorFormat),
+        (status = 401, description = "Missing or wrong signature", body = HttpErrorFormat,
+            example = json!({"success": false, "message": "ERR021|Unauthorized"})
+        ),
+        (status = 404, description = "Unknown source", body = HttpErrorFormat),
+        (status = 413, description = "Body over 1 MiB", body = HttpErrorFormat),
+        (status = 415, description = "Content-Type is not application/json", body = HttpErrorFormat)
+    )
+)]
+/// — verify a third-party webhook's signature and record it as an internal event.
+pub async fn receive(
+  State(state): State<Arc<AppState>>,
+  PathParam(source): PathParam<String>,
+  headers: HeaderMap,
+  GuardedBytes(body): GuardedBytes,
+) -> Result<impl IntoResponse, HttpError> {
+  let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+  let receipt = service::receive(&state, &source, signature, &body).await?;
+  let status_url = format!("/webhooks/events/{}", receipt.event_id);
+  Ok(
+    Accepted::new(receipt)
+      .status_url(status_url)
+      .message("WEBHOOK_RECEIVED"),
+  )
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/events/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "`eventId` from the webhook receipt"),
+        ("x-api-key" = String, Header, description = "One of `API_KEYS`")
+    ),
+    responses(
+        (status = 200, description = "Delivery state of the event", body = HttpResponseFormat<WebhookEventStatus>),
+        (status = 401, description = "Missing or unknown API key", body = HttpErrorFormat),
+        (status = 404, description = "No such webhook event", body = HttpErrorFormat)
+    )
+)]
+/// — poll whether a received webhook has been relayed yet.
+pub async fn event_status(
+  State(state): State<Arc<AppState>>,
+  _key: ApiKey,
+  PathParam(event_id): PathParam<i32>,
+) -> Result<impl IntoResponse, HttpError> {
+  let status = service::event_status(&state, event_id).await?;
+  Ok(HttpResponse::ok(status, "OK"))
+}
@@ -0,0 +1,18 @@
+use utoipa::{OpenApi, openapi};
+
+use super::{
+  controller,
+  model::{WebhookEventState, WebhookEventStatus, WebhookReceipt},
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(controller::receive, controller::event_status),
+    components(schemas(WebhookReceipt, WebhookEventStatus, WebhookEventState)),
+    tags((name = "webhooks", description = "Signed events from third parties, configured by `WEBHOOK_SECRETS`")),
+)]
+pub struct WebhookApiDoc;
+
+pub fn build() -> openapi::OpenApi {
+  WebhookApiDoc::openapi()
+}
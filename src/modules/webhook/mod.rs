@@ -0,0 +1,27 @@
+pub mod controller;
+pub mod doc;
+pub mod model;
+pub mod service;
+
+use crate::extractors::BytesGuardConfig;
+use crate::models::AppState;
+use crate::modules::route_table::RouteTable;
+use axum::{Extension, http::Method, routing::post};
+use std::sync::Arc;
+
+/// Largest webhook body accepted.
+const MAX_WEBHOOK_BYTES: usize = 1024 * 1024;
+
+pub fn routes() -> RouteTable<Arc<AppState>> {
+  RouteTable::new()
+    .route(
+      Method::POST,
+      "/webhooks/{source}",
+      post(controller::receive).route_layer(Extension(
+        BytesGuardConfig::new()
+          .max_size(MAX_WEBHOOK_BYTES)
+          .allowed_content_types(vec!["application/json".to_string()]),
+      )),
+    )
+    .get("/webhooks/events/{id}", controller::event_status)
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Result of `POST /webhooks/{source}`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookReceipt {
+  /// Outbox id of the recorded event.
+  pub event_id: i32,
+  /// Event type it was recorded as, `webhook.<source>`.
+  pub event_type: String,
+}
+
+/// Delivery state of a recorded webhook event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEventState {
+  /// Waiting in the outbox for the relay.
+  Pending,
+  /// Handed to the event sink.
+  Published,
+}
+
+/// Result of `GET /webhooks/events/{id}`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEventStatus {
+  pub event_id: i32,
+  pub event_type: String,
+  pub state: WebhookEventState,
+  /// When the relay published it (RFC 3339); `null` while pending.
+  pub published_at: Option<String>,
+}
@@ -0,0 +1,70 @@
+use super::model::{WebhookEventState, WebhookEventStatus, WebhookReceipt};
+use crate::{
+  models::AppState,
+  services::{HttpError, OutboxWriter, outbox},
+  utils::hmac,
+};
+
+/// Checks the signature of a webhook from `source` and records its payload
+/// in the outbox as `webhook.<source>`.
+///
+/// Sources without a `WEBHOOK_SECRETS` entry are unknown (`ERR404`); a
+/// missing or wrong signature is `ERR021`, and a body that is not a JSON
+/// object is `ERR033`. The signature is checked before the body is parsed,
+/// and the body is recorded as received, not re-serialized.
+pub async fn receive(
+  state: &AppState,
+  source: &str,
+  signature: Option<&str>,
+  body: &[u8],
+) -> Result<WebhookReceipt, HttpError> {
+  let (_, secret) = state
+    .env
+    .webhook_secrets
+    .iter()
+    .find(|(name, _)| name == source)
+    .ok_or(HttpError::ERR404)?;
+
+  let signed =
+    signature.is_some_and(|sig| hmac::verify(secret.expose_secret().as_bytes(), body, sig));
+  if !signed {
+    tracing::warn!(source, "WEBHOOK_SIGNATURE_REJECTED");
+    return Err(HttpError::ERR021);
+  }
+
+  let payload = std::str::from_utf8(body).map_err(|e| HttpError::ERR033(e.to_string()))?;
+  outbox::check_payload(payload).map_err(|e| HttpError::ERR033(e.to_string()))?;
+  let event_type = format!("webhook.{source}");
+  let event_id = OutboxWriter::new(state.db.clone())
+    .enqueue_raw(&event_type, payload)
+    .await?;
+  tracing::info!(source, event_id, "WEBHOOK_RECEIVED");
+
+  Ok(WebhookReceipt {
+    event_id,
+    event_type,
+  })
+}
+
+/// Delivery state of webhook event `event_id`. Other outbox events are not
+/// visible here and, like unknown ids, are `ERR404`.
+pub async fn event_status(
+  state: &AppState,
+  event_id: i32,
+) -> Result<WebhookEventStatus, HttpError> {
+  let event = OutboxWriter::new(state.db.clone())
+    .find(event_id)
+    .await?
+    .filter(|event| event.event_type.starts_with("webhook."))
+    .ok_or(HttpError::ERR404)?;
+  let state = match event.published_at {
+    Some(_) => WebhookEventState::Published,
+    None => WebhookEventState::Pending,
+  };
+  Ok(WebhookEventStatus {
+    event_id: event.id,
+    event_type: event.event_type,
+    state,
+    published_at: event.published_at,
+  })
+}
@@ -13,6 +13,36 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    audit_log (id) {
+        id -> Integer,
+        actor_id -> Text,
+        action -> Text,
+        entity -> Text,
+        before -> Nullable<Text>,
+        after -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    outbox_events (id) {
+        id -> Integer,
+        event_type -> Text,
+        payload -> Text,
+        created_at -> Text,
+        published_at -> Nullable<Text>,
+        dedup_key -> Text,
+    }
+}
+
+diesel::table! {
+    processed_events (dedup_key) {
+        dedup_key -> Text,
+        processed_at -> Text,
+    }
+}
+
 diesel::table! {
     refresh_tokens (id) {
         id -> Text,
@@ -36,4 +66,11 @@ diesel::table! {
 
 diesel::joinable!(refresh_tokens -> users (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(attachments, refresh_tokens, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+  attachments,
+  audit_log,
+  outbox_events,
+  processed_events,
+  refresh_tokens,
+  users,
+);
@@ -1,65 +1,275 @@
 use crate::{
-  constants::{HEADER_ALLOW, METHOD_ALLOW},
-  models::AppState,
+  constants::{OVERLOAD_RETRY_AFTER_SECS, UDS_PERMISSIONS},
+  middlewares,
+  models::{AppState, TrailingSlash},
   modules::AppRoutes,
   services::HttpError,
+  utils::{runtime_config, tasks::BackgroundTasks},
 };
 use axum::{
+  Router,
   error_handling::HandleErrorLayer,
   extract::Request,
-  http::HeaderValue,
+  http::header,
+  middleware,
   response::{IntoResponse, Response},
-  routing::any,
+  routing::{Route, any},
 };
+use std::any::Any;
+use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
-use tower::{ServiceBuilder, buffer::BufferLayer, limit::RateLimitLayer};
+use tokio::{sync::oneshot, task::JoinHandle};
+use tower::{
+  BoxError, Layer, Service, ServiceBuilder,
+  buffer::BufferLayer,
+  limit::{GlobalConcurrencyLimitLayer, RateLimitLayer},
+  timeout::TimeoutLayer,
+};
 use tower_http::{
+  catch_panic::CatchPanicLayer,
   classify::ServerErrorsFailureClass,
-  cors::CorsLayer,
+  normalize_path::NormalizePathLayer,
   request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
   services::ServeDir,
   trace::TraceLayer,
 };
 use tracing::{Span, info_span};
 
-pub struct AppServer;
-impl AppServer {
-  pub async fn serve(app_state: Arc<AppState>) -> Result<(), Box<dyn std::error::Error>> {
-    let port: u16 = app_state.env.port;
-    let timeout_secs = app_state.env.timeout;
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    let cors = Self::cors_config(&app_state.env.cors_origins);
+/// A server started with [`AppServer::start`].
+///
+/// Dropping the handle leaves the server running until a shutdown signal;
+/// call [`ServerHandle::shutdown`] to stop it from code (tests).
+pub struct ServerHandle {
+  /// Address actually bound, so `PORT=0` can be resolved to the port the OS
+  /// picked. `None` when listening on `BIND_UDS`.
+  pub local_addr: Option<SocketAddr>,
+  shutdown: oneshot::Sender<()>,
+  served: JoinHandle<std::io::Result<()>>,
+}
+
+impl ServerHandle {
+  /// Waits until the server stops on a shutdown signal and its background
+  /// tasks have finished.
+  pub async fn wait(self) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(self.served.await??)
+  }
+
+  /// Starts a graceful shutdown, as a signal would, and waits for it.
+  pub async fn shutdown(self) -> Result<(), Box<dyn std::error::Error>> {
+    let _ = self.shutdown.send(());
+    Ok(self.served.await??)
+  }
+}
+
+/// Built-in layers [`AppServerBuilder::without`] can leave out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerLayer {
+  /// The 1024 requests per second rate limit, and the `429` budget from
+  /// `RuntimeConfig::rate_limit`.
+  RateLimit,
+  /// The per-request `TIMEOUT`.
+  Timeout,
+  /// `MAX_HEADER_COUNT` / `MAX_HEADER_BYTES` checks.
+  HeaderLimits,
+  /// Security response headers (`X-Frame-Options`, CSP, HSTS, ...).
+  SecurityHeaders,
+}
+
+type RouterLayer = Box<dyn FnOnce(Router) -> Router + Send>;
+type ShutdownSignal = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Configures an [`AppServer`] in code instead of from the environment.
+///
+/// Starts from the same settings [`AppServer::start`] reads from
+/// `app_state.env`; each method overrides one of them.
+///
+/// ```rust,ignore
+/// let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+/// let server = AppServer::builder(app_state, BackgroundTasks::new())
+///   .bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+///   .timeout(Duration::from_secs(2))
+///   .without(ServerLayer::RateLimit)
+///   .shutdown_signal(async move {
+///     let _ = stopped.await;
+///   })
+///   .build()
+///   .start()
+///   .await?;
+/// // ... exercise the server ...
+/// stop.send(()).unwrap();
+/// server.wait().await?;
+/// ```
+///
+/// A custom shutdown signal replaces Ctrl+C / SIGTERM, so a test server is
+/// not stopped by (and does not install handlers for) process signals;
+/// [`ServerHandle::shutdown`] keeps working either way.
+pub struct AppServerBuilder {
+  app_state: Arc<AppState>,
+  tasks: BackgroundTasks,
+  bind: Option<SocketAddr>,
+  timeout: Duration,
+  without: Vec<ServerLayer>,
+  layers: Vec<RouterLayer>,
+  shutdown_signal: Option<ShutdownSignal>,
+}
+
+impl AppServerBuilder {
+  /// Listens on `addr` over TCP, instead of `0.0.0.0:PORT` or `BIND_UDS`.
+  pub fn bind(
+    mut self,
+    addr: SocketAddr,
+  ) -> Self {
+    self.bind = Some(addr);
+    self
+  }
+
+  /// Per-request timeout, instead of `TIMEOUT`.
+  pub fn timeout(
+    mut self,
+    timeout: Duration,
+  ) -> Self {
+    self.timeout = timeout;
+    self
+  }
+
+  /// Leaves a built-in layer out of the stack.
+  pub fn without(
+    mut self,
+    layer: ServerLayer,
+  ) -> Self {
+    self.without.push(layer);
+    self
+  }
+
+  /// Adds `layer` around the routes, inside the built-in route layers, so
+  /// it sees requests after request-ID, tracing and limits have run. Layers
+  /// added later wrap earlier ones.
+  pub fn layer<L>(
+    mut self,
+    layer: L,
+  ) -> Self
+  where
+    L: Layer<Route> + Clone + Send + Sync + 'static,
+    L::Service: Service<Request> + Clone + Send + Sync + 'static,
+    <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+    <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+    <L::Service as Service<Request>>::Future: Send + 'static,
+  {
+    self
+      .layers
+      .push(Box::new(move |router| router.layer(layer)));
+    self
+  }
+
+  /// Shuts down gracefully when `signal` completes, instead of on Ctrl+C or
+  /// SIGTERM.
+  pub fn shutdown_signal<F>(
+    mut self,
+    signal: F,
+  ) -> Self
+  where
+    F: Future<Output = ()> + Send + 'static,
+  {
+    self.shutdown_signal = Some(Box::pin(signal));
+    self
+  }
+
+  pub fn build(self) -> ConfiguredServer {
+    ConfiguredServer(self)
+  }
+}
+
+/// An [`AppServerBuilder`] ready to run.
+pub struct ConfiguredServer(AppServerBuilder);
+
+impl ConfiguredServer {
+  /// Serves the app until the shutdown signal, then stops the background
+  /// tasks and waits for them to finish before returning.
+  pub async fn serve(self) -> Result<(), Box<dyn std::error::Error>> {
+    self.start().await?.wait().await
+  }
+
+  /// Binds the listener and serves the app in the background. Returns once
+  /// the address is bound, so bind errors are reported here; port `0` binds
+  /// an OS-assigned port, reported in [`ServerHandle::local_addr`].
+  ///
+  /// The server stops on the shutdown signal or [`ServerHandle::shutdown`],
+  /// then stops the background tasks and waits for them before the handle
+  /// resolves.
+  pub async fn start(self) -> Result<ServerHandle, Box<dyn std::error::Error>> {
+    let AppServerBuilder {
+      app_state,
+      mut tasks,
+      bind,
+      timeout,
+      without,
+      layers,
+      shutdown_signal,
+    } = self.0;
+    let enabled = |layer| !without.contains(&layer);
+    let max_concurrency = app_state.env.max_concurrency;
+    let request_buffer = app_state.env.request_buffer;
+    let sla = middlewares::SlaThreshold::from_millis(app_state.env.sla_ms);
+    let trace_sample_rate = middlewares::TraceSampleRate(app_state.env.trace_sample_rate);
+    let addr = bind.unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], app_state.env.port)));
+    let bind_uds = app_state.env.bind_uds.clone().filter(|_| bind.is_none());
 
     let trace_layer = TraceLayer::new_for_http()
       .make_span_with(|req: &Request<_>| {
+        // Unsampled requests get no span; `sample_trace` logs their access line
+        if !middlewares::is_sampled(req) {
+          return Span::none();
+        }
         let request_id = req
           .headers()
           .get("x-request-id")
           .and_then(|v| v.to_str().ok())
           .unwrap_or("unknown");
+        // Span names are static in `tracing`; the route template goes in
+        // `route` so traces group by `/users/{id}` rather than `/users/42`.
         info_span!(
           "REQUEST",
           method = %req.method(),
+          route = %middlewares::route_or_path(req),
           uri = %req.uri(),
           request_id = %request_id,
         )
       })
-      .on_request(|req: &Request<_>, _span: &Span| {
+      .on_request(|req: &Request<_>, span: &Span| {
+        if span.is_none() {
+          return;
+        }
         tracing::info!(
           content_type = ?req.headers().get("content-type"),
           user_agent = ?req.headers().get("user-agent"),
           "ON_REQUEST"
         );
       })
-      .on_response(|res: &Response<_>, latency: Duration, _span: &Span| {
+      .on_response(|res: &Response<_>, latency: Duration, span: &Span| {
+        if span.is_none() {
+          return;
+        }
         tracing::info!(
           status = %res.status().as_u16(),
           latency_ms = latency.as_millis() as u64,
           "ON_RESPONSE"
         );
       })
+      .on_eos(
+        |_trailers: Option<&_>, stream_duration: Duration, span: &Span| {
+          if span.is_none() {
+            return;
+          }
+          // Streamed bodies finish well after ON_RESPONSE (see utils::response)
+          tracing::debug!(
+            duration_ms = stream_duration.as_millis() as u64,
+            "ON_RESPONSE_END"
+          );
+        },
+      )
       .on_failure(
         |error: ServerErrorsFailureClass, latency: Duration, _span: &Span| {
           tracing::error!(
@@ -70,51 +280,292 @@ impl AppServer {
         },
       );
 
+    // Only `TRACE_SAMPLE_RATE` of requests get a `REQUEST` span; the rest are
+    // logged with one `ON_RESPONSE` line (see `middlewares::trace_sampling`).
+    //
+    // Gzip request bodies are inflated before any extractor sees them, and
+    // capped at `MAX_DECOMPRESSED_BYTES` while inflating (see
+    // `middlewares::decompression`).
+    //
+    // Requests over the `[rate_limit]` budget from `constant.toml` get 429.
+    // It is one window shared by every client, not a per-client limit; the
+    // limiter follows reloads and lets everything through while unset.
+    let decompression_limit = middlewares::DecompressionLimit(app_state.env.max_decompressed_bytes);
+    let global_rate_limit = enabled(ServerLayer::RateLimit).then(|| {
+      let limiter = middlewares::RateLimiter::unlimited();
+      let follower = limiter.clone();
+      runtime_config::spawn_follower(
+        &mut tasks,
+        "rate_limit_follower",
+        app_state.runtime_config.subscribe(),
+        move |config| {
+          match config.rate_limit {
+            Some(limit) => follower.reconfigure(limit.requests, limit.window()),
+            None => follower.reconfigure(u32::MAX, Duration::from_secs(1)),
+          }
+          Ok(())
+        },
+      );
+      middleware::from_fn_with_state(limiter, middlewares::rate_limit)
+    });
+    // Admission limits, skipped by CORS preflights (see `SkipPreflight`).
+    //
+    // Load shedding sits outside the buffer so a request over
+    // `MAX_CONCURRENCY` is rejected immediately with 503 instead of queueing.
+    // The permit is held until the response completes, so it also covers
+    // time spent in the buffer. The global limit shares one semaphore across
+    // every route the layer wraps.
+    //
+    // The buffer fronts the rate limiter, which admits 1024 requests per
+    // second; requests over the rate wait in the buffer for the next window.
+    // Once `REQUEST_BUFFER` are waiting the buffer stops reporting ready, and
+    // because readiness propagates up through the limit, `load_shed` answers
+    // 503 instead of letting the queue grow with requests clients may
+    // already have given up on. A smaller buffer sheds sooner; a buffer
+    // larger than the rate means waits past one second.
+    let limits = ServiceBuilder::new()
+      .option_layer(global_rate_limit)
+      .layer(HandleErrorLayer::new(AppServer::handle_layer_error))
+      .load_shed()
+      .layer(GlobalConcurrencyLimitLayer::new(max_concurrency))
+      .layer(BufferLayer::<Request>::new(request_buffer))
+      .option_layer(
+        enabled(ServerLayer::RateLimit).then(|| RateLimitLayer::new(1024, Duration::from_secs(1))),
+      )
+      .into_inner();
+    // Outermost to innermost:
+    // - Request ID first, so every span, log line and error response carries it.
+    // - Tracing, so the span covers everything below, rejections included.
+    // - Panic catching inside tracing, so a panicking handler is logged as a
+    //   500 in its request span rather than dropping the connection.
+    // - SLA, locale and CORS preflight normalizing, which only post-process.
+    // - Decompression, which is lazy and costs nothing until a body is read.
+    // - The admission limits, which preflights skip: the browser sends one
+    //   before the request it guards, and a throttled preflight surfaces as
+    //   an unreadable CORS error instead of the real request's 429 / 503.
+    // - The timeout innermost, so it bounds the handler alone; time waiting
+    //   in the buffer for the rate limiter is not charged to it.
     let route_layer = ServiceBuilder::new()
       .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+      .layer(middleware::from_fn(middlewares::request_id))
+      .layer(middleware::from_fn_with_state(
+        trace_sample_rate,
+        middlewares::sample_trace,
+      ))
       .layer(trace_layer)
-      .layer(HandleErrorLayer::new(Self::handle_timeout_error))
-      .timeout(Duration::from_secs(timeout_secs))
-      .layer(cors)
-      .layer(BufferLayer::<Request>::new(1024))
-      .layer(RateLimitLayer::new(1024, Duration::from_secs(1)))
+      .layer(CatchPanicLayer::custom(AppServer::handle_panic))
+      .layer(middleware::from_fn_with_state(sla, middlewares::sla_breach))
+      .layer(middleware::from_fn(middlewares::locale))
+      .layer(middleware::from_fn(middlewares::cors::preflight))
+      .layer(middleware::from_fn_with_state(
+        decompression_limit,
+        middlewares::decompression::accept_gzip,
+      ))
+      .layer(middlewares::decompression::layer())
+      .layer(middleware::from_fn(
+        middlewares::decompression::limit_decompressed,
+      ))
+      .layer(middlewares::cors::SkipPreflight(limits))
+      .layer(HandleErrorLayer::new(AppServer::handle_layer_error))
+      .option_layer(enabled(ServerLayer::Timeout).then(|| TimeoutLayer::new(timeout)))
+      .map_err(BoxError::from)
       .layer(PropagateRequestIdLayer::x_request_id());
 
-    let serve_dir = ServeDir::new("public").fallback(any(Self::handle_404));
+    // CORS is applied per route group in `AppRoutes::build`; the static-file
+    // fallback gets the default policy here.
+    let serve_dir = ServiceBuilder::new()
+      .layer(middlewares::cors::restricted(&app_state.env.cors_origins))
+      .service(ServeDir::new("public").fallback(any(AppServer::handle_404)));
 
-    let app = AppRoutes::build(app_state.clone())
-      .fallback_service(serve_dir)
+    // Duplicate route registrations surface here instead of as a panic.
+    // Builder layers go on first so the built-in route layers wrap them.
+    let app = AppRoutes::build(app_state.clone())?.fallback_service(serve_dir);
+    let app = layers
+      .into_iter()
+      .fold(app, |app, layer| layer(app))
       .layer(route_layer);
 
-    let listener: tokio::net::TcpListener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app)
-      .with_graceful_shutdown(Self::shutdown_signal())
-      .await?;
-    Ok(())
-  }
-
-  fn cors_config(origins: &[String]) -> CorsLayer {
-    let allowed: Vec<HeaderValue> = origins
-      .iter()
-      .filter_map(|o| o.parse::<HeaderValue>().ok())
-      .collect();
-    CorsLayer::new()
-      .allow_origin(allowed)
-      .allow_methods(METHOD_ALLOW)
-      .allow_headers(HEADER_ALLOW)
-  }
-
-  async fn handle_timeout_error(
-    err: Box<dyn std::error::Error + Send + Sync>
-  ) -> impl IntoResponse {
-    if err.is::<tower::timeout::error::Elapsed>() {
-      HttpError::ERR408
+    // Trailing-slash handling has to wrap the whole router: layers added with
+    // `Router::layer` only run after a route has already matched. Header
+    // limits sit just inside the security headers so oversized requests are
+    // refused before anything else looks at them, and every response,
+    // including those refusals and redirects, gets the security headers.
+    let header_limits = middlewares::HeaderLimits {
+      max_count: app_state.env.max_header_count,
+      max_bytes: app_state.env.max_header_bytes,
+    };
+    let trailing_slash = app_state.env.trailing_slash;
+    let security_headers = middlewares::SecurityHeaders::new(&app_state.env.security_headers);
+    let app = ServiceBuilder::new()
+      .option_layer(
+        enabled(ServerLayer::SecurityHeaders)
+          .then(|| middleware::from_fn_with_state(security_headers, middlewares::security_headers)),
+      )
+      .option_layer(
+        enabled(ServerLayer::HeaderLimits)
+          .then(|| middleware::from_fn_with_state(header_limits, middlewares::header_limits)),
+      )
+      .option_layer(
+        (trailing_slash == TrailingSlash::Rewrite).then(NormalizePathLayer::trim_trailing_slash),
+      )
+      .option_layer(
+        (trailing_slash == TrailingSlash::Redirect)
+          .then(|| middleware::from_fn(middlewares::redirect_trailing_slash)),
+      )
+      .service(app);
+
+    #[cfg(not(unix))]
+    if bind_uds.is_some() {
+      tracing::warn!("BIND_UDS_UNSUPPORTED: listening on TCP instead");
+    }
+
+    let app = axum::ServiceExt::<Request>::into_make_service(app);
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    // A dropped `ServerHandle` closes the channel without sending, which
+    // disables that branch instead of shutting down.
+    let signal = shutdown_signal.unwrap_or_else(|| Box::pin(AppServer::shutdown_signal()));
+    let shutdown = tasks.trigger_on(async move {
+      tokio::select! {
+          _ = signal => {},
+          Ok(()) = shutdown_rx => {},
+      }
+    });
+    let (local_addr, server): (_, JoinHandle<std::io::Result<()>>) = match bind_uds {
+      #[cfg(unix)]
+      Some(path) => {
+        let listener = AppServer::bind_uds(&path)?;
+        tracing::info!(path, "LISTENING_UDS");
+        let server = tokio::spawn(async move {
+          let result = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await;
+          let _ = std::fs::remove_file(&path);
+          result
+        });
+        (None, server)
+      }
+      _ => {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        tracing::info!(%local_addr, "LISTENING_TCP");
+        let server = tokio::spawn(async move {
+          axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+        });
+        (Some(local_addr), server)
+      }
+    };
+    let served = tokio::spawn(async move {
+      let result = server.await.map_err(std::io::Error::other).and_then(|r| r);
+      tasks.shutdown().await;
+      result
+    });
+    Ok(ServerHandle {
+      local_addr,
+      shutdown: shutdown_tx,
+      served,
+    })
+  }
+}
+
+pub struct AppServer;
+impl AppServer {
+  /// Builder starting from the settings in `app_state.env`; see
+  /// [`AppServerBuilder`].
+  pub fn builder(
+    app_state: Arc<AppState>,
+    tasks: BackgroundTasks,
+  ) -> AppServerBuilder {
+    let timeout = Duration::from_secs(app_state.env.timeout);
+    AppServerBuilder {
+      app_state,
+      tasks,
+      bind: None,
+      timeout,
+      without: Vec::new(),
+      layers: Vec::new(),
+      shutdown_signal: None,
+    }
+  }
+
+  /// Serves the app until a shutdown signal, then stops `tasks` and waits for
+  /// them to finish before returning.
+  pub async fn serve(
+    app_state: Arc<AppState>,
+    tasks: BackgroundTasks,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    Self::builder(app_state, tasks).build().serve().await
+  }
+
+  /// [`ConfiguredServer::start`] with every setting from `app_state.env`.
+  pub async fn start(
+    app_state: Arc<AppState>,
+    tasks: BackgroundTasks,
+  ) -> Result<ServerHandle, Box<dyn std::error::Error>> {
+    Self::builder(app_state, tasks).build().start().await
+  }
+
+  /// Binds a Unix socket at `path` with [`UDS_PERMISSIONS`].
+  ///
+  /// A socket file left behind by a previous run is removed first. The path
+  /// is refused if another process still accepts connections on it, or if it
+  /// is not a socket at all, so a typo cannot delete an unrelated file.
+  #[cfg(unix)]
+  fn bind_uds(path: &str) -> std::io::Result<tokio::net::UnixListener> {
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+      Ok(meta) if meta.file_type().is_socket() => {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+          return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("BIND_UDS_IN_USE: {path}"),
+          ));
+        }
+        std::fs::remove_file(path)?;
+      }
+      Ok(_) => {
+        return Err(Error::new(
+          ErrorKind::AlreadyExists,
+          format!("BIND_UDS_NOT_SOCKET: {path}"),
+        ));
+      }
+      Err(e) if e.kind() == ErrorKind::NotFound => {}
+      Err(e) => return Err(e),
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(UDS_PERMISSIONS))?;
+    Ok(listener)
+  }
+
+  fn handle_panic(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+      .downcast_ref::<&str>()
+      .copied()
+      .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+      .unwrap_or("non-string panic payload");
+    tracing::error!(panic = message, "HANDLER_PANICKED");
+    HttpError::ERR043.into_response()
+  }
+
+  async fn handle_layer_error(err: Box<dyn std::error::Error + Send + Sync>) -> Response {
+    if err.is::<tower::load_shed::error::Overloaded>() {
+      tracing::warn!("REQUEST_SHED_OVERLOADED");
+      let retry_after = [(header::RETRY_AFTER, OVERLOAD_RETRY_AFTER_SECS.to_string())];
+      (retry_after, HttpError::ERR503).into_response()
+    } else if err.is::<tower::timeout::error::Elapsed>() {
+      HttpError::ERR408.into_response()
     } else {
-      HttpError::ERR043
+      HttpError::ERR043.into_response()
     }
   }
 
-  async fn shutdown_signal() {
+  /// Resolves on Ctrl+C, or SIGTERM (Windows: close / shutdown events). Once
+  /// awaited, those signals no longer terminate the process by default.
+  pub async fn shutdown_signal() {
     let ctrl_c = async {
       tokio::signal::ctrl_c()
         .await
@@ -129,7 +580,21 @@ impl AppServer {
         .await;
     };
 
-    #[cfg(not(unix))]
+    // Windows has no SIGTERM; `docker stop` and service shutdown deliver
+    // CTRL_CLOSE_EVENT / CTRL_SHUTDOWN_EVENT instead.
+    #[cfg(windows)]
+    let terminate = async {
+      let mut close =
+        tokio::signal::windows::ctrl_close().expect("failed to install ctrl_close handler");
+      let mut shutdown =
+        tokio::signal::windows::ctrl_shutdown().expect("failed to install ctrl_shutdown handler");
+      tokio::select! {
+          _ = close.recv() => {},
+          _ = shutdown.recv() => {},
+      }
+    };
+
+    #[cfg(not(any(unix, windows)))]
     let terminate = std::future::pending::<()>();
 
     tokio::select! {
@@ -142,3 +607,39 @@ impl AppServer {
     HttpError::ERR404
   }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use std::os::unix::fs::PermissionsExt;
+
+  #[tokio::test]
+  async fn bind_uds_replaces_stale_socket_and_sets_permissions() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("app.sock");
+    let path = path.to_str().unwrap();
+
+    // A socket nobody listens on any more, as left by a crashed process.
+    drop(std::os::unix::net::UnixListener::bind(path).unwrap());
+
+    let listener = AppServer::bind_uds(path).unwrap();
+    let mode = std::fs::metadata(path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, UDS_PERMISSIONS);
+
+    // Still accepting: a second server must not steal the path.
+    let err = AppServer::bind_uds(path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    drop(listener);
+  }
+
+  #[tokio::test]
+  async fn bind_uds_refuses_regular_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("not-a-socket");
+    std::fs::write(&path, "data").unwrap();
+
+    let err = AppServer::bind_uds(path.to_str().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert!(path.exists());
+  }
+}
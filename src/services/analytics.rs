@@ -0,0 +1,117 @@
+//! Time-bucketed row counts for dashboards (`GROUP BY date_trunc(...)`).
+//!
+//! `DBSqlite::count_by_window` and `DBPostgres::count_by_window` run the same
+//! query shape over a time column, differing only in how a timestamp is
+//! truncated to its bucket:
+//!
+//! | window | Postgres                                   | SQLite (UTC only)                              |
+//! | ------ | ------------------------------------------ | ---------------------------------------------- |
+//! | hour   | `date_trunc('hour', col AT TIME ZONE 'UTC')` | `strftime('%Y-%m-%dT%H:00:00Z', col)`        |
+//! | day    | `date_trunc('day', col AT TIME ZONE tz)`   | `strftime('%Y-%m-%dT00:00:00Z', col)`          |
+//! | week   | `date_trunc('week', col AT TIME ZONE tz)`  | same, shifted to Monday (`'weekday 0', '-6 days'`) |
+//!
+//! Buckets are returned as their start instant in UTC, oldest first; empty
+//! buckets are not returned. Weeks start on Monday (ISO), as with
+//! `date_trunc('week', …)`.
+//!
+//! # Time zones and DST
+//!
+//! Postgres day and week buckets follow local midnight in the requested
+//! zone, so a bucket spanning a DST change is 23 or 25 hours long. Hour
+//! buckets are always cut in UTC: truncating local wall-clock time would fold
+//! the repeated hour of a DST fall-back into one bucket. SQLite has no zone
+//! database and always buckets in UTC.
+//!
+//! # Identifiers
+//!
+//! `table` and `column` are spliced into the SQL, so they must come from code,
+//! never from request input; anything other than `[A-Za-z_][A-Za-z0-9_]*`
+//! (optionally `schema.table`) is rejected with `INVALID_IDENTIFIER`.
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use diesel::QueryableByName;
+use diesel::sql_types::{BigInt, Text};
+
+/// Bucket width for `count_by_window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+  Hour,
+  Day,
+  Week,
+}
+
+impl TimeWindow {
+  /// Unit argument of Postgres `date_trunc`.
+  pub fn postgres_unit(self) -> &'static str {
+    match self {
+      TimeWindow::Hour => "hour",
+      TimeWindow::Day => "day",
+      TimeWindow::Week => "week",
+    }
+  }
+
+  /// SQLite expression truncating `column` to its bucket start, formatted as
+  /// RFC 3339 UTC.
+  pub fn sqlite_bucket(
+    self,
+    column: &str,
+  ) -> String {
+    match self {
+      TimeWindow::Hour => format!("strftime('%Y-%m-%dT%H:00:00Z', {column})"),
+      TimeWindow::Day => format!("strftime('%Y-%m-%dT00:00:00Z', {column})"),
+      // `weekday 0` moves forward to Sunday (or stays), `-6 days` back to Monday.
+      TimeWindow::Week => {
+        format!("strftime('%Y-%m-%dT00:00:00Z', {column}, 'weekday 0', '-6 days')")
+      }
+    }
+  }
+}
+
+/// Half-open `[from, to)` range of the time column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+  pub from: DateTime<Utc>,
+  pub to: DateTime<Utc>,
+}
+
+impl TimeRange {
+  /// `from` and `to` as RFC 3339 strings for binding.
+  pub(crate) fn bounds(&self) -> (String, String) {
+    let format = |t: DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    (format(self.from), format(self.to))
+  }
+}
+
+/// One row of the bucket query; `bucket` is RFC 3339 UTC.
+#[derive(QueryableByName)]
+pub(crate) struct BucketRow {
+  #[diesel(sql_type = Text)]
+  bucket: String,
+  #[diesel(sql_type = BigInt)]
+  count: i64,
+}
+
+impl BucketRow {
+  pub(crate) fn into_pair(self) -> Result<(DateTime<Utc>, i64)> {
+    let bucket = DateTime::parse_from_rfc3339(&self.bucket)
+      .map_err(|e| anyhow!("INVALID_BUCKET: {}: {}", self.bucket, e))?;
+    Ok((bucket.with_timezone(&Utc), self.count))
+  }
+}
+
+/// Rejects anything but a plain (optionally schema-qualified) SQL identifier.
+pub(crate) fn check_identifier(name: &str) -> Result<()> {
+  let valid = |part: &str| {
+    let mut chars = part.chars();
+    chars
+      .next()
+      .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+      && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+  };
+  let parts: Vec<&str> = name.split('.').collect();
+  if parts.len() > 2 || !parts.iter().all(|part| valid(part)) {
+    bail!("INVALID_IDENTIFIER: {}", name);
+  }
+  Ok(())
+}
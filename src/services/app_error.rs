@@ -0,0 +1,180 @@
+use crate::services::HttpError;
+use crate::utils::validation::format_validation_errors;
+use axum::response::{IntoResponse, Response};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use validator::ValidationErrors;
+
+/// Single error type for handlers that mix database, validation, and service errors.
+///
+/// Every variant renders through [`HttpError`], so status codes, error codes and
+/// localized messages stay identical to handlers that return `HttpError` directly.
+/// The [`From`] impls let handlers return `Result<T, AppError>` and use `?` on
+/// Diesel results, `validate()`, `anyhow` results and existing `HttpError`
+/// service calls alike.
+///
+/// # Usage
+/// ```rust,ignore
+/// pub async fn update(
+///   State(state): State<Arc<AppState>>,
+///   auth: AuthUser,
+///   BodyJson(body): BodyJson<UpdateRequest>,
+/// ) -> Result<impl IntoResponse, AppError> {
+///   let user = service::find_by_id(&state.db, auth.user_id).await?; // HttpError
+///   if user.locked {
+///     return Err(AppError::Forbidden);
+///   }
+///   body.validate()?; // ValidationErrors
+///   Ok(HttpResponse::ok(user, "OK"))
+/// }
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+  /// Diesel error: `NotFound` → `404`, unique violation → `409`, anything else → `500`.
+  #[error("DB_ERROR: {0}")]
+  Db(#[from] DieselError),
+
+  /// `422 Unprocessable Entity` — input failed validation; the message lists the fields.
+  #[error("VALIDATION_ERROR: {0}")]
+  Validation(String),
+
+  /// `404 Not Found`.
+  #[error("NOT_FOUND")]
+  NotFound,
+
+  /// `401 Unauthorized`.
+  #[error("UNAUTHORIZED")]
+  Unauthorized,
+
+  /// `403 Forbidden`.
+  #[error("FORBIDDEN")]
+  Forbidden,
+
+  /// `409 Conflict` — the message says what conflicted (e.g. `"slug"`).
+  #[error("CONFLICT: {0}")]
+  Conflict(String),
+
+  /// `500 Internal Server Error`. The full error chain is logged; the response
+  /// only carries a generic message.
+  #[error("INTERNAL_ERROR: {0}")]
+  Internal(anyhow::Error),
+
+  /// An already-typed [`HttpError`] passed through unchanged.
+  #[error(transparent)]
+  Http(#[from] HttpError),
+}
+
+/// Optimistic-concurrency conflict: a versioned update matched no row because
+/// the row's `version` is no longer `expected` (or the row is gone).
+///
+/// Returned inside `anyhow::Error` by `update_if_version` on both database
+/// backends; converting that error into [`HttpError`] or [`AppError`] yields
+/// `409` (`ERR045`, detail `version`), so handlers can simply use `?`.
+#[derive(Debug, thiserror::Error)]
+#[error("DB_STALE_VERSION: expected version {expected}")]
+pub struct StaleVersion {
+  pub expected: i32,
+}
+
+/// A capped query (`load_capped`) matched more rows than the caller allowed.
+///
+/// Not a client error: it means a query is missing a filter or pagination, so
+/// it is left to render as `500` like any other unexpected failure.
+#[derive(Debug, thiserror::Error)]
+#[error("DB_TOO_MANY_ROWS: query returned more than {max_rows} rows")]
+pub struct TooManyRows {
+  pub max_rows: u32,
+}
+
+impl From<StaleVersion> for HttpError {
+  fn from(_: StaleVersion) -> Self {
+    HttpError::ERR045("version".to_string())
+  }
+}
+
+impl From<ValidationErrors> for AppError {
+  fn from(e: ValidationErrors) -> Self {
+    Self::Validation(format_validation_errors(&e))
+  }
+}
+
+impl From<anyhow::Error> for AppError {
+  fn from(e: anyhow::Error) -> Self {
+    match e.downcast::<StaleVersion>() {
+      Ok(stale) => Self::Http(stale.into()),
+      Err(e) => Self::Internal(e),
+    }
+  }
+}
+
+impl From<AppError> for HttpError {
+  fn from(e: AppError) -> Self {
+    match e {
+      AppError::Db(DieselError::NotFound) | AppError::NotFound => HttpError::ERR404,
+      AppError::Db(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info)) => {
+        HttpError::ERR045(info.constraint_name().unwrap_or("unique").to_string())
+      }
+      AppError::Db(e) => {
+        tracing::error!(error = ?e, "DB_ERROR");
+        HttpError::ERR043
+      }
+      AppError::Validation(msg) => HttpError::ERR034(msg),
+      AppError::Unauthorized => HttpError::ERR021,
+      AppError::Forbidden => HttpError::ERR044,
+      AppError::Conflict(msg) => HttpError::ERR045(msg),
+      AppError::Internal(e) => {
+        tracing::error!(error = ?e, "INTERNAL_ERROR");
+        HttpError::ERR043
+      }
+      AppError::Http(e) => e,
+    }
+  }
+}
+
+impl IntoResponse for AppError {
+  fn into_response(self) -> Response {
+    HttpError::from(self).into_response()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::http::StatusCode;
+
+  fn status(e: AppError) -> StatusCode {
+    e.into_response().status()
+  }
+
+  #[test]
+  fn maps_variants_to_status_codes() {
+    assert_eq!(status(AppError::NotFound), StatusCode::NOT_FOUND);
+    assert_eq!(status(DieselError::NotFound.into()), StatusCode::NOT_FOUND);
+    assert_eq!(status(AppError::Unauthorized), StatusCode::UNAUTHORIZED);
+    assert_eq!(status(AppError::Forbidden), StatusCode::FORBIDDEN);
+    assert_eq!(
+      status(AppError::Conflict("slug".into())),
+      StatusCode::CONFLICT
+    );
+    assert_eq!(
+      status(AppError::Validation("email".into())),
+      StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(status(HttpError::ERR013.into()), StatusCode::UNAUTHORIZED);
+  }
+
+  #[test]
+  fn stale_version_is_a_conflict() {
+    let e = anyhow::Error::from(StaleVersion { expected: 3 });
+    assert_eq!(status(AppError::from(e)), StatusCode::CONFLICT);
+    let e = anyhow::Error::from(StaleVersion { expected: 3 });
+    assert_eq!(HttpError::from(e).status(), StatusCode::CONFLICT);
+  }
+
+  #[test]
+  fn internal_errors_hide_details() {
+    let e = AppError::from(anyhow::anyhow!("connection refused at 10.0.0.5"));
+    let http = HttpError::from(e);
+    assert_eq!(http.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!http.to_string().contains("10.0.0.5"));
+  }
+}
@@ -0,0 +1,186 @@
+//! Audit trail of who changed what, in `audit_log`.
+//!
+//! [`AuditLog::record`] takes the connection of the transaction making the
+//! change, so the audit row commits or rolls back together with it: there is
+//! never a change without its entry, or an entry for a change that did not
+//! happen.
+//!
+//! ```rust,ignore
+//! db.transaction(move |conn| {
+//!   let before = users::table.find(&id).select(User::as_select()).first(conn)?;
+//!   diesel::update(users::table.find(&id))
+//!     .set(users::username.eq(&username))
+//!     .execute(conn)?;
+//!   let after = users::table.find(&id).select(User::as_select()).first(conn)?;
+//!   AuditLog::record(conn, &actor_id, "user.update", &format!("user:{id}"), Some(&before), Some(&after))?;
+//!   Ok(after)
+//! })
+//! .await
+//! ```
+
+use crate::schemas::table::audit_log;
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{dsl::sql, prelude::*, sql_types::Integer};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = audit_log)]
+struct NewAuditEntry {
+  actor_id: String,
+  action: String,
+  entity: String,
+  before: Option<String>,
+  after: Option<String>,
+  created_at: String,
+}
+
+/// Writes audit entries inside the caller's transaction.
+pub struct AuditLog;
+
+impl AuditLog {
+  /// Records that `actor_id` performed `action` (e.g. `user.update`) on
+  /// `entity` (e.g. `user:42`) and returns the entry's id.
+  ///
+  /// `before` and `after` are the entity's state around the change; `None`
+  /// for a creation or a deletion. Only the difference is stored: for
+  /// objects, the top-level fields whose values differ (a field present on
+  /// one side only appears on that side); other values are stored whole when
+  /// they differ.
+  pub fn record<T: Serialize>(
+    conn: &mut SqliteConnection,
+    actor_id: &str,
+    action: &str,
+    entity: &str,
+    before: Option<&T>,
+    after: Option<&T>,
+  ) -> Result<i32> {
+    let before = before.map(serde_json::to_value).transpose()?;
+    let after = after.map(serde_json::to_value).transpose()?;
+    let (before, after) = match (before, after) {
+      (Some(before), Some(after)) => {
+        let (before, after) = diff(before, after);
+        (Some(before), Some(after))
+      }
+      sides => sides,
+    };
+    let entry = NewAuditEntry {
+      actor_id: actor_id.to_string(),
+      action: action.to_string(),
+      entity: entity.to_string(),
+      before: before.map(|v| v.to_string()),
+      after: after.map(|v| v.to_string()),
+      created_at: Utc::now().to_rfc3339(),
+    };
+    diesel::insert_into(audit_log::table)
+      .values(&entry)
+      .execute(conn)
+      .map_err(|e| anyhow::anyhow!("AUDIT_RECORD_FAILED: {}", e))?;
+    Ok(diesel::select(sql::<Integer>("last_insert_rowid()")).get_result(conn)?)
+  }
+}
+
+/// The changed parts of `before` and `after`.
+fn diff(
+  before: Value,
+  after: Value,
+) -> (Value, Value) {
+  match (before, after) {
+    (Value::Object(mut before), Value::Object(mut after)) => {
+      let unchanged: Vec<String> = before
+        .iter()
+        .filter(|(key, value)| after.get(*key) == Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+      for key in unchanged {
+        before.remove(&key);
+        after.remove(&key);
+      }
+      (Value::Object(before), Value::Object(after))
+    }
+    (before, after) if before == after => (Value::Object(Map::new()), Value::Object(Map::new())),
+    sides => sides,
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::DBSqlite;
+  use serde_json::json;
+  use tempfile::NamedTempFile;
+
+  async fn audit_db() -> (NamedTempFile, DBSqlite) {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.run_migrations().unwrap();
+    (file, db)
+  }
+
+  async fn entries(db: &DBSqlite) -> Vec<(String, Option<String>, Option<String>)> {
+    db.execute(|conn| {
+      Ok(
+        audit_log::table
+          .select((audit_log::action, audit_log::before, audit_log::after))
+          .order(audit_log::id)
+          .load(conn)?,
+      )
+    })
+    .await
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn stores_only_changed_fields() {
+    let (_file, db) = audit_db().await;
+    db.transaction(|conn| {
+      let before = json!({"id": "u1", "username": "ada", "email": "a@x.io"});
+      let after = json!({"id": "u1", "username": "lovelace", "email": "a@x.io"});
+      AuditLog::record(conn, "admin", "user.create", "user:u1", None, Some(&before))?;
+      AuditLog::record(
+        conn,
+        "admin",
+        "user.update",
+        "user:u1",
+        Some(&before),
+        Some(&after),
+      )?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+
+    let entries = entries(&db).await;
+    assert_eq!(entries[0].1, None);
+    assert_eq!(
+      entries[1],
+      (
+        "user.update".to_string(),
+        Some(r#"{"username":"ada"}"#.to_string()),
+        Some(r#"{"username":"lovelace"}"#.to_string())
+      )
+    );
+  }
+
+  #[tokio::test]
+  async fn rolled_back_transaction_leaves_no_entry() {
+    let (_file, db) = audit_db().await;
+    let result: Result<()> = db
+      .transaction(|conn| {
+        AuditLog::record(
+          conn,
+          "admin",
+          "user.delete",
+          "user:u1",
+          Some(&json!({"id": "u1"})),
+          None,
+        )?;
+        anyhow::bail!("DELETE_FAILED")
+      })
+      .await;
+    assert!(result.is_err());
+    assert!(entries(&db).await.is_empty());
+  }
+}
@@ -1,75 +1,751 @@
+use crate::utils::{SharedClock, SystemClock};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-  collections::HashMap,
-  sync::Arc,
+  collections::{HashMap, HashSet},
+  io::ErrorKind,
+  path::Path,
+  sync::{Arc, OnceLock},
   time::{Duration, Instant},
 };
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Format version of [`Cache::save_snapshot`] files. Bump it when
+/// [`Snapshot`] changes shape; files with another version are ignored.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct SnapshotHeader {
+  version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+  version: u32,
+  saved_at: DateTime<Utc>,
+  entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+  key: String,
+  data: Value,
+  /// TTL left when the snapshot was saved.
+  ttl_ms: u64,
+  tags: Vec<String>,
+}
 #[derive(Clone, Debug)]
 pub struct CacheEntry {
   data: Value,
   expires: Instant,
+  tags: Vec<String>,
+}
+
+/// Entries plus the reverse `tag -> keys` index, guarded by one lock so both
+/// always change together.
+///
+/// The index costs one `String` per tag and one `String` per (tag, key) pair on
+/// top of the entry itself, roughly `tags × (key length + ~50 bytes)` per
+/// tagged entry. Untagged entries add nothing.
+#[derive(Debug, Default)]
+struct CacheStore {
+  entries: HashMap<String, CacheEntry>,
+  tags: HashMap<String, HashSet<String>>,
 }
+
+impl CacheStore {
+  fn insert(
+    &mut self,
+    key: String,
+    entry: CacheEntry,
+  ) {
+    for tag in &entry.tags {
+      self
+        .tags
+        .entry(tag.clone())
+        .or_default()
+        .insert(key.clone());
+    }
+    if let Some(old) = self.entries.insert(key.clone(), entry) {
+      self.unindex(&key, &old);
+    }
+  }
+
+  fn remove(
+    &mut self,
+    key: &str,
+  ) -> Option<CacheEntry> {
+    let entry = self.entries.remove(key)?;
+    self.unindex(key, &entry);
+    Some(entry)
+  }
+
+  /// Drops `key` from the index of every tag in `entry` that the key's current
+  /// entry (if any) no longer carries, removing tags left empty.
+  fn unindex(
+    &mut self,
+    key: &str,
+    entry: &CacheEntry,
+  ) {
+    let current = self.entries.get(key).map(|e| &e.tags);
+    for tag in &entry.tags {
+      if current.is_some_and(|tags| tags.contains(tag)) {
+        continue;
+      }
+      if let Some(keys) = self.tags.get_mut(tag) {
+        keys.remove(key);
+        if keys.is_empty() {
+          self.tags.remove(tag);
+        }
+      }
+    }
+  }
+}
+
+/// Size of a [`Cache`] at one point in time, from [`Cache::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+  /// Stored entries, including expired ones not yet purged.
+  pub entries: usize,
+  /// Entries past their TTL that [`Cache::purge_expired`] would remove.
+  pub expired: usize,
+  /// Distinct tags in the index.
+  pub tags: usize,
+}
+
+/// In-memory key/value cache with per-entry TTL and tag invalidation.
+///
+/// # Cloning
+///
+/// The store sits behind an `Arc`, so a clone is a cheap handle to the same
+/// entries, never a copy: `AppState` clones (one per request) all see and
+/// modify one cache. Only the default TTL and the clock are per handle.
+///
+/// # Ownership
+///
+/// Constructing a cache ([`Cache::new`], [`Cache::default`]) always creates
+/// a new, empty store that shares nothing with any other. The app's cache is
+/// [`Cache::global`], wired into `AppState` once in `main`; code that has the
+/// state uses `state.cache` (or a clone of it), and code without it can call
+/// `Cache::global()` and see the same entries. Build a separate cache only
+/// when isolation is the point: tests, or a subsystem whose entries must not
+/// be visible to, or invalidated by, the rest of the app.
 #[derive(Clone, Debug)]
 pub struct Cache {
-  store: Arc<RwLock<HashMap<String, CacheEntry>>>,
+  store: Arc<RwLock<CacheStore>>,
   ttl: Duration,
+  clock: SharedClock,
+  /// Lock acquisitions through any clone, for tests of the bulk operations.
+  #[cfg(test)]
+  lock_acquisitions: Arc<std::sync::atomic::AtomicUsize>,
 }
+/// A new, empty cache with a 24-hour default TTL. Not shared with any other;
+/// see [`Cache::global`].
 impl Default for Cache {
   fn default() -> Self {
-    Cache {
-      store: Arc::new(RwLock::new(HashMap::new())),
-      ttl: Duration::from_secs(24 * 60 * 60),
-    }
+    Cache::new(Duration::from_secs(24 * 60 * 60))
   }
 }
 
 impl Cache {
   pub fn new(ttl: Duration) -> Self {
+    Cache::with_clock(ttl, SystemClock::shared())
+  }
+
+  /// The process-wide cache, created empty with the [`Default`] settings on
+  /// first use. `main` puts it in `AppState`, so it is the same store as
+  /// `state.cache`.
+  pub fn global() -> &'static Cache {
+    static GLOBAL: OnceLock<Cache> = OnceLock::new();
+    GLOBAL.get_or_init(Cache::default)
+  }
+
+  /// Cache that reads the time from `clock`, so tests can expire entries by
+  /// advancing a [`MockClock`](crate::utils::MockClock) instead of sleeping.
+  pub fn with_clock(
+    ttl: Duration,
+    clock: SharedClock,
+  ) -> Self {
     Cache {
-      store: Arc::new(RwLock::new(HashMap::new())),
+      store: Arc::new(RwLock::new(CacheStore::default())),
       ttl,
+      clock,
+      #[cfg(test)]
+      lock_acquisitions: Default::default(),
     }
   }
 
+  async fn read(&self) -> RwLockReadGuard<'_, CacheStore> {
+    #[cfg(test)]
+    self
+      .lock_acquisitions
+      .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    self.store.read().await
+  }
+
+  async fn write(&self) -> RwLockWriteGuard<'_, CacheStore> {
+    #[cfg(test)]
+    self
+      .lock_acquisitions
+      .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    self.store.write().await
+  }
+
   pub async fn set(
     &self,
     key: String,
     value: Value,
   ) {
-    let mut store = self.store.write().await;
+    self.set_with_tags(key, value, self.ttl, &[]).await;
+  }
+
+  /// Stores `value` under `key` for `ttl` and associates it with `tags`, so it
+  /// can be dropped together with related keys via [`Cache::invalidate_tag`].
+  ///
+  /// Overwriting a key replaces its tags.
+  pub async fn set_with_tags(
+    &self,
+    key: String,
+    value: Value,
+    ttl: Duration,
+    tags: &[&str],
+  ) {
+    let mut store = self.write().await;
     store.insert(
       key,
       CacheEntry {
         data: value,
-        expires: Instant::now() + self.ttl,
+        expires: self.clock.now() + ttl,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
       },
     );
   }
 
+  /// Stores every `(key, value)` pair with the default TTL under one write
+  /// lock, e.g. to fill the misses of a [`get_many`](Self::get_many).
+  pub async fn set_many(
+    &self,
+    entries: impl IntoIterator<Item = (String, Value)>,
+  ) {
+    let mut store = self.write().await;
+    let expires = self.clock.now() + self.ttl;
+    for (key, data) in entries {
+      store.insert(
+        key,
+        CacheEntry {
+          data,
+          expires,
+          tags: Vec::new(),
+        },
+      );
+    }
+  }
+
   pub async fn get(
     &self,
     key: &str,
   ) -> Option<Value> {
-    let store = self.store.read().await;
-    if let Some(entry) = store.get(key)
-      && entry.expires > Instant::now()
+    let store = self.read().await;
+    if let Some(entry) = store.entries.get(key)
+      && entry.expires > self.clock.now()
     {
       return Some(entry.data.clone());
     }
     None
   }
 
+  /// The present, unexpired entries among `keys`, read under one lock
+  /// instead of one per key. Keys missing from the result are the misses to
+  /// load from the database:
+  ///
+  /// ```rust,ignore
+  /// let hits = cache.get_many(&keys).await;
+  /// let misses: Vec<&str> = keys.iter().copied().filter(|k| !hits.contains_key(*k)).collect();
+  /// ```
+  pub async fn get_many(
+    &self,
+    keys: &[&str],
+  ) -> HashMap<String, Value> {
+    let store = self.read().await;
+    let now = self.clock.now();
+    keys
+      .iter()
+      .filter_map(|key| {
+        store
+          .entries
+          .get(*key)
+          .filter(|entry| entry.expires > now)
+          .map(|entry| (key.to_string(), entry.data.clone()))
+      })
+      .collect()
+  }
+
+  /// Adds `delta` to the integer counter at `key` and returns the new value.
+  ///
+  /// The read-modify-write happens under the store's write lock, so
+  /// concurrent increments are never lost (unlike `get` followed by `set`).
+  /// A missing or expired key starts from `0` with the default TTL; an
+  /// existing counter keeps its expiry and tags, so the TTL bounds a fixed
+  /// window rather than sliding with each hit. A key holding anything but an
+  /// integer is overwritten (`CACHE_COUNTER_TYPE_MISMATCH`). Counters are
+  /// evicted like any other entry and restart from `0` afterwards. The sum
+  /// saturates at the `i64` bounds.
+  pub async fn increment(
+    &self,
+    key: &str,
+    delta: i64,
+  ) -> i64 {
+    let mut store = self.write().await;
+    let now = self.clock.now();
+    if let Some(entry) = store.entries.get_mut(key)
+      && entry.expires > now
+    {
+      if let Some(current) = entry.data.as_i64() {
+        let value = current.saturating_add(delta);
+        entry.data = Value::from(value);
+        return value;
+      }
+      tracing::warn!(key, "CACHE_COUNTER_TYPE_MISMATCH");
+    }
+    store.insert(
+      key.to_string(),
+      CacheEntry {
+        data: Value::from(delta),
+        expires: now + self.ttl,
+        tags: Vec::new(),
+      },
+    );
+    delta
+  }
+
+  /// [`Cache::increment`] by `-delta`.
+  pub async fn decrement(
+    &self,
+    key: &str,
+    delta: i64,
+  ) -> i64 {
+    self.increment(key, delta.saturating_neg()).await
+  }
+
+  /// Restarts the TTL of `key` at `ttl` from now, keeping its value and
+  /// tags. Returns `false` when the key is missing or already expired.
+  pub async fn touch(
+    &self,
+    key: &str,
+    ttl: Duration,
+  ) -> bool {
+    let mut store = self.write().await;
+    let now = self.clock.now();
+    match store.entries.get_mut(key) {
+      Some(entry) if entry.expires > now => {
+        entry.expires = now + ttl;
+        true
+      }
+      _ => false,
+    }
+  }
+
   pub async fn delete(
     &self,
     key: &str,
   ) {
-    let mut store = self.store.write().await;
+    let mut store = self.write().await;
     store.remove(key);
   }
 
+  /// Removes every key tagged with `tag` and returns how many were removed.
+  pub async fn invalidate_tag(
+    &self,
+    tag: &str,
+  ) -> usize {
+    let mut store = self.write().await;
+    let Some(keys) = store.tags.remove(tag) else {
+      return 0;
+    };
+    keys
+      .iter()
+      .filter(|key| store.remove(key).is_some())
+      .count()
+  }
+
+  /// Removes expired entries (and their tag index entries), returning how many were removed.
+  ///
+  /// [`Cache::get`] ignores expired entries but does not free them; call this
+  /// periodically to reclaim memory.
+  pub async fn purge_expired(&self) -> usize {
+    let mut store = self.write().await;
+    let now = self.clock.now();
+    let expired: Vec<String> = store
+      .entries
+      .iter()
+      .filter(|(_, entry)| entry.expires <= now)
+      .map(|(key, _)| key.clone())
+      .collect();
+    for key in &expired {
+      store.remove(key);
+    }
+    expired.len()
+  }
+
+  /// Shrinks the cache to at most `target_entries`, returning how many entries
+  /// were removed. Expired entries go first, then the ones closest to expiry.
+  ///
+  /// Used to shed memory under pressure (see `CACHE_MEMORY_LIMIT_MB`); evicted
+  /// entries are simply misses on the next read.
+  pub async fn evict_to(
+    &self,
+    target_entries: usize,
+  ) -> usize {
+    let mut store = self.write().await;
+    let excess = store.entries.len().saturating_sub(target_entries);
+    if excess == 0 {
+      return 0;
+    }
+    let mut by_expiry: Vec<(Instant, String)> = store
+      .entries
+      .iter()
+      .map(|(key, entry)| (entry.expires, key.clone()))
+      .collect();
+    by_expiry.select_nth_unstable(excess - 1);
+    for (_, key) in &by_expiry[..excess] {
+      store.remove(key);
+    }
+    excess
+  }
+
+  pub async fn stats(&self) -> CacheStats {
+    let store = self.read().await;
+    let now = self.clock.now();
+    CacheStats {
+      entries: store.entries.len(),
+      expired: store
+        .entries
+        .values()
+        .filter(|entry| entry.expires <= now)
+        .count(),
+      tags: store.tags.len(),
+    }
+  }
+
   pub async fn clear(&self) {
-    let mut store = self.store.write().await;
-    store.clear();
+    let mut store = self.write().await;
+    store.entries.clear();
+    store.tags.clear();
+  }
+
+  /// Writes every unexpired entry, with its remaining TTL and tags, to `path`
+  /// as JSON (`CACHE_SNAPSHOT_PATH`, saved on shutdown). Returns how many
+  /// entries were written.
+  ///
+  /// The file is written next to `path` and renamed over it, so a crash
+  /// mid-write leaves the previous snapshot intact.
+  pub async fn save_snapshot(
+    &self,
+    path: &Path,
+  ) -> Result<usize> {
+    let snapshot = {
+      let store = self.read().await;
+      let now = self.clock.now();
+      let entries = store
+        .entries
+        .iter()
+        .filter(|(_, entry)| entry.expires > now)
+        .map(|(key, entry)| SnapshotEntry {
+          key: key.clone(),
+          data: entry.data.clone(),
+          ttl_ms: entry.expires.duration_since(now).as_millis() as u64,
+          tags: entry.tags.clone(),
+        })
+        .collect();
+      Snapshot {
+        version: SNAPSHOT_VERSION,
+        saved_at: self.clock.utc_now(),
+        entries,
+      }
+    };
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+      tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(&snapshot)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(snapshot.entries.len())
+  }
+
+  /// Restores entries saved by [`Cache::save_snapshot`], returning how many
+  /// were loaded.
+  ///
+  /// Time spent down counts against each entry's TTL, so entries that expired
+  /// in the meantime are skipped. A missing file loads nothing; a file from
+  /// another [`SNAPSHOT_VERSION`] or one that does not parse is ignored with
+  /// `CACHE_SNAPSHOT_IGNORED`, since a cold cache is always safe. Only I/O
+  /// errors other than a missing file are returned.
+  pub async fn load_snapshot(
+    &self,
+    path: &Path,
+  ) -> Result<usize> {
+    let bytes = match tokio::fs::read(path).await {
+      Ok(bytes) => bytes,
+      Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+      Err(e) => return Err(e.into()),
+    };
+    let snapshot = match serde_json::from_slice::<SnapshotHeader>(&bytes) {
+      Ok(header) if header.version != SNAPSHOT_VERSION => {
+        tracing::warn!(
+          version = header.version,
+          expected = SNAPSHOT_VERSION,
+          "CACHE_SNAPSHOT_IGNORED"
+        );
+        return Ok(0);
+      }
+      _ => match serde_json::from_slice::<Snapshot>(&bytes) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+          tracing::warn!(error = %e, "CACHE_SNAPSHOT_IGNORED");
+          return Ok(0);
+        }
+      },
+    };
+
+    let downtime = (self.clock.utc_now() - snapshot.saved_at)
+      .to_std()
+      .unwrap_or_default();
+    let now = self.clock.now();
+    let mut store = self.write().await;
+    let mut loaded = 0;
+    for entry in snapshot.entries {
+      let Some(ttl) = Duration::from_millis(entry.ttl_ms)
+        .checked_sub(downtime)
+        .filter(|ttl| !ttl.is_zero())
+      else {
+        continue;
+      };
+      store.insert(
+        entry.key,
+        CacheEntry {
+          data: entry.data,
+          expires: now + ttl,
+          tags: entry.tags,
+        },
+      );
+      loaded += 1;
+    }
+    Ok(loaded)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[tokio::test]
+  async fn invalidate_tag_removes_all_tagged_keys() {
+    let cache = Cache::default();
+    let ttl = Duration::from_secs(60);
+    cache
+      .set_with_tags("property:1".into(), json!(1), ttl, &["property:1"])
+      .await;
+    cache
+      .set_with_tags(
+        "listing".into(),
+        json!([1]),
+        ttl,
+        &["property:1", "listing"],
+      )
+      .await;
+    cache.set("other".into(), json!(true)).await;
+
+    assert_eq!(cache.invalidate_tag("property:1").await, 2);
+    assert!(cache.get("property:1").await.is_none());
+    assert!(cache.get("listing").await.is_none());
+    assert!(cache.get("other").await.is_some());
+    assert!(cache.store.read().await.tags.is_empty());
+  }
+
+  #[tokio::test]
+  async fn entries_expire_when_the_clock_passes_their_ttl() {
+    let clock = crate::utils::MockClock::new();
+    let cache = Cache::with_clock(Duration::from_secs(60), Arc::new(clock.clone()));
+    cache.set("k".into(), json!(1)).await;
+
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(cache.get("k").await, Some(json!(1)));
+    clock.advance(Duration::from_secs(1));
+    assert!(cache.get("k").await.is_none());
+    assert_eq!(cache.stats().await.expired, 1);
+  }
+
+  #[tokio::test]
+  async fn global_is_one_store_and_default_is_not() {
+    Cache::global().set("global:k".into(), json!(1)).await;
+    assert_eq!(Cache::global().get("global:k").await, Some(json!(1)));
+    assert!(Cache::default().get("global:k").await.is_none());
+    Cache::global().delete("global:k").await;
+  }
+
+  #[tokio::test]
+  async fn clones_share_one_store() {
+    let cache = Cache::default();
+    let handle = cache.clone();
+    handle.set("k".into(), json!(1)).await;
+    assert_eq!(cache.get("k").await, Some(json!(1)));
+    cache.delete("k").await;
+    assert!(handle.get("k").await.is_none());
+  }
+
+  #[tokio::test]
+  async fn bulk_get_and_set_take_the_lock_once() {
+    use std::sync::atomic::Ordering;
+
+    let clock = crate::utils::MockClock::new();
+    let cache = Cache::with_clock(Duration::from_secs(60), Arc::new(clock.clone()));
+    let keys: Vec<String> = (0..100).map(|i| format!("property:{i}")).collect();
+    cache
+      .set_many(keys.iter().take(50).map(|k| (k.clone(), json!(k))))
+      .await;
+    cache
+      .set_with_tags("stale".into(), json!(0), Duration::from_secs(1), &[])
+      .await;
+    clock.advance(Duration::from_secs(1));
+    let before = cache.lock_acquisitions.load(Ordering::Relaxed);
+
+    let mut wanted: Vec<&str> = keys.iter().map(String::as_str).collect();
+    wanted.push("stale");
+    let hits = cache.get_many(&wanted).await;
+
+    assert_eq!(cache.lock_acquisitions.load(Ordering::Relaxed) - before, 1);
+    assert_eq!(hits.len(), 50);
+    assert_eq!(hits["property:0"], json!("property:0"));
+    assert!(!hits.contains_key("property:50") && !hits.contains_key("stale"));
+  }
+
+  #[tokio::test]
+  async fn overwrite_replaces_tags() {
+    let cache = Cache::default();
+    let ttl = Duration::from_secs(60);
+    cache.set_with_tags("k".into(), json!(1), ttl, &["a"]).await;
+    cache.set_with_tags("k".into(), json!(2), ttl, &["b"]).await;
+
+    assert_eq!(cache.invalidate_tag("a").await, 0);
+    assert_eq!(cache.invalidate_tag("b").await, 1);
+  }
+
+  #[tokio::test]
+  async fn concurrent_increments_are_not_lost() {
+    let cache = Cache::default();
+    let handles: Vec<_> = (0..64)
+      .map(|_| {
+        let cache = cache.clone();
+        tokio::spawn(async move {
+          for _ in 0..50 {
+            cache.increment("views:property:1", 1).await;
+          }
+        })
+      })
+      .collect();
+    for handle in handles {
+      handle.await.unwrap();
+    }
+    assert_eq!(cache.get("views:property:1").await, Some(json!(3200)));
+    assert_eq!(cache.decrement("views:property:1", 200).await, 3000);
+  }
+
+  #[tokio::test]
+  async fn expired_or_non_numeric_counters_restart() {
+    let cache = Cache::default();
+    cache
+      .set_with_tags("hits".into(), json!(41), Duration::ZERO, &["t"])
+      .await;
+    assert_eq!(cache.increment("hits", 1).await, 1);
+    // The restarted counter no longer carries the expired entry's tags
+    assert_eq!(cache.invalidate_tag("t").await, 0);
+
+    cache.set("hits".into(), json!("n/a")).await;
+    assert_eq!(cache.increment("hits", 2).await, 2);
+  }
+
+  #[tokio::test]
+  async fn evict_to_drops_expired_then_soonest_to_expire() {
+    let cache = Cache::default();
+    cache
+      .set_with_tags("stale".into(), json!(0), Duration::ZERO, &["t"])
+      .await;
+    for (key, secs) in [("soon", 10), ("later", 20), ("latest", 30)] {
+      cache
+        .set_with_tags(key.into(), json!(secs), Duration::from_secs(secs), &["t"])
+        .await;
+    }
+
+    assert_eq!(cache.evict_to(2).await, 2);
+    assert!(cache.get("soon").await.is_none());
+    assert!(cache.get("later").await.is_some());
+    assert!(cache.get("latest").await.is_some());
+    assert_eq!(cache.stats().await.entries, 2);
+    assert_eq!(cache.evict_to(5).await, 0);
+    assert_eq!(cache.invalidate_tag("t").await, 2);
+  }
+
+  #[tokio::test]
+  async fn purge_expired_cleans_tag_index() {
+    let cache = Cache::default();
+    cache
+      .set_with_tags("k".into(), json!(1), Duration::ZERO, &["a"])
+      .await;
+
+    assert_eq!(cache.purge_expired().await, 1);
+    assert!(cache.store.read().await.tags.is_empty());
+  }
+
+  #[tokio::test]
+  async fn snapshot_round_trip_keeps_ttl_and_tags() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("cache.json");
+    let cache = Cache::default();
+    let ttl = Duration::from_secs(60);
+    cache
+      .set_with_tags("k".into(), json!({"a": 1}), ttl, &["t"])
+      .await;
+    cache
+      .set_with_tags("gone".into(), json!(1), Duration::ZERO, &[])
+      .await;
+
+    assert_eq!(cache.save_snapshot(&path).await.unwrap(), 1);
+
+    let restored = Cache::default();
+    assert_eq!(restored.load_snapshot(&path).await.unwrap(), 1);
+    assert_eq!(restored.get("k").await, Some(json!({"a": 1})));
+    let expires = restored.store.read().await.entries["k"].expires;
+    assert!(expires <= Instant::now() + ttl);
+    assert_eq!(restored.invalidate_tag("t").await, 1);
+  }
+
+  #[tokio::test]
+  async fn unusable_snapshots_are_ignored() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let cache = Cache::default();
+
+    assert_eq!(
+      cache
+        .load_snapshot(&dir.path().join("missing.json"))
+        .await
+        .unwrap(),
+      0
+    );
+
+    let other_version = dir.path().join("v99.json");
+    std::fs::write(
+      &other_version,
+      r#"{"version": 99, "entries": "new format"}"#,
+    )
+    .unwrap();
+    assert_eq!(cache.load_snapshot(&other_version).await.unwrap(), 0);
+
+    let garbage = dir.path().join("garbage.json");
+    std::fs::write(&garbage, "not json").unwrap();
+    assert_eq!(cache.load_snapshot(&garbage).await.unwrap(), 0);
   }
 }
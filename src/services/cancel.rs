@@ -0,0 +1,93 @@
+//! Cancellation of pooled database work when the awaiting request goes away.
+//!
+//! Axum drops a handler's future when the client disconnects or the timeout
+//! layer fires, but a `spawn_blocking` closure already handed to the blocking
+//! pool keeps running and holds its connection. [`CancelOnDrop`] lives in the
+//! awaiting future and flips a shared flag when that future is dropped; the
+//! blocking side checks the flag once it has a connection and bails out with
+//! [`Cancelled`] instead of running the operation.
+//!
+//! SQLite has no way to interrupt a statement that is already executing
+//! (Diesel does not expose `sqlite3_interrupt`), so a query that started
+//! before the drop runs to completion. `DBPostgres` additionally sends
+//! `pg_cancel_backend` for a query that is in flight.
+//!
+//! The blocking task itself can also end without running: when the runtime
+//! shuts down while it is still queued, its `JoinHandle` reports it
+//! cancelled. [`join_error`] turns that into [`Cancelled`] too, and a panic in
+//! the operation into `DB_TASK_PANICKED`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::task::JoinError;
+
+/// Returned by `execute`/`transaction` when the caller stopped awaiting before
+/// the operation started (or, on Postgres, while it was running), or when the
+/// runtime shut down before the blocking task ran.
+///
+/// Match it with `err.downcast_ref::<Cancelled>()`.
+#[derive(Debug, thiserror::Error)]
+#[error("DB_OPERATION_CANCELLED")]
+pub struct Cancelled;
+
+/// Sets the shared flag when dropped. Keep it alive for as long as the caller
+/// is awaiting the blocking task.
+#[derive(Debug, Default)]
+pub struct CancelOnDrop {
+  cancelled: Arc<AtomicBool>,
+}
+
+impl CancelOnDrop {
+  /// Handle for the blocking side; see [`CancelFlag::check`].
+  pub fn flag(&self) -> CancelFlag {
+    CancelFlag(self.cancelled.clone())
+  }
+}
+
+impl Drop for CancelOnDrop {
+  fn drop(&mut self) {
+    self.cancelled.store(true, Ordering::Release);
+  }
+}
+
+/// Blocking-side view of a [`CancelOnDrop`].
+#[derive(Debug, Clone)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Acquire)
+  }
+
+  /// `Err(Cancelled)` once the awaiting future has been dropped.
+  pub fn check(&self) -> Result<(), Cancelled> {
+    if self.is_cancelled() {
+      tracing::debug!("DB_OPERATION_CANCELLED");
+      return Err(Cancelled);
+    }
+    Ok(())
+  }
+}
+
+/// Error for a blocking DB task that did not return: [`Cancelled`] when the
+/// runtime dropped it unstarted, `DB_TASK_PANICKED` when the operation
+/// panicked.
+///
+/// Neither case leaks a connection. An unstarted task never checked one out,
+/// and a panicking one drops its `PooledConnection` while unwinding; the pool
+/// sees the thread panicking, discards that connection instead of reusing it
+/// (it may be mid-transaction) and opens a replacement.
+pub fn join_error(err: JoinError) -> anyhow::Error {
+  if err.is_cancelled() {
+    tracing::debug!("DB_TASK_CANCELLED");
+    return Cancelled.into();
+  }
+  let payload = err.into_panic();
+  let message = payload
+    .downcast_ref::<&str>()
+    .copied()
+    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+    .unwrap_or("non-string panic payload");
+  tracing::error!(panic = message, "DB_TASK_PANICKED");
+  anyhow::anyhow!("DB_TASK_PANICKED: {}", message)
+}
@@ -0,0 +1,62 @@
+use crate::utils::http_client::HttpClient;
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use std::future::Future;
+
+/// Destination that published events are delivered to.
+///
+/// Delivery is at-least-once: callers retry on `Err`, so implementations
+/// should be safe to call again with the same event, and must pass
+/// `dedup_key` (the outbox event's) on so consumers can drop duplicates.
+pub trait EventSink: Send + Sync {
+  fn publish(
+    &self,
+    event_type: &str,
+    dedup_key: &str,
+    payload: &Value,
+  ) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Delivers events as JSON `POST`s to a webhook URL.
+///
+/// The body is `{ "type": <event_type>, "dedupKey": <dedup_key>, "payload":
+/// <payload> }`. Transient
+/// failures and `Retry-After` on `429`/`503` are handled by [`HttpClient`],
+/// whose retries draw on its [`RetryBudget`](crate::utils::retry::RetryBudget)
+/// (the process-wide one by default; pass a client built with
+/// `with_retry_budget` to isolate a sink). Any non-2xx status left after
+/// retries is returned as an error.
+#[derive(Clone, Debug)]
+pub struct WebhookSink {
+  client: HttpClient,
+  url: String,
+}
+
+impl WebhookSink {
+  pub fn new(
+    client: HttpClient,
+    url: impl Into<String>,
+  ) -> Self {
+    Self {
+      client,
+      url: url.into(),
+    }
+  }
+}
+
+impl EventSink for WebhookSink {
+  async fn publish(
+    &self,
+    event_type: &str,
+    dedup_key: &str,
+    payload: &Value,
+  ) -> Result<()> {
+    let body = json!({ "type": event_type, "dedupKey": dedup_key, "payload": payload });
+    let response = self.client.post_json(&self.url, &body).await?;
+    let status = response.status();
+    if !status.is_success() {
+      return Err(anyhow!("WEBHOOK_DELIVERY_FAILED: {}", status));
+    }
+    Ok(())
+  }
+}
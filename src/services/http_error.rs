@@ -1,4 +1,5 @@
-use crate::services::HttpResponseFormat;
+use crate::i18n::{self, Locale};
+use crate::services::{HttpResponseFormat, StaleVersion};
 use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
@@ -10,7 +11,7 @@ use axum::response::{IntoResponse, Response};
 ///
 /// # Example JSON
 /// ```json
-/// { "success": false, "message": "ERR013-INVALID_CREDENTIALS" }
+/// { "success": false, "message": "ERR013|Invalid email or password" }
 /// ```
 #[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -137,14 +138,27 @@ pub enum HttpError {
   #[error("ERR032|INVALID_PATH_PARAM:{0}")]
   ERR032(String),
 
+  /// `400 Bad Request` — a query parameter is malformed or out of range.
+  #[error("ERR041|INVALID_QUERY_PARAM:{0}")]
+  ERR041(String),
+
+  /// `400 Bad Request` — headers the route requires are absent (names listed).
+  #[error("ERR042|MISSING_REQUIRED_HEADERS:{0}")]
+  ERR042(String),
+
   /// `400 Bad Request` — JSON request body is malformed.
   #[error("ERR033|INVALID_BODY_REQUEST:{0}")]
   ERR033(String),
 
-  /// `400 Bad Request` — request body failed validation rules.
+  /// `422 Unprocessable Entity` — request body parsed but failed validation rules.
   #[error("ERR034|INVALID_VALIDATION:{0}")]
   ERR034(String),
 
+  /// `422 Unprocessable Entity` — an array body holds more items than the
+  /// endpoint's [`BoundedVec`](crate::models::BoundedVec) allows.
+  #[error("ERR046|TOO_MANY_ITEMS:{0}")]
+  ERR046(String),
+
   /// `400 Bad Request` — multipart form data could not be parsed.
   #[error("ERR035|INVALID_MULTIPART_DATA:{0}")]
   ERR035(String),
@@ -178,6 +192,22 @@ pub enum HttpError {
   #[error("ERR408|REQUEST_TIMED_OUT")]
   ERR408,
 
+  /// `413 Payload Too Large` — the body is over the endpoint's limit (bytes).
+  #[error("ERR413|PAYLOAD_TOO_LARGE:{0}")]
+  ERR413(usize),
+
+  /// `415 Unsupported Media Type` — `Content-Type` is not one the endpoint accepts.
+  #[error("ERR415|UNSUPPORTED_MEDIA_TYPE:{0}")]
+  ERR415(String),
+
+  /// `429 Too Many Requests` — over the rate limit; see `middlewares::rate_limit`.
+  #[error("ERR429|TOO_MANY_REQUESTS")]
+  ERR429,
+
+  /// `431 Request Header Fields Too Large` — over `MAX_HEADER_COUNT` / `MAX_HEADER_BYTES`.
+  #[error("ERR431|REQUEST_HEADER_FIELDS_TOO_LARGE")]
+  ERR431,
+
   /// `500 Internal Server Error` — an unexpected error occurred.
   #[error("ERR043|UNEXPECTED_ERROR_OCCURRED")]
   ERR043,
@@ -186,6 +216,15 @@ pub enum HttpError {
   #[error("ERR404|RESOURCE_NOT_FOUND")]
   ERR404,
 
+  // ── Generic ───────────────────────────────────────────────────────────────
+  /// `403 Forbidden` — authenticated, but not allowed to access this resource.
+  #[error("ERR044|FORBIDDEN")]
+  ERR044,
+
+  /// `409 Conflict` — the request conflicts with an existing resource.
+  #[error("ERR045|RESOURCE_CONFLICT:{0}")]
+  ERR045(String),
+
   // ── Health ────────────────────────────────────────────────────────────────
   /// `503 Service Unavailable` — DB unreachable (readiness probe only).
   #[error("SERVICE_UNAVAILABLE")]
@@ -201,6 +240,71 @@ pub enum HttpError {
 }
 
 impl HttpError {
+  /// Returns the stable translation key for this variant (e.g. `"INVALID_CREDENTIALS"`).
+  ///
+  /// The key is the part of the `#[error("…")]` string after the code and is looked
+  /// up in the [`i18n`](crate::i18n) tables when rendering the response.
+  pub fn key(&self) -> &'static str {
+    match self {
+      Self::ERR010 => "EMAIL_ALREADY_EXISTS",
+      Self::ERR011 => "PASSWORD_HASH_FAILED",
+      Self::ERR013 => "INVALID_CREDENTIALS",
+      Self::ERR014 => "INVALID_REFRESH_TOKEN",
+      Self::ERR015 => "INVALID_TOKEN_EXPIRY_FORMAT",
+      Self::ERR016 => "REFRESH_TOKEN_EXPIRED",
+      Self::ERR017 => "TOKEN_CREATE_FAILED",
+      Self::ERR018 => "INVALID_TOKEN",
+      Self::ERR019 => "EXPIRED_SIGNATURE",
+      Self::ERR020 => "INVALID_SIGNATURE",
+      Self::ERR021 => "UNAUTHORIZED",
+      Self::ERR022 => "MISSING_OR_INVALID_AUTHORIZATION_HEADER",
+      Self::ERR023 => "ATTACHMENT_NOT_FOUND",
+      Self::ERR024 => "NO_FILE_PROVIDED",
+      Self::ERR025 => "EMPTY_FILE",
+      Self::ERR026(_) => "INVALID_FILE_TYPE",
+      Self::ERR027 => "INVALID_FILENAME",
+      Self::ERR029 => "FILE_ALREADY_EXISTS",
+      Self::ERR030 => "FILE_UPLOAD_FAILED",
+      Self::ERR031(_) => "FILE_TOO_LARGE",
+      Self::ERR032(_) => "INVALID_PATH_PARAM",
+      Self::ERR041(_) => "INVALID_QUERY_PARAM",
+      Self::ERR042(_) => "MISSING_REQUIRED_HEADERS",
+      Self::ERR033(_) => "INVALID_BODY_REQUEST",
+      Self::ERR034(_) => "INVALID_VALIDATION",
+      Self::ERR046(_) => "TOO_MANY_ITEMS",
+      Self::ERR035(_) => "INVALID_MULTIPART_DATA",
+      Self::ERR036(_) => "INVALID_MULTIPART_FIELD",
+      Self::ERR037(_) => "FAILED_TO_READ_FILE",
+      Self::ERR038(_) => "FAILED_TO_READ_FIELD",
+      Self::ERR039(_) => "TOO_MANY_FILES",
+      Self::ERR040(_) => "INVALID_FIELD_SERIALIZATION",
+      Self::ERR400(_) => "INVALID_FIELD_FORMAT",
+      Self::ERR408 => "REQUEST_TIMED_OUT",
+      Self::ERR413(_) => "PAYLOAD_TOO_LARGE",
+      Self::ERR415(_) => "UNSUPPORTED_MEDIA_TYPE",
+      Self::ERR429 => "TOO_MANY_REQUESTS",
+      Self::ERR431 => "REQUEST_HEADER_FIELDS_TOO_LARGE",
+      Self::ERR043 => "UNEXPECTED_ERROR_OCCURRED",
+      Self::ERR404 => "RESOURCE_NOT_FOUND",
+      Self::ERR044 => "FORBIDDEN",
+      Self::ERR045(_) => "RESOURCE_CONFLICT",
+      Self::ERR503 => "SERVICE_UNAVAILABLE",
+      Self::ERR500(_) => "SOMETHING_WENT_WRONG",
+    }
+  }
+
+  /// Renders the response message in `locale`, keeping the error code and any
+  /// dynamic context: `ERR013|INVALID_CREDENTIALS` becomes
+  /// `ERR013|Email atau kata sandi salah` for `id`.
+  pub fn localized_message(
+    &self,
+    locale: Locale,
+  ) -> String {
+    self
+      .to_string()
+      .replacen(self.key(), &i18n::translate(locale, self.key()), 1)
+  }
+
   /// Returns the [`StatusCode`] that corresponds to this error variant.
   pub fn status(&self) -> StatusCode {
     match self {
@@ -221,7 +325,8 @@ impl HttpError {
       | Self::ERR031(_)
       | Self::ERR032(_)
       | Self::ERR033(_)
-      | Self::ERR034(_)
+      | Self::ERR041(_)
+      | Self::ERR042(_)
       | Self::ERR035(_)
       | Self::ERR036(_)
       | Self::ERR037(_)
@@ -229,20 +334,31 @@ impl HttpError {
       | Self::ERR039(_)
       | Self::ERR040(_)
       | Self::ERR400(_) => StatusCode::BAD_REQUEST,
-      Self::ERR029 | Self::ERR010 => StatusCode::CONFLICT,
+      Self::ERR034(_) | Self::ERR046(_) => StatusCode::UNPROCESSABLE_ENTITY,
+      Self::ERR044 => StatusCode::FORBIDDEN,
+      Self::ERR029 | Self::ERR010 | Self::ERR045(_) => StatusCode::CONFLICT,
       Self::ERR408 => StatusCode::REQUEST_TIMEOUT,
+      Self::ERR413(_) => StatusCode::PAYLOAD_TOO_LARGE,
+      Self::ERR415(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+      Self::ERR429 => StatusCode::TOO_MANY_REQUESTS,
+      Self::ERR431 => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
       Self::ERR503 => StatusCode::SERVICE_UNAVAILABLE,
       _ => StatusCode::INTERNAL_SERVER_ERROR,
     }
   }
 }
 
-/// Converts an [`anyhow::Error`] into [`HttpError::ERR500`].
+/// Converts an [`anyhow::Error`] into [`HttpError::ERR500`], except a
+/// [`StaleVersion`], which becomes `ERR045` (409).
 ///
 /// Logs the original error at `ERROR` level before wrapping so the full
 /// context is visible in server logs even though the HTTP response is generic.
 impl From<anyhow::Error> for HttpError {
   fn from(e: anyhow::Error) -> Self {
+    let e = match e.downcast::<StaleVersion>() {
+      Ok(stale) => return stale.into(),
+      Err(e) => e,
+    };
     tracing::error!(error = %e, "unhandled anyhow error");
     Self::ERR500(e)
   }
@@ -258,12 +374,13 @@ impl From<String> for HttpError {
 /// Serialises this error into an Axum [`Response`].
 ///
 /// The response body is a [`HttpResponseFormat`] JSON object with `success: false`
-/// and `message` set to the variant's `#[error("…")]` string.
+/// and `message` set to the variant's `#[error("…")]` string, with the key
+/// translated into the request's [`Locale`] (English when none was resolved).
 impl IntoResponse for HttpError {
   fn into_response(self) -> Response {
     let body = HttpResponseFormat {
       success: false,
-      message: self.to_string(),
+      message: self.localized_message(Locale::current()),
       data: None::<serde_json::Value>,
     };
     (self.status(), Json(body)).into_response()
@@ -1,5 +1,5 @@
 use axum::Json;
-use axum::http::StatusCode;
+use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use std::fmt;
 use std::fmt::Display;
@@ -135,3 +135,183 @@ impl<T: Serialize> IntoResponse for HttpResponse<T> {
     self.into_http_response()
   }
 }
+
+/// `201 Created` with a `Location` header pointing at the new resource.
+///
+/// The body is the usual [`HttpResponseFormat`] envelope with message
+/// `CREATED` unless overridden with [`Created::message`].
+///
+/// ```rust,ignore
+/// let user = service::create(&state.db, payload).await?;
+/// Ok(Created::new(format!("/users/{}", user.id), UserResponse::from(user)))
+/// ```
+#[derive(Debug, Clone)]
+pub struct Created<T: Serialize> {
+  /// Path or absolute URL of the created resource.
+  pub location: String,
+  /// Representation of the created resource.
+  pub data: T,
+  /// Status message forwarded into [`HttpResponseFormat`].
+  pub message: String,
+}
+
+impl<T: Serialize> Created<T> {
+  pub fn new(
+    location: impl Into<String>,
+    data: T,
+  ) -> Self {
+    Created {
+      location: location.into(),
+      data,
+      message: "CREATED".to_string(),
+    }
+  }
+
+  /// Replaces the default `CREATED` message.
+  pub fn message(
+    mut self,
+    msg: &str,
+  ) -> Self {
+    self.message = msg.to_string();
+    self
+  }
+}
+
+impl<T: Serialize> IntoResponse for Created<T> {
+  fn into_response(self) -> Response {
+    // Locations are built by handlers from ids and route paths, so an
+    // invalid header value is a bug rather than bad input.
+    let Ok(location) = HeaderValue::try_from(self.location.as_str()) else {
+      tracing::error!(location = %self.location, "CREATED_LOCATION_INVALID");
+      return crate::services::HttpError::ERR043.into_response();
+    };
+    let mut res =
+      HttpResponse::new(self.message, StatusCode::CREATED, Some(self.data)).into_response();
+    res.headers_mut().insert(header::LOCATION, location);
+    res
+  }
+}
+
+/// `202 Accepted` for work that was queued rather than done, e.g. an event
+/// written to the outbox for a background relay.
+///
+/// `Location` points at `status_url` when set, so the client knows where to
+/// poll for the outcome. The body is the usual [`HttpResponseFormat`]
+/// envelope with message `ACCEPTED` unless overridden with
+/// [`Accepted::message`]; `data` usually carries the job id.
+///
+/// ```rust,ignore
+/// let event_id = OutboxWriter::new(state.db.clone()).enqueue("report.requested", &payload).await?;
+/// Ok(Accepted::new(JobReceipt { event_id }).status_url(format!("/reports/jobs/{event_id}")))
+/// ```
+#[derive(Debug, Clone)]
+pub struct Accepted<T: Serialize> {
+  /// Path or absolute URL where the job's status can be polled.
+  pub status_url: Option<String>,
+  /// Representation of the queued job.
+  pub data: T,
+  /// Status message forwarded into [`HttpResponseFormat`].
+  pub message: String,
+}
+
+impl<T: Serialize> Accepted<T> {
+  pub fn new(data: T) -> Self {
+    Accepted {
+      status_url: None,
+      data,
+      message: "ACCEPTED".to_string(),
+    }
+  }
+
+  /// Sets the `Location` the client polls for the job's status.
+  pub fn status_url(
+    mut self,
+    url: impl Into<String>,
+  ) -> Self {
+    self.status_url = Some(url.into());
+    self
+  }
+
+  /// Replaces the default `ACCEPTED` message.
+  pub fn message(
+    mut self,
+    msg: &str,
+  ) -> Self {
+    self.message = msg.to_string();
+    self
+  }
+}
+
+impl<T: Serialize> IntoResponse for Accepted<T> {
+  fn into_response(self) -> Response {
+    // Built by handlers from job ids, like `Created::location`
+    let location = match self.status_url.as_deref().map(HeaderValue::try_from) {
+      None => None,
+      Some(Ok(location)) => Some(location),
+      Some(Err(_)) => {
+        tracing::error!(status_url = ?self.status_url, "ACCEPTED_STATUS_URL_INVALID");
+        return crate::services::HttpError::ERR043.into_response();
+      }
+    };
+    let mut res =
+      HttpResponse::new(self.message, StatusCode::ACCEPTED, Some(self.data)).into_response();
+    if let Some(location) = location {
+      res.headers_mut().insert(header::LOCATION, location);
+    }
+    res
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, body::Body, extract::Request, routing::post};
+  use tower::ServiceExt;
+
+  #[tokio::test]
+  async fn created_sets_status_and_location() {
+    let app: Router = Router::new().route(
+      "/widgets",
+      post(|| async { Created::new("/widgets/42", serde_json::json!({ "id": 42 })) }),
+    );
+    let res = app
+      .oneshot(Request::post("/widgets").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(res.headers()[header::LOCATION], "/widgets/42");
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["message"], "CREATED");
+    assert_eq!(body["data"]["id"], 42);
+  }
+
+  #[tokio::test]
+  async fn accepted_sets_status_and_optional_location() {
+    let res = Accepted::new(serde_json::json!({ "jobId": 7 }))
+      .status_url("/jobs/7")
+      .into_response();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert_eq!(res.headers()[header::LOCATION], "/jobs/7");
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["message"], "ACCEPTED");
+    assert_eq!(body["data"]["jobId"], 7);
+
+    let res = Accepted::new(()).into_response();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    assert!(res.headers().get(header::LOCATION).is_none());
+  }
+
+  #[tokio::test]
+  async fn invalid_location_is_a_server_error() {
+    let res = Created::new("/widgets/\n42\n", ()).into_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(res.headers().get(header::LOCATION).is_none());
+  }
+}
@@ -0,0 +1,213 @@
+use crate::services::{Cache, DBSqlite, OutboxWriter};
+use crate::utils::tasks::BackgroundTasks;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Latest sampled values of the process gauges.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct MetricsSnapshot {
+  /// Open connections in the DB pool (idle + checked out).
+  pub db_pool_connections: u32,
+  /// Idle connections in the DB pool.
+  pub db_pool_idle: u32,
+  /// Configured maximum DB pool size.
+  pub db_pool_max: u32,
+  /// Checked-out share of `db_pool_max`, `0.0`–`1.0`; alert when it stays
+  /// near `1.0`.
+  pub db_pool_utilization: f64,
+  /// Entries in the shared cache, including expired ones not yet purged.
+  pub cache_entries: usize,
+  /// Expired cache entries awaiting purge.
+  pub cache_expired: usize,
+  /// Outbox events not yet published; alert when it keeps growing.
+  pub outbox_backlog: i64,
+  /// Seconds since the oldest unpublished outbox event was recorded; `None`
+  /// when nothing is pending.
+  pub outbox_oldest_pending_secs: Option<i64>,
+  /// When the gauges were last sampled; `None` before the first sample.
+  pub sampled_at: Option<DateTime<Utc>>,
+}
+
+/// Shared gauge registry. Cloning is cheap and shares the values.
+///
+/// Updated on a timer by [`spawn_sampler`] so readers (a scrape handler, a
+/// push exporter) always see recent values without touching the pool or the
+/// cache lock themselves.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+  gauges: Arc<RwLock<MetricsSnapshot>>,
+}
+
+impl Metrics {
+  pub fn snapshot(&self) -> MetricsSnapshot {
+    self.gauges.read().unwrap().clone()
+  }
+
+  /// Samples `db` and `cache` once and stores the result.
+  ///
+  /// The outbox gauges query `db`; if that fails they keep their previous
+  /// values and `METRICS_OUTBOX_SAMPLE_FAILURE` is logged.
+  pub async fn sample(
+    &self,
+    db: &DBSqlite,
+    cache: &Cache,
+  ) {
+    let (connections, idle) = db.pool_stats();
+    let cache_stats = cache.stats().await;
+    let previous = self.snapshot();
+    let outbox = OutboxWriter::new(db.clone());
+    let (outbox_backlog, outbox_oldest_pending_secs) =
+      match tokio::try_join!(outbox.backlog_count(), outbox.oldest_pending_age()) {
+        Ok((backlog, age)) => (backlog, age.map(|age| age.num_seconds())),
+        Err(e) => {
+          tracing::warn!(error = %e, "METRICS_OUTBOX_SAMPLE_FAILURE");
+          (previous.outbox_backlog, previous.outbox_oldest_pending_secs)
+        }
+      };
+    *self.gauges.write().unwrap() = MetricsSnapshot {
+      db_pool_connections: connections,
+      db_pool_idle: idle,
+      db_pool_max: db.pool_max_size(),
+      db_pool_utilization: db.pool_utilization(),
+      cache_entries: cache_stats.entries,
+      cache_expired: cache_stats.expired,
+      outbox_backlog,
+      outbox_oldest_pending_secs,
+      sampled_at: Some(Utc::now()),
+    };
+  }
+}
+
+/// Why a JSON body was rejected, used as the `kind` label of
+/// [`record_json_rejection`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonRejectionKind {
+  /// `Content-Type` is not `application/json`.
+  MissingContentType,
+  /// The body is not valid JSON.
+  Syntax,
+  /// Valid JSON that does not match the expected type (missing or mistyped fields).
+  Data,
+  /// The body could not be read (too large, connection dropped).
+  Body,
+}
+
+/// Route label for requests that did not match a route.
+const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// `route -> kind -> count`. Routes are matched route templates
+/// (`/users/{id}`), never raw URIs, so the label set stays bounded by the
+/// router's routes times the four kinds.
+type JsonRejectionCounts = BTreeMap<String, BTreeMap<JsonRejectionKind, u64>>;
+
+static JSON_REJECTIONS: LazyLock<Mutex<JsonRejectionCounts>> = LazyLock::new(Default::default);
+
+/// One `json_body_rejections_total` series.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct JsonRejectionCount {
+  pub route: String,
+  pub kind: JsonRejectionKind,
+  pub count: u64,
+}
+
+/// Increments the malformed-JSON counter for `route` (the `MatchedPath`;
+/// `None` when no route matched) and `kind`.
+pub fn record_json_rejection(
+  route: Option<&str>,
+  kind: JsonRejectionKind,
+) {
+  let route = route.unwrap_or(UNMATCHED_ROUTE);
+  let mut counts = JSON_REJECTIONS.lock().unwrap();
+  // Look up by `&str` first so only a route's first rejection allocates.
+  let by_kind = match counts.get_mut(route) {
+    Some(by_kind) => by_kind,
+    None => counts.entry(route.to_string()).or_default(),
+  };
+  *by_kind.entry(kind).or_default() += 1;
+}
+
+/// Current value of every malformed-JSON counter series, ordered by route.
+pub fn json_rejections() -> Vec<JsonRejectionCount> {
+  JSON_REJECTIONS
+    .lock()
+    .unwrap()
+    .iter()
+    .flat_map(|(route, by_kind)| {
+      by_kind.iter().map(|(kind, count)| JsonRejectionCount {
+        route: route.clone(),
+        kind: *kind,
+        count: *count,
+      })
+    })
+    .collect()
+}
+
+/// Starts the `metrics_sampler` task, which refreshes `metrics` every
+/// `interval` (`METRICS_INTERVAL_SECS`) until shutdown.
+///
+/// Runs under [`BackgroundTasks`], so it is restarted if it panics and is
+/// stopped and awaited during graceful shutdown. Missed ticks are delayed
+/// rather than bursted.
+pub fn spawn_sampler(
+  tasks: &mut BackgroundTasks,
+  metrics: Metrics,
+  db: DBSqlite,
+  cache: Cache,
+  interval: Duration,
+) {
+  tasks.spawn("metrics_sampler", move |mut shutdown| {
+    let (metrics, db, cache) = (metrics.clone(), db.clone(), cache.clone());
+    async move {
+      let mut ticker = tokio::time::interval(interval);
+      ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+      loop {
+        tokio::select! {
+          _ = ticker.tick() => metrics.sample(&db, &cache).await,
+          _ = shutdown.changed() => break,
+        }
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use tempfile::NamedTempFile;
+
+  #[tokio::test(start_paused = true)]
+  async fn sampler_updates_gauges_until_shutdown() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let cache = Cache::default();
+    let metrics = Metrics::default();
+    let mut tasks = BackgroundTasks::new();
+
+    spawn_sampler(
+      &mut tasks,
+      metrics.clone(),
+      db.clone(),
+      cache.clone(),
+      Duration::from_secs(15),
+    );
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    let first = metrics.snapshot();
+    assert_eq!(first.db_pool_max, db.pool_max_size());
+    assert_eq!(first.db_pool_utilization, 0.0);
+    assert_eq!(first.cache_entries, 0);
+
+    cache.set("key".into(), json!(1)).await;
+    tokio::time::sleep(Duration::from_secs(15)).await;
+    assert_eq!(metrics.snapshot().cache_entries, 1);
+
+    tasks.shutdown().await;
+    cache.set("other".into(), json!(2)).await;
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    assert_eq!(metrics.snapshot().cache_entries, 1);
+  }
+}
@@ -1,9 +1,44 @@
+pub mod analytics;
+pub mod app_error;
+pub mod audit;
+pub mod cache;
+pub mod cancel;
+pub mod db_url;
+pub mod event_sink;
 pub mod http_error;
 pub mod http_response;
+pub mod metrics;
+pub mod outbox;
+#[cfg(feature = "postgres")]
+pub mod pg_types;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod query_counter;
+pub mod row_stream;
+pub mod session;
+pub mod sql_log;
 pub mod sqlite;
 
+pub use analytics::{TimeRange, TimeWindow};
+pub use app_error::{AppError, StaleVersion, TooManyRows};
+pub use audit::AuditLog;
+pub use cache::{Cache, CacheStats};
+pub use cancel::Cancelled;
+pub use db_url::{DbScheme, DbUrl, InvalidDbUrl};
+pub use event_sink::{EventSink, WebhookSink};
 pub use http_error::HttpError;
 pub use http_error::HttpErrorFormat;
 pub use http_response::HttpResponse;
 pub use http_response::HttpResponseFormat;
-pub use sqlite::DBSqlite;
+pub use http_response::{Accepted, Created};
+pub use metrics::{JsonRejectionKind, Metrics, MetricsSnapshot};
+pub use outbox::{InvalidPayload, OutboxEvent, OutboxWriter, ProcessedEvents};
+#[cfg(feature = "postgres")]
+pub use postgres::{DBPostgres, DBPostgresConfig};
+pub use query_counter::QueryCounter;
+pub use row_stream::RowStream;
+pub use session::SessionStore;
+pub use sqlite::{
+  BatchResult, ConnectionCheck, DBSqlite, DBSqliteConfig, DBSqliteError, MigrationRun,
+  UpsertOutcome, spawn_pre_ping,
+};
@@ -0,0 +1,378 @@
+//! Outbox for internal events: [`OutboxWriter::enqueue`] records an event in
+//! `outbox_events` in the primary database, to be published to an
+//! [`EventSink`](super::EventSink) later by a relay. Recording is durable as
+//! soon as `enqueue` returns, so a crash before publishing delays the event
+//! instead of losing it.
+//!
+//! Rows with `published_at IS NULL` are pending; the relay sets it after a
+//! successful publish. Delivery is at-least-once: each event carries a
+//! `dedup_key` (a UUID set at enqueue and delivered with the event), and a
+//! consumer that records handled keys with [`ProcessedEvents::mark_processed`]
+//! in the same transaction as its side effects processes every event exactly
+//! once.
+//!
+//! Payloads are JSON objects, stored as text exactly as given: a key with a
+//! `null` value and an absent key stay distinct for consumers, as do number
+//! formatting and key order when the payload arrives as text
+//! ([`OutboxWriter::enqueue_raw`]). Anything else at the top level (arrays,
+//! scalars) is refused with [`InvalidPayload`].
+//!
+//! Pending rows are found through `idx_outbox_events_published_at`, so
+//! [`OutboxWriter::backlog_count`] stays cheap as published rows accumulate;
+//! keep that index if the table is ever rebuilt.
+
+use crate::{
+  schemas::table::{outbox_events, processed_events},
+  services::DBSqlite,
+  utils::generator::uuid,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::{dsl::sql, prelude::*, sql_types::Integer};
+use serde_json::Value;
+
+/// A payload that is not a JSON object. Carries the reason only.
+#[derive(Debug, thiserror::Error)]
+#[error("OUTBOX_PAYLOAD_INVALID: {0}")]
+pub struct InvalidPayload(String);
+
+/// Checks that `payload` is a single JSON object.
+pub fn check_payload(payload: &str) -> Result<(), InvalidPayload> {
+  let value: Value = serde_json::from_str(payload).map_err(|e| InvalidPayload(e.to_string()))?;
+  check_object(&value)
+}
+
+fn check_object(payload: &Value) -> Result<(), InvalidPayload> {
+  let kind = match payload {
+    Value::Object(_) => return Ok(()),
+    Value::Array(_) => "an array",
+    Value::String(_) => "a string",
+    Value::Number(_) => "a number",
+    Value::Bool(_) => "a boolean",
+    Value::Null => "null",
+  };
+  Err(InvalidPayload(format!(
+    "expected a JSON object, got {kind}"
+  )))
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = outbox_events)]
+struct NewOutboxEvent {
+  event_type: String,
+  payload: String,
+  created_at: String,
+  dedup_key: String,
+}
+
+/// A recorded event, as the relay reads it.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = outbox_events)]
+pub struct OutboxEvent {
+  pub id: i32,
+  pub event_type: String,
+  /// JSON payload, as text.
+  pub payload: String,
+  pub created_at: String,
+  pub published_at: Option<String>,
+  /// Stable across redeliveries; pass it to the sink with the payload.
+  pub dedup_key: String,
+}
+
+/// Writes events to the outbox.
+#[derive(Clone, Debug)]
+pub struct OutboxWriter {
+  db: DBSqlite,
+}
+
+impl OutboxWriter {
+  pub fn new(db: DBSqlite) -> Self {
+    Self { db }
+  }
+
+  /// Records `event_type` with `payload` as pending and returns its id.
+  ///
+  /// Fails with [`InvalidPayload`] unless `payload` is a JSON object.
+  pub async fn enqueue(
+    &self,
+    event_type: &str,
+    payload: &Value,
+  ) -> Result<i32> {
+    check_object(payload)?;
+    self.insert(event_type, payload.to_string()).await
+  }
+
+  /// [`enqueue`](Self::enqueue) for a payload that is already JSON text,
+  /// such as a received webhook body. The text is stored unchanged rather
+  /// than re-serialized, so consumers see exactly what was sent.
+  pub async fn enqueue_raw(
+    &self,
+    event_type: &str,
+    payload: &str,
+  ) -> Result<i32> {
+    check_payload(payload)?;
+    self.insert(event_type, payload.to_string()).await
+  }
+
+  async fn insert(
+    &self,
+    event_type: &str,
+    payload: String,
+  ) -> Result<i32> {
+    let event = NewOutboxEvent {
+      event_type: event_type.to_string(),
+      payload,
+      created_at: Utc::now().to_rfc3339(),
+      dedup_key: uuid(),
+    };
+    self
+      .db
+      .transaction(move |conn| {
+        diesel::insert_into(outbox_events::table)
+          .values(&event)
+          .execute(conn)
+          .map_err(|e| anyhow::anyhow!("OUTBOX_ENQUEUE_FAILED: {}", e))?;
+        Ok(diesel::select(sql::<Integer>("last_insert_rowid()")).get_result(conn)?)
+      })
+      .await
+  }
+
+  /// Event `id`, published or not; `None` when there is none.
+  pub async fn find(
+    &self,
+    id: i32,
+  ) -> Result<Option<OutboxEvent>> {
+    self
+      .db
+      .execute(move |conn| {
+        Ok(
+          outbox_events::table
+            .find(id)
+            .select(OutboxEvent::as_select())
+            .first(conn)
+            .optional()?,
+        )
+      })
+      .await
+  }
+
+  /// Up to `limit` unpublished events, oldest first.
+  pub async fn pending(
+    &self,
+    limit: i64,
+  ) -> Result<Vec<OutboxEvent>> {
+    self
+      .db
+      .execute(move |conn| {
+        Ok(
+          outbox_events::table
+            .filter(outbox_events::published_at.is_null())
+            .order(outbox_events::id)
+            .limit(limit)
+            .select(OutboxEvent::as_select())
+            .load(conn)?,
+        )
+      })
+      .await
+  }
+
+  /// Number of events not yet published (`published_at IS NULL`).
+  pub async fn backlog_count(&self) -> Result<i64> {
+    self
+      .db
+      .execute(|conn| {
+        Ok(
+          outbox_events::table
+            .filter(outbox_events::published_at.is_null())
+            .count()
+            .get_result(conn)?,
+        )
+      })
+      .await
+  }
+
+  /// Time since the oldest unpublished event was recorded; `None` when
+  /// nothing is pending. A growing age means the relay is stuck, even if
+  /// the backlog itself is small.
+  pub async fn oldest_pending_age(&self) -> Result<Option<chrono::Duration>> {
+    let created_at: Option<String> = self
+      .db
+      .execute(|conn| {
+        Ok(
+          outbox_events::table
+            .filter(outbox_events::published_at.is_null())
+            .order(outbox_events::id)
+            .select(outbox_events::created_at)
+            .first(conn)
+            .optional()?,
+        )
+      })
+      .await?;
+    created_at
+      .map(|at| {
+        let at = DateTime::parse_from_rfc3339(&at)
+          .map_err(|e| anyhow::anyhow!("OUTBOX_CREATED_AT_INVALID: {}", e))?;
+        Ok(Utc::now() - at.with_timezone(&Utc))
+      })
+      .transpose()
+  }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = processed_events)]
+struct NewProcessedEvent<'a> {
+  dedup_key: &'a str,
+  processed_at: String,
+}
+
+/// Consumer-side record of handled events, in `processed_events`.
+pub struct ProcessedEvents;
+
+impl ProcessedEvents {
+  /// Records `dedup_key` as processed; `false` when it already was, meaning
+  /// the event is a redelivery and should be skipped.
+  ///
+  /// Call it with the connection of the transaction that handles the event,
+  /// so the mark commits or rolls back together with the handling:
+  ///
+  /// ```rust,ignore
+  /// db.transaction(move |conn| {
+  ///   if !ProcessedEvents::mark_processed(conn, &dedup_key)? {
+  ///     return Ok(());
+  ///   }
+  ///   apply(conn, &payload)
+  /// })
+  /// .await
+  /// ```
+  pub fn mark_processed(
+    conn: &mut SqliteConnection,
+    dedup_key: &str,
+  ) -> Result<bool> {
+    let inserted = diesel::insert_or_ignore_into(processed_events::table)
+      .values(NewProcessedEvent {
+        dedup_key,
+        processed_at: Utc::now().to_rfc3339(),
+      })
+      .execute(conn)
+      .map_err(|e| anyhow::anyhow!("PROCESSED_EVENT_MARK_FAILED: {}", e))?;
+    Ok(inserted == 1)
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use tempfile::NamedTempFile;
+
+  #[tokio::test]
+  async fn backlog_counts_only_unpublished_events() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.run_migrations().unwrap();
+    let outbox = OutboxWriter::new(db.clone());
+    assert_eq!(outbox.backlog_count().await.unwrap(), 0);
+    assert!(outbox.oldest_pending_age().await.unwrap().is_none());
+
+    let first = outbox
+      .enqueue("user.created", &json!({"id": 1}))
+      .await
+      .unwrap();
+    outbox
+      .enqueue("user.created", &json!({"id": 2}))
+      .await
+      .unwrap();
+    assert_eq!(outbox.backlog_count().await.unwrap(), 2);
+
+    db.execute(move |conn| {
+      diesel::update(outbox_events::table.find(first))
+        .set(outbox_events::published_at.eq(Utc::now().to_rfc3339()))
+        .execute(conn)?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(outbox.backlog_count().await.unwrap(), 1);
+    let age = outbox.oldest_pending_age().await.unwrap().unwrap();
+    assert!(age >= chrono::Duration::zero() && age < chrono::Duration::minutes(1));
+  }
+
+  #[tokio::test]
+  async fn payloads_must_be_objects_and_are_stored_verbatim() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.run_migrations().unwrap();
+    let outbox = OutboxWriter::new(db.clone());
+
+    for payload in [json!([1, 2]), json!("id"), json!(7), json!(null)] {
+      let err = outbox.enqueue("user.created", &payload).await.unwrap_err();
+      assert!(err.downcast_ref::<InvalidPayload>().is_some(), "{err}");
+    }
+    let err = outbox
+      .enqueue_raw("user.created", "[{}]")
+      .await
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "OUTBOX_PAYLOAD_INVALID: expected a JSON object, got an array"
+    );
+    assert!(outbox.enqueue_raw("user.created", "{").await.is_err());
+    assert_eq!(outbox.backlog_count().await.unwrap(), 0);
+
+    let raw = r#"{"zeta": null, "alpha": 1.50}"#;
+    outbox.enqueue_raw("user.updated", raw).await.unwrap();
+    outbox
+      .enqueue("user.updated", &json!({"nickname": null}))
+      .await
+      .unwrap();
+    let pending = outbox.pending(10).await.unwrap();
+    assert_eq!(pending[0].payload, raw);
+    let payload: Value = serde_json::from_str(&pending[1].payload).unwrap();
+    assert!(payload["nickname"].is_null());
+    assert!(payload.get("email").is_none());
+  }
+
+  #[tokio::test]
+  async fn redelivered_events_are_processed_once() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.run_migrations().unwrap();
+    let outbox = OutboxWriter::new(db.clone());
+    outbox
+      .enqueue("user.created", &json!({"id": 1}))
+      .await
+      .unwrap();
+    outbox
+      .enqueue("user.created", &json!({"id": 1}))
+      .await
+      .unwrap();
+    let pending = outbox.pending(10).await.unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_ne!(pending[0].dedup_key, pending[1].dedup_key);
+
+    let key = pending[0].dedup_key.clone();
+    let failed: Result<()> = db
+      .transaction({
+        let key = key.clone();
+        move |conn| {
+          assert!(ProcessedEvents::mark_processed(conn, &key)?);
+          anyhow::bail!("HANDLER_FAILED")
+        }
+      })
+      .await;
+    assert!(failed.is_err());
+
+    // The failed attempt rolled back its mark, so the redelivery is handled
+    let handled = db
+      .transaction(move |conn| {
+        Ok((
+          ProcessedEvents::mark_processed(conn, &key)?,
+          ProcessedEvents::mark_processed(conn, &key)?,
+        ))
+      })
+      .await
+      .unwrap();
+    assert_eq!(handled, (true, false));
+  }
+}
@@ -0,0 +1,248 @@
+//! Diesel mappings for PostgreSQL `jsonb` and array columns.
+//!
+//! Compiled with the crate's `postgres` feature (`cargo build --features postgres`).
+//! Used together with [`DBPostgres`](super::postgres::DBPostgres).
+//!
+//! # Type mappings
+//!
+//! | Postgres column     | Diesel SQL type         | Rust type              |
+//! | ------------------- | ----------------------- | ---------------------- |
+//! | `jsonb`             | `Jsonb`                 | `serde_json::Value`    |
+//! | `jsonb`             | `Jsonb`                 | [`JsonbOf<T>`] (typed) |
+//! | `text[]`            | `Array<Text>`           | `Vec<String>`          |
+//! | nullable `text[]`   | `Nullable<Array<Text>>` | `Option<Vec<String>>`  |
+//! | `text[]` with NULLs | `Array<Nullable<Text>>` | `Vec<Option<String>>`  |
+//! | `bigint[]`          | `Array<BigInt>`         | `Vec<i64>`             |
+//!
+//! In `schema.rs` these columns are generated as `Jsonb` and `Array<Text>`, so
+//! the same Rust types work with the query builder (`Queryable`/`Insertable`).
+//!
+//! # Binding parameters in raw SQL
+//!
+//! ```rust,ignore
+//! use axum_starter::services::pg_types::{JsonbOf, TextArray};
+//! use diesel::sql_types::{BigInt, Jsonb};
+//! use diesel::{RunQueryDsl, sql_query};
+//!
+//! db.transaction(move |conn| {
+//!   sql_query("UPDATE documents SET metadata = $1, tags = $2 WHERE id = $3")
+//!     .bind::<Jsonb, _>(JsonbOf(metadata))
+//!     .bind::<TextArray, _>(vec!["draft".to_string(), "legal".to_string()])
+//!     .bind::<BigInt, _>(id)
+//!     .execute(conn)
+//!     .map_err(|e| anyhow::anyhow!("DB_ERROR: {}", e))
+//! })
+//! .await?;
+//! ```
+//!
+//! # Reading `jsonb` into a typed struct
+//!
+//! ```rust,ignore
+//! use axum_starter::services::pg_types::{JsonbOf, TextArray};
+//! use diesel::sql_types::{BigInt, Jsonb};
+//! use diesel::{QueryableByName, RunQueryDsl, sql_query};
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct DocumentMetadata {
+//!   title: String,
+//!   pages: u32,
+//! }
+//!
+//! #[derive(Debug, QueryableByName)]
+//! struct DocumentRow {
+//!   #[diesel(sql_type = BigInt)]
+//!   id: i64,
+//!   #[diesel(sql_type = Jsonb)]
+//!   metadata: JsonbOf<DocumentMetadata>,
+//!   #[diesel(sql_type = TextArray)]
+//!   tags: Vec<String>,
+//! }
+//!
+//! let rows: Vec<DocumentRow> = db
+//!   .execute(|conn| {
+//!     sql_query("SELECT id, metadata, tags FROM documents WHERE $1 = ANY(tags)")
+//!       .bind::<diesel::sql_types::Text, _>("legal")
+//!       .load(conn)
+//!       .map_err(|e| anyhow::anyhow!("DB_ERROR: {}", e))
+//!   })
+//!   .await?;
+//! let title = &rows[0].metadata.0.title;
+//! ```
+//!
+//! Query-builder filters on arrays use Diesel's `PgArrayExpressionMethods`,
+//! e.g. `documents::tags.contains(vec!["legal"])` (`@>`) or
+//! `documents::tags.overlaps_with(vec!["draft", "legal"])` (`&&`).
+//!
+//! Native enum types (`CREATE TYPE ... AS ENUM`) are mapped with
+//! [`DbEnum`](crate::models::db_enum::DbEnum); see [`crate::models::db_enum`].
+
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::{Array, BigInt, Jsonb, Text};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::io::Write;
+
+/// `text[]` column type.
+pub type TextArray = Array<Text>;
+
+/// `bigint[]` column type.
+pub type BigIntArray = Array<BigInt>;
+
+/// `jsonb` binary format version, written before the JSON text.
+const JSONB_VERSION: u8 = 1;
+
+/// A `jsonb` value (de)serialized through `T` instead of `serde_json::Value`.
+///
+/// Deserialization fails the row when the stored JSON does not match `T`,
+/// so keep `T` tolerant (`#[serde(default)]`) if the column has mixed shapes.
+#[derive(Debug, Clone, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Jsonb)]
+pub struct JsonbOf<T: Debug>(pub T);
+
+impl<T: Debug> JsonbOf<T> {
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl<T: Debug> From<T> for JsonbOf<T> {
+  fn from(value: T) -> Self {
+    JsonbOf(value)
+  }
+}
+
+impl<T> FromSql<Jsonb, Pg> for JsonbOf<T>
+where
+  T: DeserializeOwned + Debug,
+{
+  fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+    let bytes = value.as_bytes();
+    match bytes.split_first() {
+      Some((&JSONB_VERSION, json)) => Ok(JsonbOf(serde_json::from_slice(json)?)),
+      Some((version, _)) => Err(format!("JSONB_UNSUPPORTED_VERSION: {}", version).into()),
+      None => Err("JSONB_EMPTY".into()),
+    }
+  }
+}
+
+impl<T> ToSql<Jsonb, Pg> for JsonbOf<T>
+where
+  T: Serialize + Debug,
+{
+  fn to_sql<'b>(
+    &'b self,
+    out: &mut Output<'b, '_, Pg>,
+  ) -> serialize::Result {
+    out.write_all(&[JSONB_VERSION])?;
+    serde_json::to_writer(out, &self.0)?;
+    Ok(IsNull::No)
+  }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::db_enum::DbEnum;
+  use crate::services::postgres::DBPostgres;
+  use diesel::connection::SimpleConnection;
+  use diesel::prelude::*;
+  use diesel::query_builder::QueryId;
+  use diesel::sql_types::SqlType;
+
+  // `pub` because `table!` exposes the column's SQL type publicly.
+  #[derive(SqlType, QueryId)]
+  #[diesel(postgres_type(name = "db_enum_test_status"))]
+  pub struct PropertyStatusType;
+
+  diesel::table! {
+    use super::PropertyStatusType;
+    use diesel::sql_types::Integer;
+
+    db_enum_test_properties (id) {
+      id -> Integer,
+      status -> PropertyStatusType,
+    }
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+  #[diesel(sql_type = PropertyStatusType)]
+  enum PropertyStatus {
+    Draft,
+    Active,
+    Sold,
+  }
+
+  impl DbEnum for PropertyStatus {
+    const NAME: &'static str = "PropertyStatus";
+    const VARIANTS: &'static [(Self, &'static str)] = &[
+      (Self::Draft, "draft"),
+      (Self::Active, "active"),
+      (Self::Sold, "sold"),
+    ];
+  }
+
+  impl ToSql<PropertyStatusType, Pg> for PropertyStatus {
+    fn to_sql<'b>(
+      &'b self,
+      out: &mut Output<'b, '_, Pg>,
+    ) -> serialize::Result {
+      out.write_all(self.as_db_str().as_bytes())?;
+      Ok(IsNull::No)
+    }
+  }
+
+  impl FromSql<PropertyStatusType, Pg> for PropertyStatus {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+      Ok(Self::from_db_str(std::str::from_utf8(value.as_bytes())?)?)
+    }
+  }
+
+  /// `TEST_POSTGRES_URL=postgres://… cargo test -- --ignored native_enum`
+  #[tokio::test]
+  #[ignore = "needs TEST_POSTGRES_URL"]
+  async fn native_enum_round_trips() {
+    let url = std::env::var("TEST_POSTGRES_URL").expect("TEST_POSTGRES_URL");
+    let db = DBPostgres::new(&url).unwrap();
+    let loaded = db
+      .execute(|conn| {
+        conn.batch_execute(
+          "DROP TABLE IF EXISTS db_enum_test_properties;
+           DROP TYPE IF EXISTS db_enum_test_status;
+           CREATE TYPE db_enum_test_status AS ENUM ('draft', 'active', 'sold');
+           CREATE TABLE db_enum_test_properties (id INTEGER PRIMARY KEY, status db_enum_test_status NOT NULL);",
+        )?;
+        for (id, (status, _)) in (1..).zip(PropertyStatus::VARIANTS) {
+          diesel::insert_into(db_enum_test_properties::table)
+            .values((
+              db_enum_test_properties::id.eq(id),
+              db_enum_test_properties::status.eq(status),
+            ))
+            .execute(conn)?;
+        }
+        let loaded: Vec<PropertyStatus> = db_enum_test_properties::table
+          .select(db_enum_test_properties::status)
+          .order(db_enum_test_properties::id)
+          .load(conn)?;
+        conn.batch_execute(
+          "DROP TABLE db_enum_test_properties; DROP TYPE db_enum_test_status;",
+        )?;
+        Ok(loaded)
+      })
+      .await
+      .unwrap();
+    assert_eq!(
+      loaded,
+      [
+        PropertyStatus::Draft,
+        PropertyStatus::Active,
+        PropertyStatus::Sold
+      ]
+    );
+  }
+}
@@ -0,0 +1,85 @@
+//! Per-task tally of database calls, for asserting query budgets in tests.
+//!
+//! A handler that loads a list and then one row per item (N+1) passes
+//! functional tests and only shows up under load. Run the request inside
+//! [`QueryCounter::scope`] and assert on the count:
+//!
+//! ```rust,ignore
+//! let counter = QueryCounter::default();
+//! let res = counter.scope(router.oneshot(request)).await.unwrap();
+//! assert!(counter.count() <= 2, "GET /properties issued {} queries", counter.count());
+//! ```
+//!
+//! Only calls through `DBSqlite::execute` / `transaction` (and
+//! `DBPostgres::execute` / `transaction`) are counted, one per call however
+//! many statements the closure runs; helpers built on them (retries, outbox,
+//! audit) count once per underlying call. Queries on a raw connection from
+//! `get_connection` are not seen. The counter is a task-local, so work the
+//! request hands to `tokio::spawn` is not counted either, and the request
+//! has to run on the test's task: through `oneshot` on the router, not over
+//! HTTP to a spawned server.
+
+use std::sync::{
+  Arc,
+  atomic::{AtomicUsize, Ordering},
+};
+
+tokio::task_local! {
+  static CURRENT_COUNTER: QueryCounter;
+}
+
+/// Number of database calls made inside [`scope`](Self::scope); clones share
+/// the count.
+#[derive(Clone, Debug, Default)]
+pub struct QueryCounter(Arc<AtomicUsize>);
+
+impl QueryCounter {
+  /// Calls counted so far.
+  pub fn count(&self) -> usize {
+    self.0.load(Ordering::Relaxed)
+  }
+
+  /// Runs `fut` with this counter current on the task. A nested scope
+  /// shadows the outer one until it ends.
+  pub async fn scope<F: Future>(
+    &self,
+    fut: F,
+  ) -> F::Output {
+    CURRENT_COUNTER.scope(self.clone(), fut).await
+  }
+}
+
+/// Counts one call against the current task's counter, if any.
+pub(crate) fn record() {
+  let _ = CURRENT_COUNTER.try_with(|counter| counter.0.fetch_add(1, Ordering::Relaxed));
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::DBSqlite;
+  use tempfile::NamedTempFile;
+
+  #[tokio::test]
+  async fn counts_calls_on_the_scoped_task_only() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let counter = QueryCounter::default();
+
+    counter
+      .scope(async {
+        db.execute(|_| Ok(())).await.unwrap();
+        db.transaction(|_| Ok(())).await.unwrap();
+        let spawned = db.clone();
+        tokio::spawn(async move { spawned.execute(|_| Ok(())).await })
+          .await
+          .unwrap()
+          .unwrap();
+      })
+      .await;
+    db.execute(|_| Ok(())).await.unwrap();
+
+    assert_eq!(counter.count(), 2);
+  }
+}
@@ -0,0 +1,123 @@
+//! Rows produced on the blocking pool and consumed as an async [`Stream`].
+//!
+//! Diesel is synchronous, so a query that should not be materialised in full
+//! (exports, large reports) runs on a `spawn_blocking` thread that pushes
+//! rows into a bounded channel. The channel holds at most `capacity` rows:
+//! when the consumer falls behind, the producer blocks instead of buffering,
+//! and when the consumer drops the stream the next send fails and the
+//! producer stops. `DBPostgres::stream` drives this with a server-side cursor.
+
+use anyhow::Result;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+/// Async stream of rows from a blocking producer; see the module docs.
+#[derive(Debug)]
+pub struct RowStream<T> {
+  rows: mpsc::Receiver<Result<T>>,
+}
+
+/// Producer side handed to the blocking closure of [`RowStream::spawn_blocking`].
+#[derive(Debug)]
+pub struct RowSender<T> {
+  rows: mpsc::Sender<Result<T>>,
+}
+
+impl<T> RowSender<T> {
+  /// Sends one row, blocking while the channel is full. Returns `false` once
+  /// the stream has been dropped; the producer should stop then.
+  pub fn send(
+    &self,
+    row: T,
+  ) -> bool {
+    self.rows.blocking_send(Ok(row)).is_ok()
+  }
+}
+
+impl<T: Send + 'static> RowStream<T> {
+  /// Runs `producer` on the blocking pool with room for `capacity` buffered
+  /// rows. An error returned by `producer` becomes the stream's last item.
+  pub fn spawn_blocking<F>(
+    capacity: usize,
+    producer: F,
+  ) -> Self
+  where
+    F: FnOnce(&RowSender<T>) -> Result<()> + Send + 'static,
+  {
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    tokio::task::spawn_blocking(move || {
+      let sender = RowSender { rows: tx };
+      if let Err(e) = producer(&sender) {
+        let _ = sender.rows.blocking_send(Err(e));
+      }
+    });
+    Self { rows: rx }
+  }
+
+  /// Next row, or `None` once the producer has finished.
+  pub async fn next(&mut self) -> Option<Result<T>> {
+    self.rows.recv().await
+  }
+}
+
+impl<T> Stream for RowStream<T> {
+  type Item = Result<T>;
+
+  fn poll_next(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Self::Item>> {
+    self.rows.poll_recv(cx)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::Arc;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[tokio::test]
+  async fn yields_rows_then_the_producer_error() {
+    let mut rows = RowStream::spawn_blocking(2, |tx| {
+      for i in 0..5 {
+        tx.send(i);
+      }
+      anyhow::bail!("CURSOR_FAILED")
+    });
+
+    let mut seen = Vec::new();
+    while let Some(row) = rows.next().await {
+      match row {
+        Ok(i) => seen.push(i),
+        Err(e) => {
+          assert_eq!(e.to_string(), "CURSOR_FAILED");
+          break;
+        }
+      }
+    }
+    assert_eq!(seen, vec![0, 1, 2, 3, 4]);
+  }
+
+  #[tokio::test]
+  async fn dropping_the_stream_stops_the_producer() {
+    let produced = Arc::new(AtomicUsize::new(0));
+    let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+    let counter = produced.clone();
+    let mut rows = RowStream::spawn_blocking(1, move |tx| {
+      while tx.send(()) {
+        counter.fetch_add(1, Ordering::SeqCst);
+      }
+      let _ = done_tx.send(());
+      Ok(())
+    });
+
+    rows.next().await.unwrap().unwrap();
+    drop(rows);
+    done_rx.await.unwrap();
+    // One row consumed, at most one buffered and one blocked in `send`.
+    assert!(produced.load(Ordering::SeqCst) <= 3);
+  }
+}
@@ -0,0 +1,220 @@
+//! Server-side sessions kept in a [`Cache`] of their own.
+//!
+//! The client only holds an opaque, random session ID in a cookie; the data
+//! stays on the server under `session:<id>` and expires after
+//! [`SessionConfig::idle_timeout_secs`] without a request. The `Session`
+//! extractor loads it and extends the timeout on every request.
+//!
+//! ```rust,ignore
+//! pub async fn login(State(state): State<Arc<AppState>>, ...) -> Result<impl IntoResponse, HttpError> {
+//!   let sessions = SessionStore::from_state(&state);
+//!   let id = sessions.create(&UserSession { user_id }).await?;
+//!   Ok(([(header::SET_COOKIE, sessions.cookie(&id))], HttpResponse::ok((), "OK")))
+//! }
+//! ```
+//!
+//! Sessions live in this process's memory, apart from `AppState::cache`: the
+//! memory monitor's `evict_to` never logs users out, and session data is
+//! never written to `CACHE_SNAPSHOT_PATH`. They are therefore lost on restart
+//! and are not shared between instances.
+
+use crate::models::{AppState, SessionConfig};
+use crate::services::Cache;
+use crate::utils::hmac::to_hex;
+use anyhow::Result;
+use axum::http::HeaderValue;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Name of the cookie carrying the session ID.
+pub const SESSION_COOKIE: &str = "session";
+
+/// Cache tag on every session entry.
+pub const SESSION_TAG: &str = "session";
+
+/// Random bytes in a session ID (hex-encoded, so 64 characters).
+const SESSION_ID_BYTES: usize = 32;
+
+/// Creates, loads and ends sessions. Cheap to clone: a [`Cache`] handle plus
+/// the settings, so clones share the sessions.
+#[derive(Clone, Debug)]
+pub struct SessionStore {
+  cache: Cache,
+  config: SessionConfig,
+}
+
+impl SessionStore {
+  pub fn new(
+    cache: Cache,
+    config: SessionConfig,
+  ) -> Self {
+    Self { cache, config }
+  }
+
+  /// Store on a new cache that shares nothing with `AppState::cache`; `main`
+  /// creates the app's one in `AppState::sessions`.
+  pub fn isolated(config: SessionConfig) -> Self {
+    Self::new(Cache::default(), config)
+  }
+
+  /// The app's store, `AppState::sessions`.
+  pub fn from_state(state: &AppState) -> Self {
+    state.sessions.clone()
+  }
+
+  fn idle_timeout(&self) -> Duration {
+    Duration::from_secs(self.config.idle_timeout_secs)
+  }
+
+  /// Stores `data` under a new session ID drawn from the OS CSPRNG and
+  /// returns the ID.
+  pub async fn create<T: Serialize>(
+    &self,
+    data: &T,
+  ) -> Result<String> {
+    let mut bytes = [0u8; SESSION_ID_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let id = to_hex(&bytes);
+    let value = serde_json::to_value(data)?;
+    self
+      .cache
+      .set_with_tags(key(&id), value, self.idle_timeout(), &[SESSION_TAG])
+      .await;
+    Ok(id)
+  }
+
+  /// Data of session `id`, or `None` when it does not exist or has expired.
+  /// Fails only when the stored data is not a `T`.
+  pub async fn get<T: DeserializeOwned>(
+    &self,
+    id: &str,
+  ) -> Result<Option<T>> {
+    match self.cache.get(&key(id)).await {
+      Some(value) => Ok(Some(serde_json::from_value(value)?)),
+      None => Ok(None),
+    }
+  }
+
+  /// Restarts the idle timeout of session `id`. Returns `false` when it does
+  /// not exist or has expired.
+  pub async fn touch(
+    &self,
+    id: &str,
+  ) -> bool {
+    self.cache.touch(&key(id), self.idle_timeout()).await
+  }
+
+  /// Ends session `id`; a missing one is not an error.
+  pub async fn destroy(
+    &self,
+    id: &str,
+  ) {
+    self.cache.delete(&key(id)).await;
+  }
+
+  /// Removes expired sessions, returning how many were removed.
+  pub async fn purge_expired(&self) -> usize {
+    self.cache.purge_expired().await
+  }
+
+  /// `Set-Cookie` value handing `id` to the client, with the configured
+  /// flags. It has no `Max-Age`: the browser keeps it until closed, and the
+  /// server enforces the idle timeout.
+  pub fn cookie(
+    &self,
+    id: &str,
+  ) -> HeaderValue {
+    self.cookie_header(&format!("{SESSION_COOKIE}={id}"))
+  }
+
+  /// `Set-Cookie` value that makes the client drop the session cookie, e.g.
+  /// on logout after [`destroy`](Self::destroy).
+  pub fn removal_cookie(&self) -> HeaderValue {
+    self.cookie_header(&format!("{SESSION_COOKIE}=; Max-Age=0"))
+  }
+
+  fn cookie_header(
+    &self,
+    pair: &str,
+  ) -> HeaderValue {
+    let mut cookie = format!("{pair}; Path=/");
+    if self.config.http_only {
+      cookie.push_str("; HttpOnly");
+    }
+    if self.config.secure {
+      cookie.push_str("; Secure");
+    }
+    cookie.push_str("; SameSite=");
+    cookie.push_str(self.config.same_site.as_str());
+    HeaderValue::from_str(&cookie).expect("SESSION_COOKIE_INVALID")
+  }
+}
+
+fn key(id: &str) -> String {
+  format!("session:{id}")
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::{AppEnv, SameSite};
+  use crate::utils::MockClock;
+  use serde_json::{Value, json};
+  use std::sync::Arc;
+
+  fn store(clock: &MockClock) -> SessionStore {
+    let cache = Cache::with_clock(Duration::from_secs(3600), Arc::new(clock.clone()));
+    SessionStore::new(cache, SessionConfig::for_env(&AppEnv::Production))
+  }
+
+  #[tokio::test]
+  async fn sessions_expire_when_idle_and_touch_extends_them() {
+    let clock = MockClock::new();
+    let sessions = store(&clock);
+    let id = sessions.create(&json!({ "user_id": 7 })).await.unwrap();
+    assert_eq!(id.len(), 64);
+    assert_ne!(id, sessions.create(&json!({})).await.unwrap());
+
+    clock.advance(Duration::from_secs(29 * 60));
+    assert!(sessions.touch(&id).await);
+    clock.advance(Duration::from_secs(29 * 60));
+    let data: Value = sessions.get(&id).await.unwrap().unwrap();
+    assert_eq!(data["user_id"], 7);
+
+    clock.advance(Duration::from_secs(60));
+    assert!(sessions.get::<Value>(&id).await.unwrap().is_none());
+    assert!(!sessions.touch(&id).await);
+  }
+
+  #[tokio::test]
+  async fn destroy_ends_the_session() {
+    let sessions = store(&MockClock::new());
+    let id = sessions.create(&json!({})).await.unwrap();
+    sessions.destroy(&id).await;
+    assert!(sessions.get::<Value>(&id).await.unwrap().is_none());
+  }
+
+  #[test]
+  fn cookie_carries_the_configured_flags() {
+    let mut sessions = store(&MockClock::new());
+    assert_eq!(
+      sessions.cookie("abc"),
+      "session=abc; Path=/; HttpOnly; Secure; SameSite=Lax"
+    );
+
+    sessions.config = SessionConfig {
+      http_only: false,
+      secure: false,
+      same_site: SameSite::Strict,
+      ..SessionConfig::for_env(&AppEnv::Local)
+    };
+    assert_eq!(
+      sessions.removal_cookie(),
+      "session=; Max-Age=0; Path=/; SameSite=Strict"
+    );
+  }
+}
@@ -0,0 +1,100 @@
+//! Opt-in logging of every SQL statement Diesel executes (`LOG_SQL=true`).
+//!
+//! [`SqlLogging`] is an r2d2 connection customizer that installs a
+//! [`SqlLogger`] on each pooled connection as it is acquired. Statements are
+//! logged at `DEBUG` with their bound parameters and execution time; binds of
+//! statements touching a column in [`SQL_LOG_REDACTED_COLUMNS`] are replaced
+//! with `[REDACTED]`.
+//!
+//! Only honored in local mode (see `config::load_environment`): bound values
+//! include user data, and formatting every statement is not free.
+
+use crate::constants::SQL_LOG_REDACTED_COLUMNS;
+use diesel::connection::{Connection, Instrumentation, InstrumentationEvent};
+use diesel::r2d2::{CustomizeConnection, Error as R2D2Error};
+use std::time::Instant;
+
+/// Separator Diesel puts between the SQL text and its binds when displaying a query.
+const BINDS_SEPARATOR: &str = " -- binds: ";
+
+/// Per-connection [`Instrumentation`] that logs each finished query.
+#[derive(Debug, Default)]
+pub struct SqlLogger {
+  started: Option<Instant>,
+}
+
+impl Instrumentation for SqlLogger {
+  fn on_connection_event(
+    &mut self,
+    event: InstrumentationEvent<'_>,
+  ) {
+    match event {
+      InstrumentationEvent::StartQuery { .. } => self.started = Some(Instant::now()),
+      InstrumentationEvent::FinishQuery { query, error, .. } => {
+        let elapsed_ms = self
+          .started
+          .take()
+          .map(|started| started.elapsed().as_secs_f64() * 1000.0);
+        let statement = redact_binds(&query.to_string());
+        match error {
+          Some(e) => tracing::debug!(sql = %statement, elapsed_ms, error = %e, "SQL_QUERY_FAILED"),
+          None => tracing::debug!(sql = %statement, elapsed_ms, "SQL_QUERY"),
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+/// Connection customizer installing [`SqlLogger`] on every pooled connection.
+#[derive(Debug, Clone, Copy)]
+pub struct SqlLogging;
+
+impl<C: Connection + 'static> CustomizeConnection<C, R2D2Error> for SqlLogging {
+  fn on_acquire(
+    &self,
+    conn: &mut C,
+  ) -> Result<(), R2D2Error> {
+    conn.set_instrumentation(SqlLogger::default());
+    Ok(())
+  }
+}
+
+/// Replaces the binds of `statement` with `[REDACTED]` when its SQL mentions a
+/// sensitive column.
+fn redact_binds(statement: &str) -> String {
+  let Some((sql, _)) = statement.split_once(BINDS_SEPARATOR) else {
+    return statement.to_string();
+  };
+  let lowered = sql.to_lowercase();
+  if SQL_LOG_REDACTED_COLUMNS
+    .iter()
+    .any(|column| lowered.contains(column))
+  {
+    format!("{sql}{BINDS_SEPARATOR}[REDACTED]")
+  } else {
+    statement.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn redacts_binds_for_sensitive_columns() {
+    let statement =
+      r#"INSERT INTO `users` (`email`, `password`) VALUES (?, ?) -- binds: ["a@b.c", "hash"]"#;
+    assert_eq!(
+      redact_binds(statement),
+      "INSERT INTO `users` (`email`, `password`) VALUES (?, ?) -- binds: [REDACTED]"
+    );
+  }
+
+  #[test]
+  fn keeps_binds_for_other_statements() {
+    let statement =
+      r#"SELECT `users`.`id` FROM `users` WHERE `users`.`email` = ? -- binds: ["a@b.c"]"#;
+    assert_eq!(redact_binds(statement), statement);
+  }
+}
@@ -11,11 +11,20 @@
 //! - Automatic connection health checks
 //! - Transaction and execute helpers
 //!
+//! # Time zones
+//!
+//! SQLite has no timestamp type and no session time zone: timestamps are
+//! stored as whatever text was written (`Text` columns here). Nothing
+//! converts them on the way in or out, so by convention app code writes and
+//! reads them as UTC (`Utc::now().to_rfc3339()`); see
+//! [`crate::models::serde_helpers`]. Postgres pins its session time zone
+//! instead (`DBPostgresConfig::time_zone`).
+//!
 //! # Example
 //!
 //! ## Basic Usage
 //!
-//! ```rust
+//! ```rust,no_run
 //! use axum_starter::services::DBSqlite;
 //! use anyhow::Result;
 //!
@@ -63,7 +72,7 @@
 //!
 //! ## Health Check
 //!
-//! ```rust
+//! ```rust,no_run
 //! use axum_starter::services::DBSqlite;
 //!
 //! async fn health_check_example() {
@@ -76,20 +85,206 @@
 //! }
 //! ```
 
+use crate::models::AppEnv;
+use crate::services::analytics::{BucketRow, TimeRange, TimeWindow, check_identifier};
+use crate::services::app_error::{StaleVersion, TooManyRows};
+use crate::services::cancel::{CancelOnDrop, join_error};
+use crate::services::query_counter;
+use crate::services::sql_log::SqlLogging;
+use crate::utils::request_id::RequestId;
+use crate::utils::retry::{self, RetryBudget};
+use crate::utils::tasks::BackgroundTasks;
 use anyhow::Result;
-use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
-use diesel::sqlite::SqliteConnection;
+use chrono::{DateTime, Utc};
+use diesel::associations::HasTable;
+use diesel::connection::{AnsiTransactionManager, SimpleConnection, TransactionManager};
+use diesel::dsl::{self, Limit};
+use diesel::expression::AsExpression;
+use diesel::query_builder::{
+  AsChangeset, AsQuery, InsertStatement, IntoUpdateTarget, QueryBuilder, QueryFragment,
+};
+use diesel::query_dsl::LoadQuery;
+use diesel::query_dsl::methods::{ExecuteDsl, FilterDsl, LimitDsl};
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection, R2D2Connection};
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
+use diesel::sql_types::{Bool, Integer, SqlType, Text};
+use diesel::sqlite::{Sqlite, SqliteConnection, SqliteQueryBuilder};
+use diesel::{Connection, ExpressionMethods, QueryResult, QueryableByName, RunQueryDsl, Table};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
+use tokio::time::MissedTickBehavior;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
+/// How long [`DBSqlite::run_migrations`] waits for another instance's migration run.
+pub const MIGRATION_LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Acquire-timeout breakpoints used by [`DBSqlite::adaptive_acquire`], as
+/// `(pool utilization %, share of the connection timeout %)`.
+///
+/// The first row whose utilization the pool has reached applies; below 50%
+/// the full connection timeout (60s) is used. With the default pool:
+///
+/// | checked out  | utilization | acquire timeout |
+/// | ------------ | ----------- | --------------- |
+/// | < 16 of 32   | < 50%       | 60s             |
+/// | 16 – 23      | ≥ 50%       | 30s             |
+/// | 24 – 28      | ≥ 75%       | 15s             |
+/// | 29 – 32      | ≥ 90%       | 6s              |
+pub const ADAPTIVE_ACQUIRE_BREAKPOINTS: [(u32, u32); 3] = [(90, 10), (75, 25), (50, 50)];
+
+/// Retries made by [`DBSqlite::execute_with_retry`] after the first attempt.
+pub const EXECUTE_RETRIES: u32 = 3;
+
+/// First backoff of [`DBSqlite::execute_with_retry`]; doubles per retry.
+pub const EXECUTE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// How long [`DBSqlite::assert_no_leaked_connections`] waits for connections to return.
+const LEAK_CHECK_WINDOW: Duration = Duration::from_millis(500);
+
+/// Result of [`DBSqlite::upsert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+  /// No conflicting row existed; a new row was inserted.
+  Inserted,
+  /// A conflicting row existed and was updated in place.
+  Updated,
+}
+
+/// Result of [`DBSqlite::run_migrations_until`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MigrationRun {
+  /// Versions applied by this run, in order.
+  pub applied: Vec<String>,
+  /// Pending migrations left for the next run because shutdown was requested.
+  pub skipped: Vec<String>,
+}
+
+/// Result of [`DBSqlite::insert_batch_lenient`].
+#[derive(Debug, Default)]
+pub struct BatchResult {
+  /// Rows inserted by the chunks that committed.
+  pub inserted: usize,
+  /// Chunks that were rolled back, as indices into the input rows (so
+  /// `rows[range]` are the rows that were not written) and the error that
+  /// rolled them back.
+  pub failed_chunks: Vec<(std::ops::Range<usize>, diesel::result::Error)>,
+}
+
+impl BatchResult {
+  /// Whether every chunk committed.
+  pub fn is_complete(&self) -> bool {
+    self.failed_chunks.is_empty()
+  }
+}
+
+/// Why [`DBSqlite::with_config`] could not open the database.
+#[derive(Debug, thiserror::Error)]
+pub enum DBSqliteError {
+  /// The URL does not name a database file.
+  #[error("DB_INVALID_DATABASE_URL: {0:?}")]
+  InvalidDatabaseUrl(String),
+  /// The database file or its directory is missing, or cannot be created or
+  /// written.
+  #[error("DB_PATH_IO_ERROR: {}: {source}", path.display())]
+  IoError {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  /// The pool could not open its connections.
+  #[error(transparent)]
+  Pool(#[from] diesel::r2d2::PoolError),
+}
+
+/// How the pool makes sure a connection still works before it is used.
+///
+/// Checking on checkout costs a `SELECT 1` round-trip on every
+/// [`DBSqlite::execute`] / [`DBSqlite::transaction`], but a dead connection
+/// is never handed out. Pre-pinging moves that check off the request path:
+/// a background sweep ([`spawn_pre_ping`]) pings idle connections every
+/// interval and evicts the ones that fail. Checkouts are then one round-trip
+/// cheaper, at the price that a connection dying between sweeps is handed
+/// out once and the query on it fails. Prefer `PrePing` for high-QPS,
+/// latency-sensitive paths that can retry; keep `OnCheckout` otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionCheck {
+  /// r2d2's `test_on_check_out`.
+  #[default]
+  OnCheckout,
+  /// No checkout test; idle connections are pinged every interval instead.
+  PrePing(Duration),
+}
+
+/// Options for [`DBSqlite::with_config`].
+#[derive(Clone, Debug)]
+pub struct DBSqliteConfig {
+  /// Log every statement via [`SqlLogging`] (see `LOG_SQL`).
+  pub log_sql: bool,
+  /// Age after which a connection must no longer be in use.
+  pub max_lifetime: Duration,
+  /// How long before `max_lifetime` a connection stops being handed out.
+  ///
+  /// r2d2 only checks ages when its reaper runs (every 30s) and never on
+  /// checkout, so an idle connection can be handed out up to 30s past the
+  /// pool's `max_lifetime` and then expire mid-request. The pool is
+  /// therefore given `max_lifetime - lifetime_margin`: once older than that,
+  /// a connection is reaped or dropped on check-in instead of reused. With a
+  /// margin of at least the reaper interval plus the longest expected
+  /// checkout, no connection reaches `max_lifetime` while in use.
+  pub lifetime_margin: Duration,
+  /// Create the database file's parent directory when it is missing, instead
+  /// of failing with [`DBSqliteError::IoError`].
+  pub create_parent_dir: bool,
+  /// Create the database file when it is missing, instead of failing with
+  /// [`DBSqliteError::IoError`]. Off for checks that must not leave an empty
+  /// database behind, such as `probe`.
+  pub create_file: bool,
+  /// Checkout test or background pre-ping (`DB_PRE_PING_SECS`).
+  pub connection_check: ConnectionCheck,
+}
+
+impl Default for DBSqliteConfig {
+  fn default() -> Self {
+    Self {
+      log_sql: false,
+      max_lifetime: Duration::from_secs(3600),
+      lifetime_margin: Duration::from_secs(60),
+      create_parent_dir: true,
+      create_file: true,
+      connection_check: ConnectionCheck::OnCheckout,
+    }
+  }
+}
+
+impl DBSqliteConfig {
+  /// `max_lifetime` the r2d2 pool is built with: `max_lifetime -
+  /// lifetime_margin`, at least one second.
+  pub fn retire_after(&self) -> Duration {
+    self
+      .max_lifetime
+      .saturating_sub(self.lifetime_margin)
+      .max(Duration::from_secs(1))
+  }
+}
+
 /// A wrapper around a SQLite connection pool using Diesel and r2d2.
 ///
 /// This struct provides a thread-safe, cloneable handle to a connection pool.
 /// It is designed to be used in async applications and can be safely shared
 /// across multiple tasks.
 ///
+/// # Cloning
+///
+/// r2d2's `Pool` is reference counted, so clones share one pool: its
+/// connections, limits and statistics. Cloning `AppState` per request never
+/// opens new connections. The [`adaptive_acquire`](Self::adaptive_acquire)
+/// flag is the only per-handle setting.
+///
 /// # Connection Pool Configuration
 ///
 /// The pool is configured with the following defaults:
@@ -97,12 +292,13 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 /// - Max pool size: 32 connections
 /// - Min idle connections: 8
 /// - Idle timeout: 600 seconds (10 minutes)
-/// - Max lifetime: 3600 seconds (1 hour)
-/// - Test on check-out: enabled
+/// - Max lifetime: 3600 seconds (1 hour), retired 60 seconds early (see
+///   [`DBSqliteConfig::lifetime_margin`])
+/// - Test on check-out: enabled, unless [`ConnectionCheck::PrePing`]
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,no_run
 /// use axum_starter::services::DBSqlite;
 ///
 /// let db = DBSqlite::new("sqlite://database.db").unwrap();
@@ -111,6 +307,8 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 #[derive(Clone, Debug)]
 pub struct DBSqlite {
   pool: Pool<ConnectionManager<SqliteConnection>>,
+  adaptive_acquire: bool,
+  connection_check: ConnectionCheck,
 }
 
 impl DBSqlite {
@@ -127,12 +325,13 @@ impl DBSqlite {
   ///
   /// # Returns
   ///
-  /// Returns `Ok(Self)` with the configured pool, or a `PoolError` if the pool
-  /// could not be created.
+  /// Returns `Ok(Self)` with the configured pool, or a [`DBSqliteError`]
+  /// naming the URL or path if the database cannot be opened. File databases
+  /// are checked before the pool is built; see [`DBSqlite::with_config`].
   ///
   /// # Example
   ///
-  /// ```rust
+  /// ```rust,no_run
   /// use axum_starter::services::DBSqlite;
   ///
   /// // Create pool with file database
@@ -140,19 +339,92 @@ impl DBSqlite {
   ///
   /// // Create pool with in-memory database
   /// let db = DBSqlite::new(":memory:")?;
-  /// # Ok::<_, diesel::r2d2::PoolError>(())
+  /// # Ok::<_, axum_starter::services::DBSqliteError>(())
+  /// ```
+  pub fn new(database_url: &str) -> Result<Self, DBSqliteError> {
+    Self::with_config(database_url, &DBSqliteConfig::default())
+  }
+
+  /// Same as [`DBSqlite::new`], but every pooled connection logs the SQL it
+  /// executes (with bound parameters and timing) at `DEBUG` via [`SqlLogging`].
+  ///
+  /// Intended for local debugging only; `main` uses it when `LOG_SQL` is set.
+  pub fn with_sql_logging(database_url: &str) -> Result<Self, DBSqliteError> {
+    let config = DBSqliteConfig {
+      log_sql: true,
+      ..Default::default()
+    };
+    Self::with_config(database_url, &config)
+  }
+
+  /// Creates a pool using [`DBSqliteConfig`].
+  ///
+  /// For a file database the file is prepared up front, so a bad path fails
+  /// here with [`DBSqliteError::IoError`] naming it rather than as a pool
+  /// timeout on first use: the parent directory is created if missing (unless
+  /// `create_parent_dir` is off), and the file is created (unless
+  /// `create_file` is off), or opened for writing if it exists. A newly created file gets mode `0600` on Unix.
+  /// In-memory databases skip these checks.
+  ///
+  /// ```rust,ignore
+  /// let config = DBSqliteConfig {
+  ///   max_lifetime: Duration::from_secs(1800),
+  ///   lifetime_margin: Duration::from_secs(120),
+  ///   ..Default::default()
+  /// };
+  /// let db = DBSqlite::with_config(env.database_url.expose_secret(), &config)?;
   /// ```
-  pub fn new(database_url: &str) -> Result<Self, diesel::r2d2::PoolError> {
+  pub fn with_config(
+    database_url: &str,
+    config: &DBSqliteConfig,
+  ) -> Result<Self, DBSqliteError> {
+    if let Some(path) = database_file(database_url)? {
+      prepare_database_file(&path, config)?;
+    }
     let manager = ConnectionManager::<SqliteConnection>::new(database_url);
-    let pool = Pool::builder()
+    let mut builder = Pool::builder()
       .connection_timeout(Duration::from_secs(60))
       .max_size(32)
       .min_idle(Some(8))
       .idle_timeout(Some(Duration::from_secs(600)))
-      .max_lifetime(Some(Duration::from_secs(3600)))
-      .test_on_check_out(true)
-      .build(manager)?;
-    Ok(Self { pool })
+      .max_lifetime(Some(config.retire_after()))
+      .test_on_check_out(config.connection_check == ConnectionCheck::OnCheckout);
+    if config.log_sql {
+      builder = builder.connection_customizer(Box::new(SqlLogging));
+    }
+    let pool = builder.build(manager)?;
+    Ok(Self {
+      pool,
+      adaptive_acquire: false,
+      connection_check: config.connection_check,
+    })
+  }
+
+  /// Shrinks the connection acquire timeout of [`DBSqlite::execute`] and
+  /// [`DBSqlite::transaction`] as the pool fills up (`DB_ADAPTIVE_ACQUIRE`).
+  ///
+  /// Under sustained load every request would otherwise wait the full
+  /// connection timeout for a connection before failing, piling up behind the
+  /// pool. With this enabled the wait is cut according to
+  /// [`ADAPTIVE_ACQUIRE_BREAKPOINTS`], measured from [`DBSqlite::pool_stats`]
+  /// when the call is made, so requests fail fast and shed load instead.
+  /// Off by default; [`DBSqlite::get_connection`] is never affected.
+  pub fn adaptive_acquire(
+    mut self,
+    enabled: bool,
+  ) -> Self {
+    self.adaptive_acquire = enabled;
+    self
+  }
+
+  /// Timeout for the next connection checkout by `execute`/`transaction`.
+  fn acquire_timeout(&self) -> Duration {
+    let base = self.pool.connection_timeout();
+    if !self.adaptive_acquire {
+      return base;
+    }
+    let (total, idle) = self.pool_stats();
+    adaptive_timeout(base, total.saturating_sub(idle), self.pool_max_size())
   }
 
   /// Runs all pending database migrations.
@@ -165,21 +437,176 @@ impl DBSqlite {
   ///
   /// Returns `Ok(())` if all pending migrations ran successfully,
   /// or an error if any migration failed.
+  ///
+  /// # Concurrency
+  ///
+  /// Run migrations from a single process where possible: a deploy step
+  /// (`cargo run -- migrate run`) or one designated instance. As a safety net
+  /// for several processes sharing the same database file, the whole run
+  /// happens inside one `BEGIN EXCLUSIVE` transaction, so the instances
+  /// serialise: the first applies the migrations, the others wait (up to
+  /// [`MIGRATION_LOCK_TIMEOUT`] via `busy_timeout`) and then find nothing
+  /// pending. If the lock is still held after that, the call fails with
+  /// `MIGRATION_LOCK_TIMEOUT` without touching the schema. Migrations
+  /// therefore must not contain statements that are illegal inside a
+  /// transaction (e.g. `VACUUM`).
   pub fn run_migrations(&self) -> Result<()> {
-    let mut conn = self.pool.get()?;
-    match conn.run_pending_migrations(MIGRATIONS) {
-      Ok(applied) => {
+    self.run_migrations_with_timeout(MIGRATION_LOCK_TIMEOUT)
+  }
+
+  /// [`run_migrations`](Self::run_migrations) waiting at most `lock_timeout`
+  /// for another process's lock.
+  pub fn run_migrations_with_timeout(
+    &self,
+    lock_timeout: Duration,
+  ) -> Result<()> {
+    self
+      .migrate(lock_timeout, &AtomicBool::new(false))
+      .map(|_| ())
+  }
+
+  /// [`run_migrations`](Self::run_migrations) for startup, stopping early
+  /// once `stop` is set (by a shutdown signal).
+  ///
+  /// `stop` is only checked between migrations: the one running when it is
+  /// set finishes, the rest are left pending and reported in
+  /// [`MigrationRun::skipped`]. The applied ones are committed and the lock
+  /// is released before this returns, so the schema is never half-applied
+  /// and the next start (or `migrate run`) picks up where this one stopped.
+  pub fn run_migrations_until(
+    &self,
+    stop: &AtomicBool,
+  ) -> Result<MigrationRun> {
+    self.migrate(MIGRATION_LOCK_TIMEOUT, stop)
+  }
+
+  fn migrate(
+    &self,
+    lock_timeout: Duration,
+    stop: &AtomicBool,
+  ) -> Result<MigrationRun> {
+    let result = self.with_migration_lock(lock_timeout, |conn| {
+      let mut run = MigrationRun::default();
+      for migration in conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow::anyhow!(e))?
+      {
+        if stop.load(Ordering::SeqCst) {
+          run.skipped.push(migration.name().to_string());
+          continue;
+        }
+        let version = conn
+          .run_migration(&*migration)
+          .map_err(|e| anyhow::anyhow!(e))?;
+        run.applied.push(version.to_string());
+      }
+      Ok(run)
+    });
+    match result {
+      Ok(run) if run.skipped.is_empty() => {
         tracing::info!(
-          migrations_applied = applied.len(),
-          ?applied,
+          migrations_applied = run.applied.len(),
+          applied = ?run.applied,
           "MIGRATION_EXECUTE_SUCCESS"
         );
-        Ok(())
+        Ok(run)
+      }
+      Ok(run) => {
+        tracing::warn!(
+          applied = ?run.applied,
+          skipped = ?run.skipped,
+          "MIGRATION_INTERRUPTED"
+        );
+        Ok(run)
       }
+      Err(e) if is_busy(&e) => Err(lock_timeout_error(lock_timeout)),
       Err(e) => Err(anyhow::anyhow!("MIGRATION_EXECUTE_FAILURE: {}", e)),
     }
   }
 
+  /// Runs `operation` inside `BEGIN EXCLUSIVE`, waiting up to `lock_timeout`
+  /// for the lock.
+  ///
+  /// The connection goes back to the pool afterwards, so its previous
+  /// `busy_timeout` is restored whether or not `operation` succeeded;
+  /// [`execute_with_retry`](Self::execute_with_retry) relies on pooled
+  /// connections failing with `SQLITE_BUSY` instead of blocking.
+  fn with_migration_lock<T>(
+    &self,
+    lock_timeout: Duration,
+    operation: impl FnOnce(&mut SqliteConnection) -> Result<T>,
+  ) -> Result<T> {
+    let mut conn = self.pool.get()?;
+    let previous = busy_timeout(&mut conn)?;
+    conn.batch_execute(&format!(
+      "PRAGMA busy_timeout = {}",
+      lock_timeout.as_millis()
+    ))?;
+    let result = conn.exclusive_transaction(operation);
+    conn.batch_execute(&format!("PRAGMA busy_timeout = {previous}"))?;
+    result
+  }
+
+  /// Reverts the most recently applied migration by running its `down.sql`,
+  /// returning its version.
+  ///
+  /// Development only: fails with `MIGRATION_REVERT_FORBIDDEN` unless `mode`
+  /// is [`AppEnv::Local`]. Runs inside `BEGIN EXCLUSIVE` like
+  /// [`run_migrations`](Self::run_migrations).
+  pub fn revert_last_migration(
+    &self,
+    mode: &AppEnv,
+  ) -> Result<String> {
+    let reverted = self.revert(mode, |conn| {
+      conn
+        .revert_last_migration(MIGRATIONS)
+        .map(|version| vec![version.to_string()])
+    })?;
+    Ok(reverted.into_iter().next().unwrap_or_default())
+  }
+
+  /// Reverts every applied migration, newest first, returning their versions
+  /// in the order they were reverted. Same guard as
+  /// [`revert_last_migration`](Self::revert_last_migration).
+  pub fn revert_all(
+    &self,
+    mode: &AppEnv,
+  ) -> Result<Vec<String>> {
+    self.revert(mode, |conn| {
+      conn
+        .revert_all_migrations(MIGRATIONS)
+        .map(|versions| versions.iter().map(|v| v.to_string()).collect())
+    })
+  }
+
+  fn revert<F>(
+    &self,
+    mode: &AppEnv,
+    operation: F,
+  ) -> Result<Vec<String>>
+  where
+    F: FnOnce(&mut SqliteConnection) -> diesel::migration::Result<Vec<String>>,
+  {
+    if !matches!(mode, AppEnv::Local) {
+      anyhow::bail!("MIGRATION_REVERT_FORBIDDEN: {}", mode);
+    }
+    let result = self.with_migration_lock(MIGRATION_LOCK_TIMEOUT, |conn| {
+      operation(conn).map_err(|e| anyhow::anyhow!(e))
+    });
+    match result {
+      Ok(reverted) => {
+        tracing::warn!(
+          migrations_reverted = reverted.len(),
+          ?reverted,
+          "MIGRATION_REVERT_SUCCESS"
+        );
+        Ok(reverted)
+      }
+      Err(e) if is_busy(&e) => Err(lock_timeout_error(MIGRATION_LOCK_TIMEOUT)),
+      Err(e) => Err(anyhow::anyhow!("MIGRATION_REVERT_FAILURE: {}", e)),
+    }
+  }
+
   /// Retrieves a pooled database connection.
   ///
   /// This method blocks until a connection is available or the connection
@@ -227,6 +654,23 @@ impl DBSqlite {
   /// - The connection could not be acquired
   /// - The operation failed
   ///
+  /// # Cancellation
+  ///
+  /// If the returned future is dropped (client disconnect, request timeout)
+  /// before the blocking task has checked out a connection, the operation is
+  /// skipped and the connection goes straight back to the pool. An operation
+  /// that has already started runs to completion: SQLite cannot cancel a
+  /// statement mid-query. A task the runtime drops unstarted during shutdown
+  /// also ends with [`Cancelled`](crate::services::Cancelled), and a panic in
+  /// `operation` with `DB_TASK_PANICKED`; see [`crate::services::cancel`].
+  ///
+  /// # Request correlation
+  ///
+  /// The operation runs inside a `DB` span carrying the current
+  /// [`RequestId`], so `LOG_SQL` output can be matched to the request. Each
+  /// call also counts once against a scoped
+  /// [`QueryCounter`](crate::services::QueryCounter).
+  ///
   /// # Example
   ///
   /// ```rust,no_run
@@ -252,12 +696,22 @@ impl DBSqlite {
     F: FnOnce(&mut SqliteConnection) -> Result<T> + Send + 'static,
     T: Send + 'static,
   {
+    query_counter::record();
     let pool = self.pool.clone();
-    tokio::task::spawn_blocking(move || {
-      let mut conn = pool.get()?;
-      operation(&mut conn)
+    let acquire_timeout = self.acquire_timeout();
+    let guard = CancelOnDrop::default();
+    let cancel = guard.flag();
+    let span = db_span();
+    let result = tokio::task::spawn_blocking(move || {
+      let _entered = span.enter();
+      let mut conn = pool.get_timeout(acquire_timeout)?;
+      cancel.check()?;
+      conn.transaction(|conn| operation(conn))
     })
-    .await?
+    .await
+    .map_err(join_error)?;
+    drop(guard);
+    result
   }
 
   /// Executes a read-only operation using a pooled connection.
@@ -277,6 +731,23 @@ impl DBSqlite {
   /// - The connection could not be acquired
   /// - The operation failed
   ///
+  /// # Cancellation
+  ///
+  /// If the returned future is dropped (client disconnect, request timeout)
+  /// before the blocking task has checked out a connection, the operation is
+  /// skipped and the connection goes straight back to the pool. An operation
+  /// that has already started runs to completion: SQLite cannot cancel a
+  /// statement mid-query. A task the runtime drops unstarted during shutdown
+  /// also ends with [`Cancelled`](crate::services::Cancelled), and a panic in
+  /// `operation` with `DB_TASK_PANICKED`; see [`crate::services::cancel`].
+  ///
+  /// # Request correlation
+  ///
+  /// The operation runs inside a `DB` span carrying the current
+  /// [`RequestId`], so `LOG_SQL` output can be matched to the request. Each
+  /// call also counts once against a scoped
+  /// [`QueryCounter`](crate::services::QueryCounter).
+  ///
   /// # Example
   ///
   /// ```rust
@@ -309,12 +780,503 @@ impl DBSqlite {
     F: FnOnce(&mut SqliteConnection) -> Result<T> + Send + 'static,
     T: Send + 'static,
   {
+    query_counter::record();
     let pool = self.pool.clone();
-    tokio::task::spawn_blocking(move || {
-      let mut conn = pool.get()?;
+    let acquire_timeout = self.acquire_timeout();
+    let guard = CancelOnDrop::default();
+    let cancel = guard.flag();
+    let span = db_span();
+    let result = tokio::task::spawn_blocking(move || {
+      let _entered = span.enter();
+      let mut conn = pool.get_timeout(acquire_timeout)?;
+      cancel.check()?;
       operation(&mut conn)
     })
-    .await?
+    .await
+    .map_err(join_error)?;
+    drop(guard);
+    result
+  }
+
+  /// [`execute`](Self::execute), retried when SQLite reports the database
+  /// busy or locked (another writer held the lock past `busy_timeout`).
+  ///
+  /// Retries up to [`EXECUTE_RETRIES`] times with exponential backoff from
+  /// [`EXECUTE_RETRY_BACKOFF`], each one drawing on the process-wide
+  /// [`RetryBudget`]; when the budget is exhausted the last error is returned
+  /// instead of retrying. Other errors are returned immediately. `operation`
+  /// may run more than once, so it must be safe to repeat (a failed attempt's
+  /// writes are rolled back only if it ran inside its own transaction).
+  pub async fn execute_with_retry<F, T>(
+    &self,
+    operation: F,
+  ) -> Result<T>
+  where
+    F: Fn(&mut SqliteConnection) -> Result<T> + Send + Sync + 'static,
+    T: Send + 'static,
+  {
+    let budget = RetryBudget::shared();
+    let operation = Arc::new(operation);
+    budget.deposit();
+    let mut attempt = 0;
+    loop {
+      let op = operation.clone();
+      match self.execute(move |conn| op(conn)).await {
+        Err(e) if is_busy(&e) && attempt < EXECUTE_RETRIES && budget.try_withdraw() => {
+          tracing::warn!(error = %e, attempt, "DB_EXECUTE_RETRY");
+          tokio::time::sleep(retry::backoff(EXECUTE_RETRY_BACKOFF, attempt)).await;
+          attempt += 1;
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Loads the rows of `query`, failing with [`TooManyRows`] instead of
+  /// loading them all when there are more than `max_rows`. `None` loads
+  /// without a cap.
+  ///
+  /// A safety net for list queries whose filter could go missing: Diesel
+  /// loads results eagerly, so an unfiltered `SELECT` on a large table would
+  /// otherwise be read into memory in full. The query is sent with
+  /// `LIMIT max_rows + 1`, so at most one row past the cap is ever read.
+  ///
+  /// Limitations:
+  /// - Only DSL queries that accept `.limit()` can be capped; `sql_query`
+  ///   cannot, so add a `LIMIT` to raw SQL by hand.
+  /// - The cap replaces any `.limit()` already on `query`. Pass `None` for a
+  ///   query that is paginated, or page through [`RowStream`] instead.
+  ///
+  /// [`RowStream`]: super::RowStream
+  ///
+  /// ```rust,ignore
+  /// let rows: Vec<Property> = db
+  ///   .load_capped(properties::table.filter(properties::owner_id.eq(owner)), Some(1_000))
+  ///   .await?;
+  /// ```
+  pub async fn load_capped<Q, U>(
+    &self,
+    query: Q,
+    max_rows: Option<u32>,
+  ) -> Result<Vec<U>>
+  where
+    Q: LimitDsl + LoadQuery<'static, SqliteConnection, U> + Send + 'static,
+    Limit<Q>: LoadQuery<'static, SqliteConnection, U>,
+    U: Send + 'static,
+  {
+    self
+      .execute(move |conn| {
+        let Some(max_rows) = max_rows else {
+          return Ok(query.load(conn)?);
+        };
+        let rows = query.limit(i64::from(max_rows) + 1).load(conn)?;
+        if rows.len() > max_rows as usize {
+          return Err(TooManyRows { max_rows }.into());
+        }
+        Ok(rows)
+      })
+      .await
+  }
+
+  /// Counts rows of `table` per `window` of `column` within `range`, as
+  /// `(bucket start, count)` pairs, oldest first.
+  ///
+  /// Buckets are cut in UTC with `strftime` (see [`analytics`](super::analytics)
+  /// for the expressions and the Postgres counterpart). `column` must hold
+  /// text SQLite's date functions understand (`YYYY-MM-DD HH:MM:SS`, RFC
+  /// 3339); rows it cannot parse are skipped. The range is compared with
+  /// `julianday`, so mixed formats filter correctly but no index is used.
+  ///
+  /// ```rust,ignore
+  /// let signups = db
+  ///   .count_by_window("users", "created_at", TimeWindow::Day, TimeRange { from, to })
+  ///   .await?;
+  /// ```
+  pub async fn count_by_window(
+    &self,
+    table: &str,
+    column: &str,
+    window: TimeWindow,
+    range: TimeRange,
+  ) -> Result<Vec<(DateTime<Utc>, i64)>> {
+    check_identifier(table)?;
+    check_identifier(column)?;
+    let query = format!(
+      "SELECT {bucket} AS bucket, COUNT(*) AS count FROM {table} \
+       WHERE julianday({column}) >= julianday(?) AND julianday({column}) < julianday(?) \
+       GROUP BY 1 ORDER BY 1",
+      bucket = window.sqlite_bucket(column),
+    );
+    let (from, to) = range.bounds();
+    self
+      .execute(move |conn| {
+        let rows = diesel::sql_query(query)
+          .bind::<Text, _>(from)
+          .bind::<Text, _>(to)
+          .load::<BucketRow>(conn)?;
+        rows.into_iter().map(BucketRow::into_pair).collect()
+      })
+      .await
+  }
+
+  /// Inserts a row or updates the existing one when it hits a conflict.
+  ///
+  /// The statement is built by the caller with Diesel's upsert DSL
+  /// (`insert_into(..).values(..).on_conflict(..).do_update().set(..)`) so the
+  /// insertable, the conflict target and the update set are all type-checked
+  /// against the table. The statement runs on a blocking thread like
+  /// [`DBSqlite::execute`].
+  ///
+  /// # Conflict target syntax
+  ///
+  /// SQLite only accepts column targets that match a `PRIMARY KEY` or `UNIQUE`
+  /// index, e.g. `.on_conflict(users::email)` or
+  /// `.on_conflict((users::email, users::username))`. Named constraints
+  /// (`ON CONFLICT ON CONSTRAINT ..`, Diesel's `on_constraint`) are
+  /// Postgres-only, and a target is mandatory for `DO UPDATE`.
+  ///
+  /// # Returns
+  ///
+  /// Returns [`UpsertOutcome::Inserted`] or [`UpsertOutcome::Updated`], the
+  /// same contract as `DBPostgres::upsert`. SQLite cannot report which path an
+  /// upsert took (`last_insert_rowid()` is per connection, not per table, and
+  /// never set for `WITHOUT ROWID` tables), so the statement runs in a
+  /// `BEGIN IMMEDIATE` transaction next to a temporary `AFTER INSERT` trigger
+  /// on the target table that records whether a row was inserted. The
+  /// trigger only exists on this connection and is dropped before commit (or
+  /// with the rollback).
+  ///
+  /// # Example
+  ///
+  /// ```rust,ignore
+  /// use axum_starter::schemas::table::users;
+  /// use diesel::upsert::excluded;
+  ///
+  /// let outcome = db
+  ///   .upsert(
+  ///     diesel::insert_into(users::table)
+  ///       .values(new_user)
+  ///       .on_conflict(users::email)
+  ///       .do_update()
+  ///       .set(users::username.eq(excluded(users::username))),
+  ///   )
+  ///   .await?;
+  /// ```
+  pub async fn upsert<T, U, Op>(
+    &self,
+    statement: InsertStatement<T, U, Op>,
+  ) -> Result<UpsertOutcome>
+  where
+    T: Table + HasTable<Table = T> + QueryFragment<Sqlite> + Send + 'static,
+    T::FromClause: Send,
+    U: Send + 'static,
+    Op: Send + 'static,
+    InsertStatement<T, U, Op>: ExecuteDsl<SqliteConnection>,
+  {
+    self
+      .execute(move |conn| Self::upsert_on(conn, statement))
+      .await
+  }
+
+  fn upsert_on<T, U, Op>(
+    conn: &mut SqliteConnection,
+    statement: InsertStatement<T, U, Op>,
+  ) -> Result<UpsertOutcome>
+  where
+    T: Table + HasTable<Table = T> + QueryFragment<Sqlite>,
+    InsertStatement<T, U, Op>: ExecuteDsl<SqliteConnection>,
+  {
+    let mut table = SqliteQueryBuilder::new();
+    T::table().to_sql(&mut table, &Sqlite)?;
+    let table = table.finish();
+    conn.immediate_transaction(|conn| {
+      conn.batch_execute(&format!(
+        "CREATE TEMP TABLE IF NOT EXISTS upsert_probe (inserted INTEGER NOT NULL); \
+         DELETE FROM temp.upsert_probe; \
+         CREATE TEMP TRIGGER upsert_probe AFTER INSERT ON {table} \
+         BEGIN INSERT INTO upsert_probe (inserted) VALUES (1); END;"
+      ))?;
+      statement
+        .execute(conn)
+        .map_err(|e| anyhow::anyhow!("DB_ERROR_UPSERT: {}", e))?;
+      let inserted: bool =
+        diesel::select(dsl::sql::<Bool>("EXISTS (SELECT 1 FROM temp.upsert_probe)"))
+          .get_result(conn)?;
+      conn.batch_execute("DROP TRIGGER temp.upsert_probe")?;
+
+      if inserted {
+        Ok(UpsertOutcome::Inserted)
+      } else {
+        Ok(UpsertOutcome::Updated)
+      }
+    })
+  }
+
+  /// Get-or-insert for lookup rows (tags, categories): returns the row
+  /// `find` selects, inserting it with `create` first when there is none,
+  /// and whether this call inserted it.
+  ///
+  /// Find and insert run in one `BEGIN IMMEDIATE` transaction, so concurrent
+  /// callers are serialized on the write lock: the loser sees the database as
+  /// busy, is retried like [`execute_with_retry`](Self::execute_with_retry),
+  /// and then finds the winner's row. An insert from outside this helper that
+  /// lands first surfaces as a unique violation instead; the insert is rolled
+  /// back to a savepoint and `find` runs again. `find` must therefore select
+  /// by the same unique key `create` writes, and both may run more than once.
+  ///
+  /// ```rust,ignore
+  /// let (tag, created) = db
+  ///   .find_or_create(
+  ///     move |conn| tags::table.filter(tags::name.eq(&name)).first(conn).optional(),
+  ///     move |conn| {
+  ///       diesel::insert_into(tags::table)
+  ///         .values(tags::name.eq(&name))
+  ///         .get_result(conn)
+  ///     },
+  ///   )
+  ///   .await?;
+  /// ```
+  pub async fn find_or_create<T, F, C>(
+    &self,
+    find: F,
+    create: C,
+  ) -> Result<(T, bool)>
+  where
+    F: Fn(&mut SqliteConnection) -> QueryResult<Option<T>> + Send + Sync + 'static,
+    C: Fn(&mut SqliteConnection) -> QueryResult<T> + Send + Sync + 'static,
+    T: Send + 'static,
+  {
+    self
+      .execute_with_retry(move |conn| {
+        conn.immediate_transaction(|conn| {
+          if let Some(found) = find(conn)? {
+            return Ok((found, false));
+          }
+          match conn.transaction(|conn| create(conn)) {
+            Ok(created) => Ok((created, true)),
+            Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+              find(conn)?.map(|found| (found, false)).ok_or_else(|| {
+                anyhow::anyhow!("DB_FIND_OR_CREATE_MISSING: unique violation but no row found")
+              })
+            }
+            Err(e) => Err(e.into()),
+          }
+        })
+      })
+      .await
+  }
+
+  /// Optimistic-concurrency update: applies `changes` to `target` (usually
+  /// `table.find(id)`) only if its `version` column still equals
+  /// `expected_version`, and in the same statement sets `version` to
+  /// `expected_version + 1`. Callers never bump the version themselves; they
+  /// pass the version they read and get the new one back.
+  ///
+  /// The update and the affected-row check run in one transaction. When no row
+  /// matches (another writer got there first, or the row was deleted) nothing
+  /// is written and the error is a [`StaleVersion`], which `?` turns into a
+  /// `409` in handlers.
+  ///
+  /// The table needs `version INTEGER NOT NULL DEFAULT 1`.
+  ///
+  /// ```rust,ignore
+  /// let version = db
+  ///   .update_if_version(
+  ///     properties::table.find(id),
+  ///     properties::version,
+  ///     body.version,
+  ///     properties::title.eq(body.title),
+  ///   )
+  ///   .await?;
+  /// ```
+  pub async fn update_if_version<T, V, C>(
+    &self,
+    target: T,
+    version: V,
+    expected_version: i32,
+    changes: C,
+  ) -> Result<i32>
+  where
+    T: FilterDsl<dsl::Eq<V, i32>> + Send + 'static,
+    V: ExpressionMethods + Copy + Send + 'static,
+    V::SqlType: SqlType,
+    i32: AsExpression<V::SqlType>,
+    dsl::Filter<T, dsl::Eq<V, i32>>: IntoUpdateTarget,
+    (C, dsl::Eq<V, i32>):
+      AsChangeset<Target = <dsl::Filter<T, dsl::Eq<V, i32>> as HasTable>::Table>,
+    C: Send + 'static,
+    dsl::Update<dsl::Filter<T, dsl::Eq<V, i32>>, (C, dsl::Eq<V, i32>)>:
+      AsQuery + ExecuteDsl<SqliteConnection>,
+  {
+    let next_version = expected_version + 1;
+    self
+      .execute(move |conn| {
+        conn.transaction(|conn| {
+          let updated = diesel::update(target.filter(version.eq(expected_version)))
+            .set((changes, version.eq(next_version)))
+            .execute(conn)?;
+          if updated == 0 {
+            return Err(
+              StaleVersion {
+                expected: expected_version,
+              }
+              .into(),
+            );
+          }
+          Ok(next_version)
+        })
+      })
+      .await
+  }
+
+  /// Inserts `rows` in chunks of `chunk_size`, all in one transaction: either
+  /// every row is written or, if any chunk fails, none are. Returns the number
+  /// of rows inserted.
+  ///
+  /// `insert` writes one chunk. Chunking keeps each statement under SQLite's
+  /// bound-parameter limit (32766), so pick `chunk_size` below `32766 /
+  /// columns`. The error names the row range of the failing chunk, e.g.
+  /// `DB_BATCH_INSERT_FAILED: rows 200..300: UNIQUE constraint failed`.
+  ///
+  /// See [`DBSqlite::insert_batch_lenient`] for best-effort inserts.
+  ///
+  /// ```rust,ignore
+  /// let inserted = db
+  ///   .insert_batch(new_users, 100, |conn, chunk| {
+  ///     diesel::insert_into(users::table).values(chunk).execute(conn)
+  ///   })
+  ///   .await?;
+  /// ```
+  pub async fn insert_batch<T, F>(
+    &self,
+    rows: Vec<T>,
+    chunk_size: usize,
+    insert: F,
+  ) -> Result<usize>
+  where
+    T: Send + 'static,
+    F: Fn(&mut SqliteConnection, &[T]) -> diesel::QueryResult<usize> + Send + 'static,
+  {
+    let chunk_size = chunk_size.max(1);
+    self
+      .execute(move |conn| {
+        conn.transaction(|conn| {
+          let mut inserted = 0;
+          for (index, chunk) in rows.chunks(chunk_size).enumerate() {
+            let start = index * chunk_size;
+            inserted += insert(conn, chunk).map_err(|e| {
+              anyhow::anyhow!(
+                "DB_BATCH_INSERT_FAILED: rows {}..{}: {}",
+                start,
+                start + chunk.len(),
+                e
+              )
+            })?;
+          }
+          Ok(inserted)
+        })
+      })
+      .await
+  }
+
+  /// Like [`DBSqlite::insert_batch`], but commits each chunk in its own
+  /// transaction and carries on past failures, reporting which chunks were
+  /// rolled back in [`BatchResult::failed_chunks`].
+  ///
+  /// The trade-off: a failure no longer undoes the whole batch, so the table
+  /// can end up with only part of it, and the caller has to decide what to do
+  /// with the failed ranges (retry them, report them, or delete what did
+  /// commit). Use it where partial progress is worth more than atomicity,
+  /// e.g. imports whose bad rows are fixed and re-sent. One bad row still
+  /// loses its whole chunk; a smaller `chunk_size` narrows that down at the
+  /// cost of more transactions.
+  ///
+  /// Only failing to get a connection is returned as `Err`.
+  pub async fn insert_batch_lenient<T, F>(
+    &self,
+    rows: Vec<T>,
+    chunk_size: usize,
+    insert: F,
+  ) -> Result<BatchResult>
+  where
+    T: Send + 'static,
+    F: Fn(&mut SqliteConnection, &[T]) -> diesel::QueryResult<usize> + Send + 'static,
+  {
+    let chunk_size = chunk_size.max(1);
+    self
+      .execute(move |conn| {
+        let mut result = BatchResult::default();
+        for (index, chunk) in rows.chunks(chunk_size).enumerate() {
+          let start = index * chunk_size;
+          match conn.transaction(|conn| insert(conn, chunk)) {
+            Ok(inserted) => result.inserted += inserted,
+            Err(e) => {
+              let range = start..start + chunk.len();
+              tracing::warn!(rows = ?range, error = %e, "DB_BATCH_CHUNK_FAILED");
+              result.failed_chunks.push((range, e));
+            }
+          }
+        }
+        Ok(result)
+      })
+      .await
+  }
+
+  /// Writes a consistent copy of the database to `path` while the app keeps
+  /// serving requests, using `VACUUM INTO`.
+  ///
+  /// The copy is taken inside a single read transaction, so it reflects one
+  /// point in time and is already compacted. In WAL mode readers never block
+  /// writers, so writes continue normally during the backup; in the default
+  /// rollback-journal mode writers wait until the copy finishes, which for
+  /// large databases can take seconds.
+  ///
+  /// # Errors
+  ///
+  /// - `BACKUP_TARGET_EXISTS` if `path` already exists (it is never overwritten)
+  /// - `BACKUP_TARGET_NOT_WRITABLE` if `path` cannot be created
+  /// - `BACKUP_FAILED` if SQLite fails while writing; the partial file is removed
+  ///
+  /// # Example
+  ///
+  /// ```rust,ignore
+  /// db.backup_to(Path::new("data/backups/app-20260101.db")).await?;
+  /// ```
+  pub async fn backup_to(
+    &self,
+    path: &Path,
+  ) -> Result<()> {
+    // Claiming the (empty) file up front both checks writability and keeps a
+    // concurrent backup from targeting the same path; VACUUM INTO accepts an
+    // empty target file.
+    match std::fs::OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(path)
+    {
+      Ok(_) => {}
+      Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+        anyhow::bail!("BACKUP_TARGET_EXISTS: {}", path.display())
+      }
+      Err(e) => anyhow::bail!("BACKUP_TARGET_NOT_WRITABLE: {}: {}", path.display(), e),
+    }
+
+    let target = path.to_string_lossy().into_owned();
+    let result = self
+      .execute(move |conn| {
+        diesel::sql_query("VACUUM INTO ?")
+          .bind::<Text, _>(target)
+          .execute(conn)?;
+        Ok(())
+      })
+      .await;
+
+    if let Err(e) = result {
+      let _ = std::fs::remove_file(path);
+      anyhow::bail!("BACKUP_FAILED: {}", e);
+    }
+    tracing::info!(path = %path.display(), "DATABASE_BACKUP_CREATED");
+    Ok(())
   }
 
   /// Runs a health check query to verify database connectivity.
@@ -338,7 +1300,6 @@ impl DBSqlite {
   /// }
   /// ```
   pub async fn health_check(&self) -> Result<()> {
-    use diesel::RunQueryDsl;
     use diesel::sql_query;
 
     self
@@ -359,16 +1320,1076 @@ impl DBSqlite {
   ///
   /// # Example
   ///
-  /// ```rust
+  /// ```rust,no_run
   /// use axum_starter::services::DBSqlite;
   ///
   /// let db = DBSqlite::new("sqlite://database.db")?;
   /// let (total, idle) = db.pool_stats();
   /// println!("Total: {}, Idle: {}", total, idle);
-  /// # Ok::<_, diesel::r2d2::PoolError>(())
+  /// # Ok::<_, axum_starter::services::DBSqliteError>(())
   /// ```
   pub fn pool_stats(&self) -> (u32, u32) {
     let state = self.pool.state();
     (state.connections, state.idle_connections)
   }
+
+  /// Maximum number of connections the pool will open (`max_size`).
+  pub fn pool_max_size(&self) -> u32 {
+    self.pool.max_size()
+  }
+
+  /// Pings every idle connection once and evicts those that fail, returning
+  /// `(pinged, evicted)`. One sweep of [`spawn_pre_ping`]; blocks, so call it
+  /// from `spawn_blocking`.
+  ///
+  /// All idle connections are checked out together so each is visited once;
+  /// a request arriving mid-sweep waits for them or opens a new one. A failed
+  /// connection is marked broken, so r2d2 drops it on return instead of
+  /// reusing it, and refills the pool to `min_idle`.
+  pub fn pre_ping(&self) -> (usize, usize) {
+    let (_, idle) = self.pool_stats();
+    let mut held = Vec::new();
+    for _ in 0..idle {
+      match self.pool.try_get() {
+        Some(conn) => held.push(conn),
+        None => break,
+      }
+    }
+    let pinged = held.len();
+    let mut evicted = 0;
+    for conn in &mut held {
+      if let Err(e) = conn.ping() {
+        tracing::warn!(error = %e, "DB_PRE_PING_EVICTED");
+        <AnsiTransactionManager as TransactionManager<SqliteConnection>>::transaction_manager_status_mut(conn)
+          .set_in_error();
+        evicted += 1;
+      }
+    }
+    (pinged, evicted)
+  }
+
+  /// Share of [`pool_max_size`](Self::pool_max_size) currently checked out,
+  /// from `0.0` (all idle or not yet opened) to `1.0` (callers now wait for
+  /// a connection). Idle connections do not count.
+  pub fn pool_utilization(&self) -> f64 {
+    let (total, idle) = self.pool_stats();
+    f64::from(total.saturating_sub(idle)) / f64::from(self.pool_max_size())
+  }
+
+  /// Fails the test if connections checked out from the pool don't drop back to
+  /// `baseline` within a short window, flagging a leaked `PooledConnection`.
+  ///
+  /// `baseline` is a [`DBSqlite::pool_stats`] snapshot taken before the code under
+  /// test ran; only checked-out connections (`total - idle`) are compared, so
+  /// the pool growing or reaping idle connections does not count as a leak.
+  ///
+  /// This is advisory: connections are returned when the blocking closure or
+  /// guard drops, which can lag the awaited response slightly, hence the polling
+  /// window. Work still legitimately running in background tasks will also trip
+  /// it. Intended for integration tests, not production code.
+  ///
+  /// # Panics
+  ///
+  /// Panics with `DB_CONNECTION_LEAKED` if more connections are still checked
+  /// out than at `baseline` after the window elapses.
+  ///
+  /// # Example
+  ///
+  /// ```rust,ignore
+  /// let baseline = db.pool_stats();
+  /// app.client.get(format!("{}/users", app.address)).send().await?;
+  /// db.assert_no_leaked_connections(baseline).await;
+  /// ```
+  pub async fn assert_no_leaked_connections(
+    &self,
+    baseline: (u32, u32),
+  ) {
+    let (total, idle) = baseline;
+    let expected = total.saturating_sub(idle);
+    let deadline = tokio::time::Instant::now() + LEAK_CHECK_WINDOW;
+    loop {
+      let (total, idle) = self.pool_stats();
+      let checked_out = total.saturating_sub(idle);
+      if checked_out <= expected {
+        return;
+      }
+      if tokio::time::Instant::now() >= deadline {
+        panic!(
+          "DB_CONNECTION_LEAKED: {} checked out, expected at most {}",
+          checked_out, expected
+        );
+      }
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  }
+}
+
+/// Share of `base` allowed when `in_use` of `max` connections are checked out,
+/// per [`ADAPTIVE_ACQUIRE_BREAKPOINTS`].
+fn adaptive_timeout(
+  base: Duration,
+  in_use: u32,
+  max: u32,
+) -> Duration {
+  let utilization = in_use.saturating_mul(100) / max.max(1);
+  ADAPTIVE_ACQUIRE_BREAKPOINTS
+    .iter()
+    .find(|(threshold, _)| utilization >= *threshold)
+    .map_or(base, |(_, share)| base * *share / 100)
+}
+
+/// Span for work handed to the blocking pool.
+///
+/// `spawn_blocking` does not inherit the caller's span, so SQL logged by
+/// `LOG_SQL` would lose the `REQUEST` context. This span is created in the
+/// caller (making the request span its parent) and also records the current
+/// [`RequestId`], which covers work scoped with `with_request_id` outside the
+/// HTTP stack. SQLite has no per-connection session label comparable to
+/// Postgres' `application_name`, so logs are the only correlation point.
+fn db_span() -> tracing::Span {
+  let request_id = RequestId::current();
+  tracing::debug_span!(
+    "DB",
+    request_id = request_id.as_ref().map(RequestId::as_str)
+  )
+}
+
+/// Starts task `name`, sweeping `db` with [`DBSqlite::pre_ping`] every
+/// interval. No-op unless `db` was built with [`ConnectionCheck::PrePing`];
+/// start one per pool built that way, or its connections go unchecked.
+pub fn spawn_pre_ping(
+  tasks: &mut BackgroundTasks,
+  name: &'static str,
+  db: DBSqlite,
+) {
+  let ConnectionCheck::PrePing(interval) = db.connection_check else {
+    return;
+  };
+  tasks.spawn(name, move |mut shutdown| {
+    let db = db.clone();
+    async move {
+      let mut ticker = tokio::time::interval(interval);
+      ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+      ticker.tick().await;
+      loop {
+        tokio::select! {
+          _ = ticker.tick() => {
+            let sweep = db.clone();
+            match tokio::task::spawn_blocking(move || sweep.pre_ping()).await {
+              Ok((pinged, evicted)) => tracing::debug!(task = name, pinged, evicted, "DB_PRE_PING"),
+              Err(e) => tracing::error!(task = name, error = %e, "DB_PRE_PING_FAILED"),
+            }
+          }
+          _ = shutdown.changed() => break,
+        }
+      }
+    }
+  });
+}
+
+/// Current `busy_timeout` of `conn`, in milliseconds.
+fn busy_timeout(conn: &mut SqliteConnection) -> QueryResult<i32> {
+  #[derive(QueryableByName)]
+  struct BusyTimeout {
+    #[diesel(sql_type = Integer)]
+    timeout: i32,
+  }
+
+  diesel::sql_query("PRAGMA busy_timeout")
+    .get_result::<BusyTimeout>(conn)
+    .map(|row| row.timeout)
+}
+
+/// Whether `error` is SQLite's transient `SQLITE_BUSY`/`SQLITE_LOCKED`.
+fn is_busy(error: &anyhow::Error) -> bool {
+  match error.downcast_ref::<diesel::result::Error>() {
+    Some(diesel::result::Error::DatabaseError(_, info)) => {
+      let message = info.message();
+      message.contains("database is locked") || message.contains("database table is locked")
+    }
+    _ => false,
+  }
+}
+
+fn lock_timeout_error(lock_timeout: Duration) -> anyhow::Error {
+  anyhow::anyhow!(
+    "MIGRATION_LOCK_TIMEOUT: another process held the database lock for over {}ms",
+    lock_timeout.as_millis()
+  )
+}
+
+/// File a SQLite URL opens, or `None` for an in-memory database. Accepts a
+/// plain path, `sqlite://path` (diesel rewrites it to `file:path`) and
+/// `file:` URIs.
+pub(super) fn database_file(database_url: &str) -> Result<Option<PathBuf>, DBSqliteError> {
+  let invalid = || DBSqliteError::InvalidDatabaseUrl(database_url.to_string());
+  if database_url == ":memory:" {
+    return Ok(None);
+  }
+  let Some(uri) = database_url
+    .strip_prefix("sqlite://")
+    .or_else(|| database_url.strip_prefix("file:"))
+  else {
+    return if database_url.is_empty() {
+      Err(invalid())
+    } else {
+      Ok(Some(PathBuf::from(database_url)))
+    };
+  };
+  let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+  // `file://host/path`: only an empty or `localhost` authority is valid
+  let path = match path.strip_prefix("//") {
+    Some(rest) => &rest[rest.find('/').unwrap_or(rest.len())..],
+    None => path,
+  };
+  if path == ":memory:" || query.split('&').any(|param| param == "mode=memory") {
+    return Ok(None);
+  }
+  if path.is_empty() {
+    return Err(invalid());
+  }
+  Ok(Some(PathBuf::from(path)))
+}
+
+/// Makes sure `path` can be opened for writing, creating its directory
+/// (when `create_parent_dir`) and the file itself (when `create_file`) if
+/// needed.
+fn prepare_database_file(
+  path: &Path,
+  config: &DBSqliteConfig,
+) -> Result<(), DBSqliteError> {
+  let io_error = |path: &Path, source| DBSqliteError::IoError {
+    path: path.to_path_buf(),
+    source,
+  };
+  let parent = path
+    .parent()
+    .filter(|dir| !dir.as_os_str().is_empty())
+    .unwrap_or(Path::new("."));
+  if !parent.is_dir() {
+    if !config.create_parent_dir {
+      return Err(io_error(
+        parent,
+        std::io::Error::new(ErrorKind::NotFound, "directory does not exist"),
+      ));
+    }
+    std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    tracing::info!(dir = %parent.display(), "DB_DIRECTORY_CREATED");
+  }
+
+  if !config.create_file {
+    return OpenOptions::new()
+      .write(true)
+      .open(path)
+      .map(drop)
+      .map_err(|e| io_error(path, e));
+  }
+  let mut create = OpenOptions::new();
+  create.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut create, 0o600);
+  match create.open(path) {
+    Ok(_) => Ok(()),
+    Err(e) if e.kind() == ErrorKind::AlreadyExists => OpenOptions::new()
+      .write(true)
+      .open(path)
+      .map(drop)
+      .map_err(|e| io_error(path, e)),
+    Err(e) => Err(io_error(path, e)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use diesel::dsl::sql;
+  use diesel::prelude::*;
+  use diesel::sql_types::BigInt;
+  use diesel::upsert::excluded;
+  use std::sync::Arc;
+  use std::sync::atomic::{AtomicBool, Ordering};
+  use tempfile::NamedTempFile;
+
+  diesel::table! {
+    settings (key) {
+      key -> Text,
+      value -> Text,
+    }
+  }
+
+  diesel::table! {
+    flags (name) {
+      name -> Text,
+      value -> Text,
+    }
+  }
+
+  diesel::table! {
+    documents (id) {
+      id -> Integer,
+      title -> Text,
+      version -> Integer,
+    }
+  }
+
+  async fn settings_db() -> (NamedTempFile, DBSqlite) {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.execute(|conn| {
+      diesel::sql_query(
+        "CREATE TABLE settings (key TEXT PRIMARY KEY NOT NULL, value TEXT NOT NULL)",
+      )
+      .execute(conn)?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+    (file, db)
+  }
+
+  /// Five rows in chunks of two; row 3 repeats key `k1`, so chunk `2..4` fails.
+  fn batch_rows() -> Vec<(String, String)> {
+    ["k0", "k1", "k2", "k1", "k4"]
+      .iter()
+      .map(|k| (k.to_string(), "v".to_string()))
+      .collect()
+  }
+
+  fn insert_settings(
+    conn: &mut SqliteConnection,
+    chunk: &[(String, String)],
+  ) -> diesel::QueryResult<usize> {
+    let values: Vec<_> = chunk
+      .iter()
+      .map(|(k, v)| (settings::key.eq(k), settings::value.eq(v)))
+      .collect();
+    diesel::insert_into(settings::table)
+      .values(values)
+      .execute(conn)
+  }
+
+  async fn setting_keys(db: &DBSqlite) -> Vec<String> {
+    db.execute(|conn| {
+      Ok(
+        settings::table
+          .select(settings::key)
+          .order(settings::key)
+          .load(conn)?,
+      )
+    })
+    .await
+    .unwrap()
+  }
+
+  #[tokio::test]
+  async fn insert_batch_is_all_or_nothing() {
+    let (_file, db) = settings_db().await;
+    let err = db
+      .insert_batch(batch_rows(), 2, insert_settings)
+      .await
+      .unwrap_err();
+    assert!(
+      err
+        .to_string()
+        .starts_with("DB_BATCH_INSERT_FAILED: rows 2..4:")
+    );
+    assert!(setting_keys(&db).await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn insert_batch_lenient_keeps_committed_chunks() {
+    let (_file, db) = settings_db().await;
+    let result = db
+      .insert_batch_lenient(batch_rows(), 2, insert_settings)
+      .await
+      .unwrap();
+    assert_eq!(result.inserted, 3);
+    assert_eq!(result.failed_chunks.len(), 1);
+    assert_eq!(result.failed_chunks[0].0, 2..4);
+    assert_eq!(setting_keys(&db).await, ["k0", "k1", "k4"]);
+  }
+
+  #[test]
+  fn missing_file_is_reported_when_creation_is_off() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.db");
+    let url = format!("sqlite://{}", path.display());
+
+    let existing_only = DBSqliteConfig {
+      create_file: false,
+      ..Default::default()
+    };
+    let err = DBSqlite::with_config(&url, &existing_only).unwrap_err();
+    assert!(matches!(&err, DBSqliteError::IoError { path: p, .. } if p == &path));
+    assert!(!path.exists());
+
+    DBSqlite::new(&url).unwrap();
+    DBSqlite::with_config(&url, &existing_only).unwrap();
+  }
+
+  #[test]
+  fn missing_directory_is_created_or_reported_up_front() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested/app.db");
+    let url = format!("sqlite://{}", path.display());
+
+    let strict = DBSqliteConfig {
+      create_parent_dir: false,
+      ..Default::default()
+    };
+    let err = DBSqlite::with_config(&url, &strict).unwrap_err();
+    assert!(
+      matches!(&err, DBSqliteError::IoError { path: p, .. } if p == &dir.path().join("nested"))
+    );
+    assert!(err.to_string().starts_with("DB_PATH_IO_ERROR"));
+
+    DBSqlite::new(&url).unwrap();
+    assert!(path.is_file());
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+    }
+  }
+
+  #[test]
+  fn database_file_parses_urls() {
+    let file = |url| database_file(url).unwrap();
+    assert_eq!(file(":memory:"), None);
+    assert_eq!(file("file::memory:?cache=shared"), None);
+    assert_eq!(file("file:app.db?mode=memory"), None);
+    assert_eq!(file("sqlite://data/app.db"), Some("data/app.db".into()));
+    assert_eq!(file("sqlite:///srv/app.db"), Some("/srv/app.db".into()));
+    assert_eq!(
+      file("file:///srv/app.db?mode=rwc"),
+      Some("/srv/app.db".into())
+    );
+    assert_eq!(file("app.db"), Some("app.db".into()));
+    assert!(matches!(
+      database_file("sqlite://"),
+      Err(DBSqliteError::InvalidDatabaseUrl(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn update_if_version_rejects_a_stale_version() {
+    let (_file, db) = settings_db().await;
+    db.execute(|conn| {
+      conn.batch_execute(
+        "CREATE TABLE documents (
+           id INTEGER PRIMARY KEY NOT NULL,
+           title TEXT NOT NULL,
+           version INTEGER NOT NULL DEFAULT 1
+         );
+         INSERT INTO documents (id, title) VALUES (1, 'draft');",
+      )?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+
+    let rename = |title: &str, expected| {
+      db.update_if_version(
+        documents::table.find(1),
+        documents::version,
+        expected,
+        documents::title.eq(title.to_string()),
+      )
+    };
+
+    assert_eq!(rename("first", 1).await.unwrap(), 2);
+    // A second writer that also read version 1 loses
+    let err = rename("second", 1).await.unwrap_err();
+    assert_eq!(err.downcast_ref::<StaleVersion>().unwrap().expected, 1);
+    assert_eq!(
+      crate::services::HttpError::from(err).status(),
+      axum::http::StatusCode::CONFLICT
+    );
+
+    let row: (String, i32) = db
+      .execute(|conn| {
+        Ok(
+          documents::table
+            .select((documents::title, documents::version))
+            .first(conn)?,
+        )
+      })
+      .await
+      .unwrap();
+    assert_eq!(row, ("first".to_string(), 2));
+  }
+
+  #[tokio::test]
+  async fn upsert_reports_insert_then_update() {
+    let (_file, db) = settings_db().await;
+
+    let upsert = |value: &str| {
+      diesel::insert_into(settings::table)
+        .values((
+          settings::key.eq("theme"),
+          settings::value.eq(value.to_string()),
+        ))
+        .on_conflict(settings::key)
+        .do_update()
+        .set(settings::value.eq(excluded(settings::value)))
+    };
+
+    assert_eq!(
+      db.upsert(upsert("light")).await.unwrap(),
+      UpsertOutcome::Inserted
+    );
+    assert_eq!(
+      db.upsert(upsert("dark")).await.unwrap(),
+      UpsertOutcome::Updated
+    );
+
+    let value: String = db
+      .execute(|conn| Ok(settings::table.select(settings::value).first(conn)?))
+      .await
+      .unwrap();
+    assert_eq!(value, "dark");
+  }
+
+  #[tokio::test]
+  async fn load_capped_fails_past_the_cap() {
+    let (_file, db) = settings_db().await;
+    db.execute(|conn| {
+      let rows: Vec<_> = ["a", "b", "c"]
+        .iter()
+        .map(|key| (settings::key.eq(*key), settings::value.eq("v")))
+        .collect();
+      Ok(
+        diesel::insert_into(settings::table)
+          .values(rows)
+          .execute(conn)?,
+      )
+    })
+    .await
+    .unwrap();
+
+    let keys = || settings::table.select(settings::key).order(settings::key);
+    let all: Vec<String> = db.load_capped(keys(), Some(3)).await.unwrap();
+    assert_eq!(all, ["a", "b", "c"]);
+    assert_eq!(
+      db.load_capped::<_, String>(keys(), None)
+        .await
+        .unwrap()
+        .len(),
+      3
+    );
+
+    let err = db
+      .load_capped::<_, String>(keys(), Some(2))
+      .await
+      .unwrap_err();
+    assert_eq!(err.downcast_ref::<TooManyRows>().unwrap().max_rows, 2);
+  }
+
+  #[tokio::test]
+  async fn upsert_outcome_ignores_other_tables_and_rowids() {
+    let (_file, db) = settings_db().await;
+    let mut conn = db.get_connection().unwrap();
+    diesel::sql_query(
+      "CREATE TABLE flags (name TEXT PRIMARY KEY NOT NULL, value TEXT NOT NULL) WITHOUT ROWID",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    // Rowid 1 in another table, so the settings insert below reuses it
+    diesel::sql_query("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+      .execute(&mut conn)
+      .unwrap();
+    diesel::sql_query("INSERT INTO other (id) VALUES (1)")
+      .execute(&mut conn)
+      .unwrap();
+
+    let insert_setting = diesel::insert_into(settings::table)
+      .values((settings::key.eq("theme"), settings::value.eq("light")))
+      .on_conflict(settings::key)
+      .do_update()
+      .set(settings::value.eq(excluded(settings::value)));
+    assert_eq!(
+      DBSqlite::upsert_on(&mut conn, insert_setting).unwrap(),
+      UpsertOutcome::Inserted
+    );
+
+    let upsert_flag = |conn: &mut SqliteConnection| {
+      let statement = diesel::insert_into(flags::table)
+        .values((flags::name.eq("beta"), flags::value.eq("on")))
+        .on_conflict(flags::name)
+        .do_update()
+        .set(flags::value.eq(excluded(flags::value)));
+      DBSqlite::upsert_on(conn, statement).unwrap()
+    };
+    assert_eq!(upsert_flag(&mut conn), UpsertOutcome::Inserted);
+    assert_eq!(upsert_flag(&mut conn), UpsertOutcome::Updated);
+    assert_eq!(
+      DBSqlite::upsert_on(
+        &mut conn,
+        diesel::insert_into(settings::table)
+          .values((settings::key.eq("theme"), settings::value.eq("dark")))
+          .on_conflict(settings::key)
+          .do_update()
+          .set(settings::value.eq(excluded(settings::value))),
+      )
+      .unwrap(),
+      UpsertOutcome::Updated
+    );
+  }
+
+  #[tokio::test]
+  async fn find_or_create_race_inserts_once() {
+    let (_file, db) = settings_db().await;
+    let find_or_create = |db: DBSqlite| async move {
+      db.find_or_create(
+        |conn| {
+          settings::table
+            .find("locale")
+            .select((settings::key, settings::value))
+            .first::<(String, String)>(conn)
+            .optional()
+        },
+        |conn| {
+          diesel::insert_into(settings::table)
+            .values((settings::key.eq("locale"), settings::value.eq("en")))
+            .execute(conn)?;
+          Ok(("locale".to_string(), "en".to_string()))
+        },
+      )
+      .await
+    };
+
+    let (a, b) = tokio::join!(
+      tokio::spawn(find_or_create(db.clone())),
+      tokio::spawn(find_or_create(db.clone()))
+    );
+    let (a, b) = (a.unwrap().unwrap(), b.unwrap().unwrap());
+    assert_eq!(a.0, b.0);
+    assert!(a.1 ^ b.1, "exactly one call inserts: {a:?} {b:?}");
+
+    let rows: i64 = db
+      .execute(|conn| Ok(settings::table.count().get_result(conn)?))
+      .await
+      .unwrap();
+    assert_eq!(rows, 1);
+  }
+
+  #[tokio::test]
+  #[should_panic(expected = "DB_CONNECTION_LEAKED")]
+  async fn assert_no_leaked_connections_flags_held_connection() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let baseline = db.pool_stats();
+
+    let _held = db.get_connection().unwrap();
+    db.assert_no_leaked_connections(baseline).await;
+  }
+
+  #[test]
+  fn pool_utilization_counts_checked_out_connections() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    assert_eq!(db.pool_utilization(), 0.0);
+
+    let max = db.pool_max_size();
+    let mut held: Vec<_> = (0..max / 2).map(|_| db.get_connection().unwrap()).collect();
+    assert_eq!(db.pool_utilization(), f64::from(max / 2) / f64::from(max));
+
+    held.extend((max / 2..max).map(|_| db.get_connection().unwrap()));
+    assert_eq!(db.pool_utilization(), 1.0);
+    drop(held);
+    assert_eq!(db.pool_utilization(), 0.0);
+  }
+
+  #[test]
+  fn pre_ping_visits_each_idle_connection_once() {
+    let file = NamedTempFile::new().unwrap();
+    let config = DBSqliteConfig {
+      connection_check: ConnectionCheck::PrePing(Duration::from_secs(30)),
+      ..Default::default()
+    };
+    let db = DBSqlite::with_config(file.path().to_str().unwrap(), &config).unwrap();
+    let held = db.get_connection().unwrap();
+    // r2d2 refills `min_idle` in the background after the checkout
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while db.pool_stats().1 < 8 && std::time::Instant::now() < deadline {
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    let (total, idle) = db.pool_stats();
+
+    assert_eq!(db.pre_ping(), (idle as usize, 0));
+    assert_eq!(db.pool_stats(), (total, idle));
+    drop(held);
+  }
+
+  #[tokio::test]
+  async fn clones_share_one_pool() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let clone = db.clone();
+    let checked_out = |db: &DBSqlite| {
+      let (total, idle) = db.pool_stats();
+      total - idle
+    };
+    assert_eq!(checked_out(&db), 0);
+
+    let _held = clone.get_connection().unwrap();
+    assert_eq!(checked_out(&db), 1);
+  }
+
+  #[tokio::test]
+  async fn dropped_execute_skips_operation_and_releases_connection() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let baseline = db.pool_stats();
+
+    // Exhaust the pool so the blocking task is still waiting for a connection
+    // when the caller gives up.
+    let held: Vec<_> = (0..db.pool_max_size())
+      .map(|_| db.get_connection().unwrap())
+      .collect();
+    let ran = Arc::new(AtomicBool::new(false));
+    let ran_in_task = ran.clone();
+    let abandoned = tokio::time::timeout(
+      Duration::from_millis(50),
+      db.execute(move |_| {
+        ran_in_task.store(true, Ordering::SeqCst);
+        Ok(())
+      }),
+    )
+    .await;
+    assert!(abandoned.is_err());
+
+    drop(held);
+    // Give the blocking task time to pick up a freed connection.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    db.assert_no_leaked_connections(baseline).await;
+    assert!(!ran.load(Ordering::SeqCst));
+  }
+
+  #[tokio::test]
+  async fn blocking_task_dropped_at_shutdown_is_cancelled() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let baseline = db.pool_stats();
+
+    // A runtime that has shut down cancels blocking tasks instead of running
+    // them, as it does with tasks still queued when shutdown starts.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .build()
+      .unwrap();
+    let handle = runtime.handle().clone();
+    runtime.shutdown_background();
+
+    let ran = Arc::new(AtomicBool::new(false));
+    let ran_in_task = ran.clone();
+    let mut queued = Box::pin(db.execute(move |_| {
+      ran_in_task.store(true, Ordering::SeqCst);
+      Ok(())
+    }));
+    // Only the first poll calls `spawn_blocking`, so only it needs the
+    // shut-down runtime entered.
+    let first_poll = {
+      let _runtime = handle.enter();
+      let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+      queued.as_mut().poll(&mut cx)
+    };
+    let result = match first_poll {
+      std::task::Poll::Ready(result) => result,
+      std::task::Poll::Pending => queued.await,
+    };
+
+    let err = result.unwrap_err();
+    assert!(err.downcast_ref::<crate::services::Cancelled>().is_some());
+    assert!(!ran.load(Ordering::SeqCst));
+    db.assert_no_leaked_connections(baseline).await;
+  }
+
+  #[tokio::test]
+  async fn panicking_operation_reports_panic_and_releases_connection() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let baseline = db.pool_stats();
+
+    let err = db
+      .transaction(|_| -> Result<()> { panic!("boom") })
+      .await
+      .unwrap_err();
+    assert!(err.downcast_ref::<crate::services::Cancelled>().is_none());
+    assert_eq!(err.to_string(), "DB_TASK_PANICKED: boom");
+    db.assert_no_leaked_connections(baseline).await;
+
+    let one: i32 = db
+      .execute(|conn| {
+        Ok(diesel::select(1.into_sql::<diesel::sql_types::Integer>()).get_result(conn)?)
+      })
+      .await
+      .unwrap();
+    assert_eq!(one, 1);
+  }
+
+  #[test]
+  fn adaptive_timeout_follows_breakpoints() {
+    let base = Duration::from_secs(60);
+    assert_eq!(adaptive_timeout(base, 0, 32), base);
+    assert_eq!(adaptive_timeout(base, 15, 32), base);
+    assert_eq!(adaptive_timeout(base, 16, 32), Duration::from_secs(30));
+    assert_eq!(adaptive_timeout(base, 24, 32), Duration::from_secs(15));
+    assert_eq!(adaptive_timeout(base, 29, 32), Duration::from_secs(6));
+    assert_eq!(adaptive_timeout(base, 32, 32), Duration::from_secs(6));
+  }
+
+  #[tokio::test]
+  async fn backup_to_copies_database_and_never_overwrites() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.execute(|conn| {
+      diesel::sql_query("CREATE TABLE notes (body TEXT NOT NULL)").execute(conn)?;
+      diesel::sql_query("INSERT INTO notes (body) VALUES ('hello')").execute(conn)?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("backup.db");
+    db.backup_to(&target).await.unwrap();
+
+    let copy = DBSqlite::new(target.to_str().unwrap()).unwrap();
+    let count: i64 = copy
+      .execute(|conn| {
+        Ok(diesel::select(sql::<BigInt>("(SELECT COUNT(*) FROM notes)")).get_result(conn)?)
+      })
+      .await
+      .unwrap();
+    assert_eq!(count, 1);
+
+    let err = db.backup_to(&target).await.unwrap_err();
+    assert!(err.to_string().starts_with("BACKUP_TARGET_EXISTS"));
+  }
+
+  #[tokio::test]
+  async fn backup_to_reports_unwritable_target() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+
+    // The parent "directory" is a regular file.
+    let err = db
+      .backup_to(&file.path().join("backup.db"))
+      .await
+      .unwrap_err();
+    assert!(err.to_string().starts_with("BACKUP_TARGET_NOT_WRITABLE"));
+  }
+
+  #[test]
+  fn concurrent_migration_runs_do_not_conflict() {
+    let file = NamedTempFile::new().unwrap();
+    let url = file.path().to_str().unwrap().to_string();
+
+    let runs: Vec<_> = (0..2)
+      .map(|_| {
+        let url = url.clone();
+        std::thread::spawn(move || DBSqlite::new(&url).unwrap().run_migrations())
+      })
+      .collect();
+
+    for run in runs {
+      run.join().unwrap().unwrap();
+    }
+  }
+
+  #[test]
+  fn migrations_fail_clearly_while_another_process_holds_the_lock() {
+    let file = NamedTempFile::new().unwrap();
+    let url = file.path().to_str().unwrap().to_string();
+    let db = DBSqlite::new(&url).unwrap();
+
+    let mut holder = SqliteConnection::establish(&url).unwrap();
+    holder.batch_execute("BEGIN EXCLUSIVE").unwrap();
+    let err = db
+      .run_migrations_with_timeout(Duration::from_millis(50))
+      .unwrap_err();
+    assert!(err.to_string().starts_with("MIGRATION_LOCK_TIMEOUT"));
+
+    holder.batch_execute("COMMIT").unwrap();
+    db.run_migrations_with_timeout(Duration::from_millis(50))
+      .unwrap();
+  }
+
+  #[test]
+  fn migration_lock_restores_busy_timeout_of_pooled_connections() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    let timeouts = |db: &DBSqlite| {
+      let (total, _) = db.pool_stats();
+      let mut held: Vec<_> = (0..total).map(|_| db.get_connection().unwrap()).collect();
+      held
+        .iter_mut()
+        .map(|conn| busy_timeout(conn).unwrap())
+        .collect::<Vec<_>>()
+    };
+
+    db.run_migrations().unwrap();
+    assert!(timeouts(&db).iter().all(|&ms| ms == 0));
+
+    let err = db
+      .with_migration_lock(MIGRATION_LOCK_TIMEOUT, |_| -> Result<()> {
+        anyhow::bail!("MIGRATION_FAILED")
+      })
+      .unwrap_err();
+    assert_eq!(err.to_string(), "MIGRATION_FAILED");
+    assert!(timeouts(&db).iter().all(|&ms| ms == 0));
+  }
+
+  #[test]
+  fn stopped_migration_run_skips_the_rest_and_releases_the_lock() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+
+    let stopped = db.run_migrations_until(&AtomicBool::new(true)).unwrap();
+    assert!(stopped.applied.is_empty());
+    assert!(!stopped.skipped.is_empty());
+
+    // Nothing is left locked, and the next run applies what was skipped
+    let resumed = db.run_migrations_until(&AtomicBool::new(false)).unwrap();
+    assert_eq!(resumed.applied.len(), stopped.skipped.len());
+    assert!(resumed.skipped.is_empty());
+  }
+
+  #[test]
+  fn revert_is_local_only_and_reverses_run_migrations() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.run_migrations().unwrap();
+
+    let err = db.revert_all(&AppEnv::Production).unwrap_err();
+    assert!(err.to_string().starts_with("MIGRATION_REVERT_FORBIDDEN"));
+
+    let reverted = db.revert_all(&AppEnv::Local).unwrap();
+    assert!(!reverted.is_empty());
+    let err = db.revert_last_migration(&AppEnv::Local).unwrap_err();
+    assert!(err.to_string().starts_with("MIGRATION_REVERT_FAILURE"));
+
+    db.run_migrations().unwrap();
+    assert_eq!(
+      db.revert_last_migration(&AppEnv::Local).unwrap(),
+      reverted[0]
+    );
+  }
+
+  #[tokio::test]
+  async fn count_by_window_buckets_in_utc() {
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+    db.execute(|conn| {
+      conn.batch_execute(
+        "CREATE TABLE events (created_at TEXT NOT NULL);
+         INSERT INTO events VALUES
+           ('2024-03-03 23:59:59'),  -- Sunday
+           ('2024-03-04T00:00:00Z'), -- Monday, new week
+           ('2024-03-04 00:30:00'),
+           ('2024-03-04 01:00:00'),
+           ('2024-03-10 12:00:00'),  -- Sunday, same week
+           ('2024-03-11 00:00:00');  -- excluded by the range",
+      )?;
+      Ok(())
+    })
+    .await
+    .unwrap();
+
+    let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+    let range = TimeRange {
+      from: at("2024-03-01T00:00:00Z"),
+      to: at("2024-03-11T00:00:00Z"),
+    };
+    let count = |window| db.count_by_window("events", "created_at", window, range);
+
+    assert_eq!(
+      count(TimeWindow::Hour).await.unwrap(),
+      vec![
+        (at("2024-03-03T23:00:00Z"), 1),
+        (at("2024-03-04T00:00:00Z"), 2),
+        (at("2024-03-04T01:00:00Z"), 1),
+        (at("2024-03-10T12:00:00Z"), 1),
+      ]
+    );
+    assert_eq!(
+      count(TimeWindow::Day).await.unwrap(),
+      vec![
+        (at("2024-03-03T00:00:00Z"), 1),
+        (at("2024-03-04T00:00:00Z"), 3),
+        (at("2024-03-10T00:00:00Z"), 1),
+      ]
+    );
+    assert_eq!(
+      count(TimeWindow::Week).await.unwrap(),
+      vec![
+        (at("2024-02-26T00:00:00Z"), 1),
+        (at("2024-03-04T00:00:00Z"), 4),
+      ]
+    );
+
+    let err = db
+      .count_by_window(
+        "events; DROP TABLE events",
+        "created_at",
+        TimeWindow::Day,
+        range,
+      )
+      .await
+      .unwrap_err();
+    assert!(err.to_string().starts_with("INVALID_IDENTIFIER"));
+  }
+
+  #[test]
+  fn connections_retire_before_max_lifetime() {
+    let config = DBSqliteConfig::default();
+    assert_eq!(config.retire_after(), Duration::from_secs(3540));
+
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::with_config(file.path().to_str().unwrap(), &config).unwrap();
+    assert_eq!(db.pool.max_lifetime(), Some(Duration::from_secs(3540)));
+
+    let too_large = DBSqliteConfig {
+      lifetime_margin: Duration::from_secs(7200),
+      ..config
+    };
+    assert_eq!(too_large.retire_after(), Duration::from_secs(1));
+  }
+
+  #[tokio::test]
+  async fn execute_with_retry_retries_only_busy_errors() {
+    use diesel::result::{DatabaseErrorKind, Error as DieselError};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let file = NamedTempFile::new().unwrap();
+    let db = DBSqlite::new(file.path().to_str().unwrap()).unwrap();
+
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counter = attempts.clone();
+    let value = db
+      .execute_with_retry(move |_| {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+          let info = Box::new("database is locked".to_string());
+          return Err(DieselError::DatabaseError(DatabaseErrorKind::Unknown, info).into());
+        }
+        Ok(7)
+      })
+      .await
+      .unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counter = attempts.clone();
+    let err = db
+      .execute_with_retry(move |_| -> Result<()> {
+        counter.fetch_add(1, Ordering::SeqCst);
+        anyhow::bail!("NOT_TRANSIENT")
+      })
+      .await
+      .unwrap_err();
+    assert_eq!(err.to_string(), "NOT_TRANSIENT");
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+  }
 }
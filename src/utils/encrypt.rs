@@ -51,3 +51,13 @@ pub fn verify(
     .is_ok();
   Ok(password_matches)
 }
+
+/// Compares without short-circuiting on the first differing byte, so the time
+/// taken does not reveal how much of a secret token was guessed correctly.
+/// Only the length leaks.
+pub fn constant_time_eq(
+  a: &[u8],
+  b: &[u8],
+) -> bool {
+  a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,173 @@
+//! Upload extension allowlist, the `[file_types]` section of
+//! `config/constant.toml`.
+//!
+//! The lists arrive with [`RuntimeConfig`] and live behind an [`ArcSwap`]:
+//! [`validate_file`] reads the current snapshot without locking, and
+//! [`spawn_follower`] swaps in a new one atomically whenever the config
+//! changes, so in-flight uploads finish against the list they started with.
+//! Ops can edit the file and run `kill -HUP <pid>` instead of redeploying.
+//!
+//! ```toml
+//! [file_types]
+//! image = ["jpg", "jpeg", "png", "webp"]
+//! video = ["mp4"]
+//! document = ["pdf", "txt"]
+//! ```
+//!
+//! A missing file or section falls back to the compiled-in
+//! [`IMAGE_TYPES_SUPPORT`], [`VIDEO_TYPES_SUPPORT`] and
+//! [`DOCUMENT_TYPES_SUPPORT`]; a missing key falls back per list. A file that
+//! fails to parse is rejected and the previous lists stay active.
+//!
+//! [`RuntimeConfig`]: crate::utils::runtime_config::RuntimeConfig
+
+use crate::constants::{DOCUMENT_TYPES_SUPPORT, IMAGE_TYPES_SUPPORT, VIDEO_TYPES_SUPPORT};
+use crate::services::HttpError;
+use crate::utils::{
+  runtime_config::{self, RuntimeConfig},
+  tasks::BackgroundTasks,
+};
+use arc_swap::ArcSwap;
+use serde::Deserialize;
+use std::sync::{Arc, LazyLock};
+use tokio::sync::watch;
+
+static CURRENT: LazyLock<ArcSwap<AllowedFileTypes>> =
+  LazyLock::new(|| ArcSwap::from_pointee(AllowedFileTypes::default()));
+
+/// File extensions (lowercase, without the dot) accepted for upload.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AllowedFileTypes {
+  pub image: Vec<String>,
+  pub video: Vec<String>,
+  pub document: Vec<String>,
+}
+
+impl Default for AllowedFileTypes {
+  fn default() -> Self {
+    let owned = |types: &[&str]| types.iter().map(|t| t.to_string()).collect();
+    Self {
+      image: owned(&IMAGE_TYPES_SUPPORT),
+      video: owned(&VIDEO_TYPES_SUPPORT),
+      document: owned(&DOCUMENT_TYPES_SUPPORT),
+    }
+  }
+}
+
+impl AllowedFileTypes {
+  /// Whether `filename`'s extension (case-insensitive) is in any list.
+  pub fn allows(
+    &self,
+    filename: &str,
+  ) -> bool {
+    let Some((_, extension)) = filename.rsplit_once('.') else {
+      return false;
+    };
+    let extension = extension.to_ascii_lowercase();
+    self.all().any(|allowed| *allowed == extension)
+  }
+
+  fn all(&self) -> impl Iterator<Item = &String> {
+    self.image.iter().chain(&self.video).chain(&self.document)
+  }
+
+  /// Lowercases every extension and strips a leading dot.
+  pub(crate) fn normalized(mut self) -> Self {
+    for list in [&mut self.image, &mut self.video, &mut self.document] {
+      for extension in list.iter_mut() {
+        *extension = extension.trim_start_matches('.').to_ascii_lowercase();
+      }
+    }
+    self
+  }
+}
+
+/// Snapshot of the active allowlist.
+pub fn current() -> Arc<AllowedFileTypes> {
+  CURRENT.load_full()
+}
+
+/// Makes `types` the active allowlist.
+pub fn store(types: &AllowedFileTypes) {
+  CURRENT.store(Arc::new(types.clone()));
+}
+
+/// Rejects `filename` with [`HttpError::ERR026`] unless its extension is in
+/// the active allowlist.
+pub fn validate_file(filename: &str) -> Result<(), HttpError> {
+  let types = CURRENT.load();
+  if !types.allows(filename) {
+    let allowed: Vec<&str> = types.all().map(String::as_str).collect();
+    return Err(HttpError::ERR026(format!("allowed={}", allowed.join(", "))));
+  }
+  Ok(())
+}
+
+/// Starts the `file_types_follower` task, which keeps the active allowlist
+/// in step with `[file_types]` in the published [`RuntimeConfig`].
+pub fn spawn_follower(
+  tasks: &mut BackgroundTasks,
+  receiver: watch::Receiver<RuntimeConfig>,
+) {
+  runtime_config::spawn_follower(tasks, "file_types_follower", receiver, |config| {
+    if **CURRENT.load() != config.file_types {
+      store(&config.file_types);
+    }
+    Ok(())
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+  use tempfile::NamedTempFile;
+
+  #[test]
+  fn defaults_match_compiled_lists() {
+    let types = AllowedFileTypes::default();
+    assert!(types.allows("photo.JPG"));
+    assert!(types.allows("clip.mp4"));
+    assert!(!types.allows("script.exe"));
+    assert!(!types.allows("no-extension"));
+  }
+
+  async fn wait_for(check: impl Fn() -> bool) {
+    for _ in 0..200 {
+      if check() {
+        return;
+      }
+      tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    panic!("allowlist did not follow the runtime config");
+  }
+
+  #[tokio::test]
+  async fn reload_changes_which_extensions_pass() {
+    let (sender, receiver) = watch::channel(RuntimeConfig::default());
+    let mut tasks = BackgroundTasks::new();
+    spawn_follower(&mut tasks, receiver);
+
+    assert!(validate_file("report.pdf").is_ok());
+    assert!(validate_file("photo.webp").is_err());
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+      file,
+      "[file_types]\nimage = [\"webp\"]\nvideo = []\ndocument = []"
+    )
+    .unwrap();
+    runtime_config::reload(file.path(), &sender).unwrap();
+    wait_for(|| validate_file("photo.webp").is_ok()).await;
+    assert!(validate_file("report.pdf").is_err());
+
+    // A broken file keeps the last good list.
+    std::fs::write(file.path(), "[file_types\n").unwrap();
+    assert!(runtime_config::reload(file.path(), &sender).is_err());
+    assert!(validate_file("photo.webp").is_ok());
+
+    tasks.shutdown().await;
+    store(&AllowedFileTypes::default());
+  }
+}
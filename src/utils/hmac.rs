@@ -0,0 +1,100 @@
+use super::encrypt::constant_time_eq;
+use sha2::{Digest, Sha256};
+
+/// SHA-256 block size in bytes.
+const BLOCK_SIZE: usize = 64;
+
+/// HMAC-SHA256 (RFC 2104) of `payload` under `secret`.
+pub fn sign(
+  secret: &[u8],
+  payload: &[u8],
+) -> [u8; 32] {
+  let mut key = [0u8; BLOCK_SIZE];
+  if secret.len() > BLOCK_SIZE {
+    key[..32].copy_from_slice(&Sha256::digest(secret));
+  } else {
+    key[..secret.len()].copy_from_slice(secret);
+  }
+  let pad = |byte: u8| key.map(|k| k ^ byte);
+
+  let inner = Sha256::new()
+    .chain_update(pad(0x36))
+    .chain_update(payload)
+    .finalize();
+  Sha256::new()
+    .chain_update(pad(0x5c))
+    .chain_update(inner)
+    .finalize()
+    .into()
+}
+
+/// Checks a hex HMAC-SHA256 `signature` of `payload`, as sent in webhook
+/// signature headers. A `sha256=` prefix (GitHub style) is accepted and hex
+/// digits may be either case. The comparison is constant-time; malformed
+/// signatures are simply not equal.
+pub fn verify(
+  secret: &[u8],
+  payload: &[u8],
+  signature: &str,
+) -> bool {
+  let signature = signature.trim();
+  let hex = signature.strip_prefix("sha256=").unwrap_or(signature);
+  match decode_hex(hex) {
+    Some(provided) => constant_time_eq(&provided, &sign(secret, payload)),
+    None => false,
+  }
+}
+
+/// Lowercase hex of `bytes`, e.g. for sending a signature.
+pub fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+  if !hex.len().is_multiple_of(2) {
+    return None;
+  }
+  (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // RFC 4231, test case 2.
+  const SECRET: &[u8] = b"Jefe";
+  const PAYLOAD: &[u8] = b"what do ya want for nothing?";
+  const SIGNATURE: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+  #[test]
+  fn verifies_known_signature() {
+    assert_eq!(to_hex(&sign(SECRET, PAYLOAD)), SIGNATURE);
+    assert!(verify(SECRET, PAYLOAD, SIGNATURE));
+    assert!(verify(
+      SECRET,
+      PAYLOAD,
+      &format!("sha256={}", SIGNATURE.to_uppercase())
+    ));
+
+    assert!(!verify(b"jefe", PAYLOAD, SIGNATURE));
+    assert!(!verify(SECRET, b"what do ya want for nothing!", SIGNATURE));
+    assert!(!verify(SECRET, PAYLOAD, &SIGNATURE[..62]));
+    assert!(!verify(SECRET, PAYLOAD, "not hex"));
+  }
+
+  #[test]
+  fn hashes_keys_longer_than_a_block() {
+    // RFC 4231, test case 6: 131-byte key.
+    let secret = [0xaa; 131];
+    assert_eq!(
+      to_hex(&sign(
+        &secret,
+        b"Test Using Larger Than Block-Size Key - Hash Key First"
+      )),
+      "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+  }
+}
@@ -0,0 +1,311 @@
+use crate::{
+  models::Environment,
+  utils::{
+    Secret,
+    retry::{self, RetryBudget},
+  },
+};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use reqwest::{NoProxy, Proxy, RequestBuilder, Response, StatusCode, header::RETRY_AFTER};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Retry, timeout and proxy settings for [`HttpClient`].
+///
+/// Outbound clients should start from [`HttpClientConfig::from_env`] so
+/// egress timeouts and proxying are configured in one place.
+///
+/// # Proxies
+///
+/// Without [`proxy`](Self::proxy), reqwest's own detection applies:
+/// `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` (or their lower-case
+/// forms) are read from the process environment. Setting `proxy` replaces that
+/// detection: `https://` calls go through it, except for hosts in
+/// [`no_proxy`](Self::no_proxy), and plain `http://` calls go direct.
+#[derive(Clone, Debug)]
+pub struct HttpClientConfig {
+  /// Retries after the first attempt (default: 3, so up to 4 attempts).
+  pub max_retries: u32,
+  /// Timeout for a single attempt, including reading the response body (default: 10s).
+  pub attempt_timeout: Duration,
+  /// TCP/TLS connect timeout (default: 5s).
+  pub connect_timeout: Duration,
+  /// First backoff when the server gives no `Retry-After`; doubles per retry (default: 200ms).
+  pub base_backoff: Duration,
+  /// Upper bound for any single wait, including server-sent `Retry-After` (default: 30s).
+  pub max_backoff: Duration,
+  /// Idle connections kept per host for reuse (default: 16).
+  pub pool_max_idle_per_host: usize,
+  /// Proxy URL for `https://` calls (default: none, see above).
+  pub proxy: Option<Secret<String>>,
+  /// Comma-separated hosts, domains (`.internal`) and CIDRs that bypass
+  /// `proxy`, in `NO_PROXY` syntax (default: none).
+  pub no_proxy: Option<String>,
+}
+
+impl Default for HttpClientConfig {
+  fn default() -> Self {
+    Self {
+      max_retries: 3,
+      attempt_timeout: Duration::from_secs(10),
+      connect_timeout: Duration::from_secs(5),
+      base_backoff: Duration::from_millis(200),
+      max_backoff: Duration::from_secs(30),
+      pool_max_idle_per_host: 16,
+      proxy: None,
+      no_proxy: None,
+    }
+  }
+}
+
+impl HttpClientConfig {
+  /// Defaults with the timeouts and proxy from `HTTP_CONNECT_TIMEOUT`,
+  /// `HTTP_REQUEST_TIMEOUT`, `HTTPS_PROXY` and `NO_PROXY`.
+  pub fn from_env(env: &Environment) -> Self {
+    Self {
+      attempt_timeout: Duration::from_secs(env.http_request_timeout_secs),
+      connect_timeout: Duration::from_secs(env.http_connect_timeout_secs),
+      proxy: env.https_proxy.clone(),
+      no_proxy: env.no_proxy.clone(),
+      ..Self::default()
+    }
+  }
+}
+
+/// Pooled `reqwest` client for outbound calls that retries transient failures.
+///
+/// Retries on connect errors, timeouts, `429`, and `5xx` responses. On `429`
+/// and `503` the server's `Retry-After` (seconds or HTTP date) is honored,
+/// capped at [`HttpClientConfig::max_backoff`]; otherwise backoff is
+/// exponential from [`HttpClientConfig::base_backoff`]. Cloning is cheap and
+/// shares the connection pool.
+///
+/// When retries run out on a retryable status, the last response is returned
+/// so the caller can decide how to treat it. Retries also stop early when the
+/// [`RetryBudget`] is exhausted (the process-wide one unless
+/// [`HttpClient::with_retry_budget`] sets another), so an outage does not
+/// multiply outbound load by `1 + max_retries`.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+  client: reqwest::Client,
+  config: HttpClientConfig,
+  budget: RetryBudget,
+}
+
+impl HttpClient {
+  pub fn new(config: HttpClientConfig) -> Result<Self> {
+    let mut builder = reqwest::Client::builder()
+      .connect_timeout(config.connect_timeout)
+      .pool_max_idle_per_host(config.pool_max_idle_per_host)
+      .pool_idle_timeout(Duration::from_secs(90));
+    if let Some(url) = &config.proxy {
+      // Not `{e}`: the error would echo the URL and its credentials
+      let proxy = Proxy::https(url.expose_secret().as_str())
+        .map_err(|_| anyhow!("HTTP_PROXY_INVALID"))?
+        .no_proxy(config.no_proxy.as_deref().and_then(NoProxy::from_string));
+      builder = builder.proxy(proxy);
+    }
+    let client = builder
+      .build()
+      .map_err(|e| anyhow!("HTTP_CLIENT_BUILD_FAILED: {}", e))?;
+    Ok(Self {
+      client,
+      config,
+      budget: RetryBudget::shared(),
+    })
+  }
+
+  /// Uses `budget` instead of [`RetryBudget::shared`].
+  pub fn with_retry_budget(
+    mut self,
+    budget: RetryBudget,
+  ) -> Self {
+    self.budget = budget;
+    self
+  }
+
+  /// Underlying client, for building requests passed to [`HttpClient::send`].
+  pub fn inner(&self) -> &reqwest::Client {
+    &self.client
+  }
+
+  /// POSTs `body` as JSON to `url` with retries.
+  pub async fn post_json<T: Serialize + ?Sized>(
+    &self,
+    url: &str,
+    body: &T,
+  ) -> Result<Response> {
+    self.send(self.client.post(url).json(body)).await
+  }
+
+  /// Sends `request`, retrying per the client's config.
+  ///
+  /// Requests with a streaming body cannot be cloned and are sent once.
+  pub async fn send(
+    &self,
+    request: RequestBuilder,
+  ) -> Result<Response> {
+    self.budget.deposit();
+    let mut attempt = 0;
+    loop {
+      let Some(current) = request.try_clone() else {
+        return request
+          .timeout(self.config.attempt_timeout)
+          .send()
+          .await
+          .map_err(|e| anyhow!("HTTP_REQUEST_FAILED: {}", e));
+      };
+
+      let result = current.timeout(self.config.attempt_timeout).send().await;
+      // Only consult the budget when a retry would actually happen.
+      let retries_left = attempt < self.config.max_retries
+        && match &result {
+          Ok(response) => is_retryable(response.status()),
+          Err(e) => e.is_timeout() || e.is_connect(),
+        }
+        && self.budget.try_withdraw();
+
+      let wait = match result {
+        Ok(response) if !is_retryable(response.status()) || !retries_left => return Ok(response),
+        Ok(response) => {
+          let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|_| {
+              matches!(
+                response.status(),
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+              )
+            })
+            .and_then(|v| parse_retry_after(v, Utc::now()));
+          tracing::warn!(
+            status = response.status().as_u16(),
+            attempt,
+            "HTTP_REQUEST_RETRY"
+          );
+          retry_after.unwrap_or_else(|| self.backoff(attempt))
+        }
+        Err(e) if (e.is_timeout() || e.is_connect()) && retries_left => {
+          tracing::warn!(error = %e, attempt, "HTTP_REQUEST_RETRY");
+          self.backoff(attempt)
+        }
+        Err(e) => return Err(anyhow!("HTTP_REQUEST_FAILED: {}", e)),
+      };
+
+      tokio::time::sleep(wait.min(self.config.max_backoff)).await;
+      attempt += 1;
+    }
+  }
+
+  fn backoff(
+    &self,
+    attempt: u32,
+  ) -> Duration {
+    retry::backoff(self.config.base_backoff, attempt)
+  }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+  status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses a `Retry-After` value, either delay-seconds (`"120"`) or an HTTP
+/// date (`"Wed, 21 Oct 2015 07:28:00 GMT"`), into a wait relative to `now`.
+///
+/// Dates in the past yield `Duration::ZERO`; unparseable values yield `None`.
+pub fn parse_retry_after(
+  value: &str,
+  now: DateTime<Utc>,
+) -> Option<Duration> {
+  let value = value.trim();
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+  let at = DateTime::parse_from_rfc2822(value).ok()?;
+  Some(
+    (at.with_timezone(&Utc) - now)
+      .to_std()
+      .unwrap_or(Duration::ZERO),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{Router, http::HeaderMap, routing::post};
+  use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  };
+
+  #[test]
+  fn parses_seconds_and_http_dates() {
+    let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+      .unwrap()
+      .with_timezone(&Utc);
+    assert_eq!(
+      parse_retry_after("120", now),
+      Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+      parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+      Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+      parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+      Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+  }
+
+  #[test]
+  fn invalid_proxy_is_rejected_without_echoing_it() {
+    let config = HttpClientConfig {
+      proxy: Some(Secret::new("http://user:hunter2@[bad".to_string())),
+      ..HttpClientConfig::default()
+    };
+    let err = HttpClient::new(config).unwrap_err().to_string();
+    assert_eq!(err, "HTTP_PROXY_INVALID");
+
+    let config = HttpClientConfig {
+      proxy: Some(Secret::new("http://127.0.0.1:3128".to_string())),
+      no_proxy: Some("localhost,.internal".to_string()),
+      ..HttpClientConfig::default()
+    };
+    assert!(HttpClient::new(config).is_ok());
+  }
+
+  #[tokio::test]
+  async fn retries_after_429_then_succeeds() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+      "/hook",
+      post(move || {
+        let counter = counter.clone();
+        async move {
+          let mut headers = HeaderMap::new();
+          if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            headers.insert(RETRY_AFTER, "0".parse().unwrap());
+            return (StatusCode::TOO_MANY_REQUESTS, headers);
+          }
+          (StatusCode::OK, headers)
+        }
+      }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = HttpClient::new(HttpClientConfig::default()).unwrap();
+    let response = client
+      .post_json(&format!("http://{addr}/hook"), &serde_json::json!({}))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+  }
+}
@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+/// Resident set size of this process in bytes, or `None` where it cannot be
+/// measured.
+///
+/// Best-effort and platform-specific: on Linux it is read from the `VmRSS`
+/// line of `/proc/self/status`; every other platform returns `None`. RSS
+/// counts pages the allocator has not yet returned to the OS, so it lags
+/// behind frees and can stay high after the cache shrinks.
+pub fn resident_bytes() -> Option<u64> {
+  #[cfg(target_os = "linux")]
+  {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_rss(&status)
+  }
+  #[cfg(not(target_os = "linux"))]
+  {
+    None
+  }
+}
+
+/// Decides when the cache memory monitor shrinks the cache.
+///
+/// RSS lags behind frees (see [`resident_bytes`]), so right after an eviction
+/// it usually still reads over the limit. The gate therefore evicts once,
+/// then ignores the reading for `cooldown` instead of draining the cache again
+/// on every check.
+#[derive(Debug)]
+pub struct PressureGate {
+  cooldown: Duration,
+  last_eviction: Option<Instant>,
+}
+
+impl PressureGate {
+  pub fn new(cooldown: Duration) -> Self {
+    Self {
+      cooldown,
+      last_eviction: None,
+    }
+  }
+
+  /// Whether to evict for `rss` against `limit` at `now`; a `true` starts the
+  /// cooldown.
+  pub fn should_evict(
+    &mut self,
+    rss: u64,
+    limit: u64,
+    now: Instant,
+  ) -> bool {
+    let cooling_down = self
+      .last_eviction
+      .is_some_and(|at| now.duration_since(at) < self.cooldown);
+    if rss <= limit || cooling_down {
+      return false;
+    }
+    self.last_eviction = Some(now);
+    true
+  }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+  let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+  let kib = line
+    .trim_start_matches("VmRSS:")
+    .trim()
+    .trim_end_matches("kB")
+    .trim()
+    .parse::<u64>()
+    .ok()?;
+  Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_vm_rss_line() {
+    let status = "Name:\taxum-starter\nVmPeak:\t  20000 kB\nVmRSS:\t    5120 kB\nThreads:\t4\n";
+    assert_eq!(parse_vm_rss(status), Some(5 * 1024 * 1024));
+    assert_eq!(parse_vm_rss("Name:\tx\n"), None);
+  }
+
+  #[test]
+  fn pressure_gate_waits_out_the_cooldown_after_evicting() {
+    let mut gate = PressureGate::new(Duration::from_secs(300));
+    let start = Instant::now();
+    assert!(!gate.should_evict(90, 100, start));
+    assert!(gate.should_evict(120, 100, start));
+    // RSS has not caught up with the eviction yet
+    assert!(!gate.should_evict(120, 100, start + Duration::from_secs(15)));
+    assert!(!gate.should_evict(120, 100, start + Duration::from_secs(299)));
+    assert!(gate.should_evict(120, 100, start + Duration::from_secs(300)));
+  }
+}
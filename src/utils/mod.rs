@@ -1,13 +1,30 @@
+pub mod clock;
 pub mod encrypt;
+pub mod export;
+pub mod file_types;
 pub mod files;
 pub mod generator;
+pub mod hmac;
+pub mod http_client;
 pub mod integer;
+pub mod memory;
+pub mod request_id;
+pub mod response;
+pub mod retry;
+pub mod runtime;
+pub mod runtime_config;
+pub mod scheduler;
+pub mod secret;
 pub mod string;
+pub mod tasks;
 pub mod token;
+pub mod upload;
 pub mod validation;
 
 // Re-export barrel pattern
+pub use clock::{Clock, MockClock, SharedClock, SystemClock};
 pub use encrypt::{hash as hash_password, verify as verify_password};
 pub use generator::id as generate_id;
 pub use integer::{to_i64, to_u32};
+pub use secret::Secret;
 pub use token::{create_token, decode_token};
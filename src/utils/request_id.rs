@@ -0,0 +1,94 @@
+//! Request ID of the task being handled, available without threading it
+//! through every call.
+//!
+//! The [`request_id`](crate::middlewares::request_id) layer scopes the
+//! `x-request-id` of each request with [`with_request_id`]; code further down
+//! (notably `DBSqlite::execute`/`transaction`) reads it with
+//! [`RequestId::current`]. Background jobs that act on behalf of a request can
+//! scope the ID themselves.
+//!
+//! Postgres carries the ID into the session's `application_name`; SQLite has
+//! no session label, so there it only reaches the `DB` span in logs.
+
+use std::future::Future;
+
+/// Longest request ID that is propagated; longer values are dropped.
+pub const MAX_REQUEST_ID_LEN: usize = 64;
+
+tokio::task_local! {
+  static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// Validated request ID: 1 to [`MAX_REQUEST_ID_LEN`] ASCII letters, digits,
+/// `-`, `_` or `.`.
+///
+/// `x-request-id` may be supplied by the client, so the ID is untrusted input.
+/// Restricting it to this charset means it can be written to logs and
+/// database session settings without quoting concerns; database code still
+/// passes it as a bound parameter rather than splicing it into SQL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+  /// Returns `None` when `value` is empty, too long or has other characters.
+  pub fn parse(value: &str) -> Option<Self> {
+    let valid = !value.is_empty()
+      && value.len() <= MAX_REQUEST_ID_LEN
+      && value
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    valid.then(|| Self(value.to_string()))
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+
+  /// Request ID scoped on the current task, if any.
+  pub fn current() -> Option<Self> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+  }
+}
+
+impl std::fmt::Display for RequestId {
+  fn fmt(
+    &self,
+    f: &mut std::fmt::Formatter<'_>,
+  ) -> std::fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+/// Runs `fut` with `id` as the task's current request ID.
+///
+/// ```rust,ignore
+/// with_request_id(id, async move { db.execute(|conn| ...).await }).await
+/// ```
+pub async fn with_request_id<F: Future>(
+  id: RequestId,
+  fut: F,
+) -> F::Output {
+  CURRENT_REQUEST_ID.scope(id, fut).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn accepts_uuids_and_rejects_unsafe_values() {
+    assert!(RequestId::parse("3f2b9c1e-8a4d-4c1f-9b7e-2d5a6c8e0f1a").is_some());
+    assert!(RequestId::parse("").is_none());
+    assert!(RequestId::parse("abc'; DROP TABLE users; --").is_none());
+    assert!(RequestId::parse("abc */ SELECT 1 /*").is_none());
+    assert!(RequestId::parse(&"a".repeat(MAX_REQUEST_ID_LEN + 1)).is_none());
+  }
+
+  #[tokio::test]
+  async fn current_is_scoped_to_the_future() {
+    let id = RequestId::parse("req-1").unwrap();
+    let seen = with_request_id(id.clone(), async { RequestId::current() }).await;
+    assert_eq!(seen, Some(id));
+    assert_eq!(RequestId::current(), None);
+  }
+}
@@ -0,0 +1,103 @@
+//! Buffered versus streamed response bodies.
+//!
+//! Responses are buffered by default: `Json`, [`HttpResponse`] and friends
+//! build the whole body first, so hyper knows its size and sends
+//! `Content-Length`. That is the right choice for small JSON, and clients can
+//! show progress or detect truncation.
+//!
+//! Endpoints whose body is large or produced incrementally (exports, reports)
+//! opt into streaming with [`stream`]. The body then has no known size, so
+//! hyper sends it with `Transfer-Encoding: chunked` on HTTP/1.1 and as plain
+//! `DATA` frames on HTTP/2. Never set `Content-Length` or `Transfer-Encoding`
+//! by hand on these responses; `stream` removes a `Content-Length` if one
+//! slips in.
+//!
+//! How the middleware stack treats a streamed body:
+//!
+//! - The request `TIMEOUT` only runs until the response headers are sent; the
+//!   body can keep streaming after that.
+//! - The trace layer logs `ON_RESPONSE` when the headers go out, so its
+//!   `latency_ms` is time to first byte; `ON_RESPONSE_END` (debug) carries
+//!   the total duration once the last chunk is sent.
+//! - No compression layer is installed. tower-http's `CompressionLayer`
+//!   compresses chunk by chunk and drops `Content-Length` itself, so adding
+//!   one needs no change here.
+//! - Errors after the headers cannot become an error response; the connection
+//!   is aborted instead (see [`csv_response`](super::export::csv_response)).
+//!
+//! [`HttpResponse`]: crate::services::HttpResponse
+
+use axum::{
+  BoxError,
+  body::{Body, Bytes},
+  http::{HeaderValue, header},
+  response::Response,
+};
+use futures_core::TryStream;
+
+/// Response that sends `body` chunk by chunk as it is produced; see the
+/// module docs for when to use it. Add headers such as `Content-Type` to the
+/// returned response.
+///
+/// Also sets `X-Accel-Buffering: no`, so an nginx in front forwards chunks as
+/// they arrive instead of collecting the whole body.
+///
+/// ```rust,ignore
+/// async fn export(State(state): State<Arc<AppState>>) -> Response {
+///   let mut response = response::stream(report_chunks(&state));
+///   response
+///     .headers_mut()
+///     .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+///   response
+/// }
+/// ```
+pub fn stream<S>(body: S) -> Response
+where
+  S: TryStream + Send + 'static,
+  S::Ok: Into<Bytes>,
+  S::Error: Into<BoxError>,
+{
+  let mut response = Response::new(Body::from_stream(body));
+  let headers = response.headers_mut();
+  headers.remove(header::CONTENT_LENGTH);
+  headers.insert("x-accel-buffering", HeaderValue::from_static("no"));
+  response
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::body::HttpBody;
+  use std::{
+    pin::Pin,
+    task::{Context, Poll},
+  };
+
+  struct Chunks(Vec<&'static str>);
+
+  impl futures_core::Stream for Chunks {
+    type Item = Result<Bytes, std::io::Error>;
+
+    fn poll_next(
+      mut self: Pin<&mut Self>,
+      _cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+      let next = (!self.0.is_empty()).then(|| Ok(Bytes::from_static(self.0.remove(0).as_bytes())));
+      Poll::Ready(next)
+    }
+  }
+
+  #[tokio::test]
+  async fn streamed_body_has_no_known_length() {
+    let response = stream(Chunks(vec!["a,b\n", "1,2\n"]));
+    assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+    assert_eq!(response.headers()["x-accel-buffering"], "no");
+    assert_eq!(response.body().size_hint().exact(), None);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    assert_eq!(&body[..], b"a,b\n1,2\n");
+  }
+}
@@ -0,0 +1,174 @@
+//! Process-wide retry budget shared by every retry loop.
+//!
+//! Each retry path (outbound HTTP in [`HttpClient`](super::http_client::HttpClient),
+//! and through it the webhook sink; `DBSqlite::execute_with_retry`) retries a
+//! failed call a few times on its own. During an outage every caller does so
+//! at once and the retries multiply the load on the failing dependency. A
+//! [`RetryBudget`] caps retries globally instead:
+//!
+//! - every first attempt deposits [`RetryBudgetConfig::retry_ratio`] tokens;
+//! - every retry withdraws one token, and is skipped when none is left;
+//! - [`RetryBudgetConfig::min_retries_per_sec`] tokens are added per second
+//!   regardless of traffic, so low-volume callers can still retry;
+//! - the balance is capped at [`RetryBudgetConfig::max_tokens`], so a quiet
+//!   period cannot bank an unlimited burst.
+//!
+//! Over any stretch of time, retries are therefore bounded by
+//! `retry_ratio × first attempts + min_retries_per_sec × seconds (+ max_tokens)`.
+//! With the default ratio of `0.2` a dependency sees at most ~1.2× its normal
+//! request rate however many callers are retrying, instead of the `1 +
+//! max_retries` (4×) each caller's own limit would allow.
+
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+static SHARED: LazyLock<RetryBudget> = LazyLock::new(|| RetryBudget::new(Default::default()));
+
+/// Settings for [`RetryBudget`].
+#[derive(Clone, Debug)]
+pub struct RetryBudgetConfig {
+  /// Tokens earned per first attempt; the long-run retry-to-request ratio (default: 0.2).
+  pub retry_ratio: f64,
+  /// Tokens added per second independent of traffic (default: 10).
+  pub min_retries_per_sec: u32,
+  /// Most tokens that can be banked (default: 100).
+  pub max_tokens: u32,
+}
+
+impl Default for RetryBudgetConfig {
+  fn default() -> Self {
+    Self {
+      retry_ratio: 0.2,
+      min_retries_per_sec: 10,
+      max_tokens: 100,
+    }
+  }
+}
+
+#[derive(Debug)]
+struct Bucket {
+  tokens: f64,
+  refilled_at: Instant,
+}
+
+/// Token bucket limiting retries across callers; see the module docs.
+///
+/// Cloning is cheap and shares the balance.
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+  config: RetryBudgetConfig,
+  bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RetryBudget {
+  /// A budget that starts full.
+  pub fn new(config: RetryBudgetConfig) -> Self {
+    let bucket = Bucket {
+      tokens: f64::from(config.max_tokens),
+      refilled_at: Instant::now(),
+    };
+    Self {
+      config,
+      bucket: Arc::new(Mutex::new(bucket)),
+    }
+  }
+
+  /// The process-wide budget used by retry paths unless given another one.
+  pub fn shared() -> Self {
+    SHARED.clone()
+  }
+
+  /// Records a first attempt.
+  pub fn deposit(&self) {
+    self.update(|bucket, config| {
+      bucket.tokens = (bucket.tokens + config.retry_ratio).min(f64::from(config.max_tokens));
+    });
+  }
+
+  /// Takes one token for a retry. `false` means the budget is exhausted and
+  /// the caller should give up instead of retrying.
+  pub fn try_withdraw(&self) -> bool {
+    let allowed = self.update(|bucket, _| {
+      let allowed = bucket.tokens >= 1.0;
+      if allowed {
+        bucket.tokens -= 1.0;
+      }
+      allowed
+    });
+    if !allowed {
+      tracing::warn!("RETRY_BUDGET_EXHAUSTED");
+    }
+    allowed
+  }
+
+  /// Current balance, for metrics and tests.
+  pub fn available(&self) -> f64 {
+    self.update(|bucket, _| bucket.tokens)
+  }
+
+  fn update<T>(
+    &self,
+    f: impl FnOnce(&mut Bucket, &RetryBudgetConfig) -> T,
+  ) -> T {
+    let mut bucket = self.bucket.lock().unwrap();
+    let now = Instant::now();
+    let elapsed = now.duration_since(bucket.refilled_at);
+    let refill = elapsed.as_secs_f64() * f64::from(self.config.min_retries_per_sec);
+    bucket.tokens = (bucket.tokens + refill).min(f64::from(self.config.max_tokens));
+    bucket.refilled_at = now;
+    f(&mut bucket, &self.config)
+  }
+}
+
+/// Exponential backoff for retry `attempt` (0-based): `base × 2^attempt`.
+pub fn backoff(
+  base: Duration,
+  attempt: u32,
+) -> Duration {
+  base.saturating_mul(2u32.saturating_pow(attempt))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn empty_budget() -> RetryBudget {
+    let budget = RetryBudget::new(RetryBudgetConfig {
+      retry_ratio: 0.5,
+      min_retries_per_sec: 1,
+      max_tokens: 3,
+    });
+    while budget.try_withdraw() {}
+    budget
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn retries_are_earned_by_first_attempts() {
+    let budget = empty_budget();
+    assert!(!budget.try_withdraw());
+
+    // Two first attempts at ratio 0.5 earn one retry.
+    budget.deposit();
+    assert!(!budget.try_withdraw());
+    budget.deposit();
+    assert!(budget.try_withdraw());
+    assert!(!budget.try_withdraw());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn refills_over_time_up_to_the_cap() {
+    let budget = empty_budget();
+
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert!(budget.try_withdraw());
+    assert!(!budget.try_withdraw());
+
+    tokio::time::advance(Duration::from_secs(60)).await;
+    assert_eq!(budget.available(), 3.0);
+    for _ in 0..10 {
+      budget.deposit();
+    }
+    assert_eq!(budget.available(), 3.0);
+  }
+}
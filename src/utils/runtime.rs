@@ -0,0 +1,89 @@
+//! Tokio runtime setup and panic diagnostics for `main`.
+//!
+//! Threads are named so log lines and panics say where they ran:
+//! [`WORKER_THREAD_NAME`]`-N` for the async workers and
+//! [`BLOCKING_THREAD_NAME`]`-N` for the blocking pool, which runs
+//! `DBSqlite` operations (and any other `spawn_blocking` work). Tokio has a
+//! single naming hook for both kinds, so the split relies on the runtime
+//! starting all of its workers before any blocking thread.
+
+use crate::constants::{BLOCKING_THREAD_NAME, WORKER_THREAD_NAME};
+use std::{
+  backtrace::Backtrace,
+  num::NonZero,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::runtime::Runtime;
+
+/// Multi-threaded runtime with one worker per CPU and named threads.
+pub fn build() -> std::io::Result<Runtime> {
+  let workers = std::thread::available_parallelism().map_or(1, NonZero::get);
+  tokio::runtime::Builder::new_multi_thread()
+    .worker_threads(workers)
+    .thread_name_fn(thread_namer(workers))
+    .enable_all()
+    .build()
+}
+
+/// Names the first `workers` threads as workers and every later one as a
+/// blocking thread.
+fn thread_namer(workers: usize) -> impl Fn() -> String + Send + Sync + 'static {
+  let spawned = AtomicUsize::new(0);
+  move || {
+    let n = spawned.fetch_add(1, Ordering::Relaxed);
+    if n < workers {
+      format!("{WORKER_THREAD_NAME}-{n}")
+    } else {
+      format!("{BLOCKING_THREAD_NAME}-{}", n - workers)
+    }
+  }
+}
+
+/// Logs every panic with `tracing::error!("PANIC")`, carrying the thread
+/// name, location and a backtrace, then hands it to the previously installed
+/// hook so the usual stderr report (and test harness output) still happens.
+///
+/// Install it before the runtime starts so panics in any task are covered.
+/// Until logging is initialised the event goes nowhere, but the chained
+/// default hook still prints the panic.
+pub fn install_panic_hook() {
+  let previous = std::panic::take_hook();
+  std::panic::set_hook(Box::new(move |info| {
+    let thread = std::thread::current();
+    let message = info
+      .payload()
+      .downcast_ref::<&str>()
+      .copied()
+      .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+      .unwrap_or("non-string panic payload");
+    tracing::error!(
+      thread = thread.name().unwrap_or("unnamed"),
+      location = info.location().map(ToString::to_string),
+      message,
+      backtrace = %Backtrace::force_capture(),
+      "PANIC"
+    );
+    previous(info);
+  }));
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn names_workers_first_then_blocking_threads() {
+    let name = thread_namer(2);
+    let names: Vec<String> = (0..4).map(|_| name()).collect();
+    assert_eq!(
+      names,
+      [
+        "axum-worker-0",
+        "axum-worker-1",
+        "db-blocking-0",
+        "db-blocking-1"
+      ]
+    );
+  }
+}
@@ -0,0 +1,261 @@
+//! Settings that can change while the process runs, loaded from
+//! `config/constant.toml` and published on a watch channel.
+//!
+//! [`AppState::runtime_config`] holds the [`watch::Sender`]; the
+//! `runtime_config_reloader` task re-reads the file on `SIGHUP` and sends the
+//! new [`RuntimeConfig`] only when it differs from the current one. A
+//! subsystem that should follow it subscribes with [`spawn_follower`] instead
+//! of polling:
+//!
+//! ```rust,ignore
+//! runtime_config::spawn_follower(
+//!   &mut tasks,
+//!   "log_level_follower",
+//!   app_state.runtime_config.subscribe(),
+//!   |config| config::set_log_filter(config.log_level.as_deref()),
+//! );
+//! ```
+//!
+//! ```toml
+//! [log]
+//! level = "info,axum_starter=debug"
+//!
+//! [rate_limit]
+//! requests = 600
+//! window_secs = 60
+//!
+//! [file_types]
+//! image = ["jpg", "jpeg", "png", "webp"]
+//! ```
+//!
+//! All sections are optional. A file that fails to parse, or whose log level
+//! is not a valid filter, is rejected and the previous settings stay active.
+//!
+//! [`AppState::runtime_config`]: crate::models::AppState::runtime_config
+
+use crate::utils::{file_types::AllowedFileTypes, tasks::BackgroundTasks};
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+/// The reloadable subset of the configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+  /// Log filter in `RUST_LOG` syntax (`[log] level`); `None` keeps the
+  /// filter the process started with.
+  pub log_level: Option<String>,
+  /// Server-wide budget answered with `429` (`[rate_limit]`); `None` leaves
+  /// clients unthrottled (see `middlewares::rate_limit`).
+  pub rate_limit: Option<RateLimitConfig>,
+  /// Upload extension allowlist (`[file_types]`); see `utils::file_types`.
+  pub file_types: AllowedFileTypes,
+}
+
+/// `requests` per `window_secs`, shared by every client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimitConfig {
+  pub requests: u32,
+  pub window_secs: u64,
+}
+
+impl RateLimitConfig {
+  pub fn window(&self) -> Duration {
+    Duration::from_secs(self.window_secs)
+  }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct LogSection {
+  level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ConstantFile {
+  #[serde(default)]
+  log: LogSection,
+  rate_limit: Option<RateLimitConfig>,
+  #[serde(default)]
+  file_types: AllowedFileTypes,
+}
+
+impl RuntimeConfig {
+  /// Parses the `[log]`, `[rate_limit]` and `[file_types]` sections of a
+  /// `constant.toml` document.
+  pub fn from_toml(source: &str) -> Result<Self> {
+    let file: ConstantFile =
+      toml::from_str(source).map_err(|e| anyhow!("RUNTIME_CONFIG_INVALID: {}", e))?;
+    if let Some(level) = &file.log.level {
+      EnvFilter::try_new(level).map_err(|e| anyhow!("RUNTIME_CONFIG_INVALID: log.level: {}", e))?;
+    }
+    let empty_limit = |limit: &RateLimitConfig| limit.requests == 0 || limit.window_secs == 0;
+    if file.rate_limit.as_ref().is_some_and(empty_limit) {
+      bail!("RUNTIME_CONFIG_INVALID: rate_limit.requests and window_secs must be positive");
+    }
+    Ok(Self {
+      log_level: file.log.level,
+      rate_limit: file.rate_limit,
+      file_types: file.file_types.normalized(),
+    })
+  }
+
+  /// Reads `path`; a missing file yields the defaults.
+  pub fn load(path: &Path) -> Result<Self> {
+    match std::fs::read_to_string(path) {
+      Ok(source) => Self::from_toml(&source),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+      Err(e) => bail!("RUNTIME_CONFIG_UNREADABLE: {}: {}", path.display(), e),
+    }
+  }
+}
+
+/// Loads `path` and publishes it on `sender` if it changed. On error the
+/// previous config stays in effect.
+pub fn reload(
+  path: &Path,
+  sender: &watch::Sender<RuntimeConfig>,
+) -> Result<()> {
+  let config = RuntimeConfig::load(path)?;
+  let changed = sender.send_if_modified(|current| {
+    if *current == config {
+      return false;
+    }
+    *current = config;
+    true
+  });
+  if changed {
+    let config = sender.borrow();
+    tracing::info!(
+      log_level = ?config.log_level,
+      rate_limit = ?config.rate_limit,
+      file_types = ?config.file_types,
+      "RUNTIME_CONFIG_RELOADED"
+    );
+  }
+  Ok(())
+}
+
+/// Starts the `runtime_config_reloader` task, reloading `path` on every `SIGHUP`.
+///
+/// No-op on non-unix targets, which have no `SIGHUP`.
+pub fn spawn_reloader(
+  tasks: &mut BackgroundTasks,
+  path: PathBuf,
+  sender: watch::Sender<RuntimeConfig>,
+) {
+  #[cfg(unix)]
+  tasks.spawn("runtime_config_reloader", move |mut shutdown| {
+    let path = path.clone();
+    let sender = sender.clone();
+    async move {
+      use tokio::signal::unix::{SignalKind, signal};
+      let mut hangup = signal(SignalKind::hangup()).expect("failed to install SIGHUP handler");
+      loop {
+        tokio::select! {
+          _ = hangup.recv() => {
+            if let Err(e) = reload(&path, &sender) {
+              tracing::error!(error = %e, "RUNTIME_CONFIG_RELOAD_FAILED");
+            }
+          }
+          _ = shutdown.changed() => break,
+        }
+      }
+    }
+  });
+  #[cfg(not(unix))]
+  let _ = (tasks, path, sender);
+}
+
+/// Starts task `name`, which calls `apply` with the current config and then
+/// again after every change published on `receiver`, until shutdown.
+///
+/// Changes sent in quick succession may be coalesced: `apply` always sees the
+/// latest config, not every intermediate one. An `Err` from `apply` is
+/// logged and the subsystem keeps its previous settings.
+pub fn spawn_follower<F>(
+  tasks: &mut BackgroundTasks,
+  name: &'static str,
+  receiver: watch::Receiver<RuntimeConfig>,
+  apply: F,
+) where
+  F: Fn(&RuntimeConfig) -> Result<()> + Clone + Send + Sync + 'static,
+{
+  tasks.spawn(name, move |mut shutdown| {
+    let mut receiver = receiver.clone();
+    let apply = apply.clone();
+    async move {
+      loop {
+        let config = receiver.borrow_and_update().clone();
+        if let Err(e) = apply(&config) {
+          tracing::error!(task = name, error = %e, "RUNTIME_CONFIG_APPLY_FAILED");
+        }
+        tokio::select! {
+          changed = receiver.changed() => {
+            // Sender dropped: nothing more will arrive
+            if changed.is_err() {
+              let _ = shutdown.changed().await;
+              break;
+            }
+          }
+          _ = shutdown.changed() => break,
+        }
+      }
+    }
+  });
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+  use tempfile::NamedTempFile;
+  use tokio::sync::mpsc;
+
+  #[test]
+  fn parses_sections_and_rejects_bad_filters() {
+    let config = RuntimeConfig::from_toml(
+      "[log]\nlevel = \"warn\"\n[rate_limit]\nrequests = 5\nwindow_secs = 1",
+    )
+    .unwrap();
+    assert_eq!(config.log_level.as_deref(), Some("warn"));
+    assert_eq!(config.rate_limit.unwrap().window(), Duration::from_secs(1));
+
+    let config = RuntimeConfig::from_toml("[file_types]\nimage = [\".WEBP\"]").unwrap();
+    assert_eq!(config.file_types.image, vec!["webp"]);
+    // Missing lists keep the compiled-in defaults
+    assert_eq!(config.file_types.video, AllowedFileTypes::default().video);
+    assert_eq!(
+      RuntimeConfig::from_toml("").unwrap(),
+      RuntimeConfig::default()
+    );
+    assert!(RuntimeConfig::from_toml("[log]\nlevel = \"=[\"").is_err());
+    assert!(RuntimeConfig::from_toml("[rate_limit]\nrequests = 0\nwindow_secs = 1").is_err());
+  }
+
+  #[tokio::test]
+  async fn reload_propagates_to_followers() {
+    let (sender, receiver) = watch::channel(RuntimeConfig::default());
+    let (seen_tx, mut seen) = mpsc::unbounded_channel();
+    let mut tasks = BackgroundTasks::new();
+    spawn_follower(&mut tasks, "test_follower", receiver, move |config| {
+      seen_tx.send(config.clone())?;
+      Ok(())
+    });
+    assert_eq!(seen.recv().await.unwrap(), RuntimeConfig::default());
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, "[rate_limit]\nrequests = 3\nwindow_secs = 10").unwrap();
+    reload(file.path(), &sender).unwrap();
+    let rate_limit = seen.recv().await.unwrap().rate_limit.unwrap();
+    assert_eq!((rate_limit.requests, rate_limit.window_secs), (3, 10));
+
+    // Unchanged file: followers are not woken
+    reload(file.path(), &sender).unwrap();
+    tasks.shutdown().await;
+    assert!(seen.recv().await.is_none());
+  }
+}
@@ -0,0 +1,156 @@
+use super::tasks::BackgroundTasks;
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+
+type JobFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type JobFn = Arc<dyn Fn() -> JobFuture + Send + Sync>;
+
+struct Job {
+  name: &'static str,
+  interval: Duration,
+  run: JobFn,
+}
+
+/// Runs periodic jobs (cache purge, rollups, cleanup) on fixed intervals.
+///
+/// Each job gets its own timer under [`BackgroundTasks::spawn`], so it is
+/// supervised and stopped with the other background tasks. The first run
+/// happens one interval after [`Scheduler::start`]. Runs never overlap: a
+/// tick that arrives while the previous run is still executing is skipped
+/// (`JOB_SKIPPED_OVERLAP`) rather than queued. A run that panics is logged
+/// (`JOB_PANICKED`) and the next tick runs the job again. On shutdown the
+/// timer stops and an in-flight run is awaited, not cancelled.
+///
+/// Only fixed intervals are supported; schedules tied to wall-clock times
+/// (cron) are out of scope.
+///
+/// ```rust,ignore
+/// let mut scheduler = Scheduler::new();
+/// let purge_cache = cache.clone();
+/// scheduler.add_job("cache_purge", Duration::from_secs(60), move || {
+///   let cache = purge_cache.clone();
+///   async move {
+///     let removed = cache.purge_expired().await;
+///     tracing::debug!(removed, "CACHE_PURGED");
+///   }
+/// });
+/// scheduler.start(&mut tasks);
+/// ```
+#[derive(Default)]
+pub struct Scheduler {
+  jobs: Vec<Job>,
+}
+
+impl Scheduler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers `job` to run every `interval` under `name` (used as the task
+  /// name in logs).
+  pub fn add_job<F, Fut>(
+    &mut self,
+    name: &'static str,
+    interval: Duration,
+    job: F,
+  ) -> &mut Self
+  where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+  {
+    self.jobs.push(Job {
+      name,
+      interval,
+      run: Arc::new(move || Box::pin(job()) as JobFuture),
+    });
+    self
+  }
+
+  /// Starts a timer per registered job on `tasks`.
+  pub fn start(
+    self,
+    tasks: &mut BackgroundTasks,
+  ) {
+    for job in self.jobs {
+      let Job {
+        name,
+        interval,
+        run,
+      } = job;
+      let running = Arc::new(AtomicBool::new(false));
+      tasks.spawn(name, move |mut shutdown| {
+        let (run, running) = (run.clone(), running.clone());
+        async move {
+          let mut ticker =
+            tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+          ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+          let mut current = None;
+          loop {
+            tokio::select! {
+              _ = ticker.tick() => {
+                if running.swap(true, Ordering::AcqRel) {
+                  tracing::warn!(job = name, "JOB_SKIPPED_OVERLAP");
+                  continue;
+                }
+                let (run, running) = (run.clone(), running.clone());
+                current = Some(tokio::spawn(async move {
+                  let result = tokio::spawn(run()).await;
+                  running.store(false, Ordering::Release);
+                  if let Err(e) = result {
+                    tracing::error!(job = name, error = %e, "JOB_PANICKED");
+                  }
+                }));
+              }
+              _ = shutdown.changed() => break,
+            }
+          }
+          if let Some(handle) = current {
+            let _ = handle.await;
+          }
+        }
+      });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::AtomicUsize;
+
+  #[tokio::test(start_paused = true)]
+  async fn skips_overlapping_runs_and_waits_for_the_last_one() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let finished = Arc::new(AtomicUsize::new(0));
+    let mut tasks = BackgroundTasks::new();
+    let mut scheduler = Scheduler::new();
+    let (s, f) = (started.clone(), finished.clone());
+    // Each run takes 25s, so with a 10s interval two of every three ticks
+    // land on a run that is still going.
+    scheduler.add_job("slow", Duration::from_secs(10), move || {
+      let (s, f) = (s.clone(), f.clone());
+      async move {
+        s.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(25)).await;
+        f.fetch_add(1, Ordering::SeqCst);
+      }
+    });
+    scheduler.start(&mut tasks);
+
+    // Ticks at 10s (run), 20s, 30s (skipped), 40s (run).
+    tokio::time::sleep(Duration::from_secs(45)).await;
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+    tasks.shutdown().await;
+    assert_eq!(finished.load(Ordering::SeqCst), 2);
+  }
+}
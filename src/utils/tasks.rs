@@ -0,0 +1,180 @@
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{sync::watch, task::JoinHandle};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Receiver side of the process-wide shutdown signal; flips to `true` once.
+pub type Shutdown = watch::Receiver<bool>;
+
+/// Runs a long-lived task produced by `factory` and restarts it if it panics
+/// or returns while the process is still running.
+///
+/// Restarts back off exponentially from 1s up to 60s; the delay resets once a
+/// run lasts longer than the maximum backoff. The factory receives its own
+/// [`Shutdown`] receiver and is expected to return promptly once it flips.
+/// When shutdown fires, the supervisor stops restarting, waits for the
+/// current run to finish and then exits.
+///
+/// ```rust,ignore
+/// let handle = spawn_supervised("cache_purger", shutdown, move |mut shutdown| {
+///   let cache = cache.clone();
+///   async move {
+///     loop {
+///       tokio::select! {
+///         _ = tokio::time::sleep(Duration::from_secs(60)) => { cache.purge_expired().await; }
+///         _ = shutdown.changed() => break,
+///       }
+///     }
+///   }
+/// });
+/// ```
+pub fn spawn_supervised<F, Fut>(
+  name: &'static str,
+  mut shutdown: Shutdown,
+  factory: F,
+) -> JoinHandle<()>
+where
+  F: Fn(Shutdown) -> Fut + Send + 'static,
+  Fut: Future<Output = ()> + Send + 'static,
+{
+  tokio::spawn(async move {
+    let mut backoff = INITIAL_BACKOFF;
+
+    while !*shutdown.borrow() {
+      let started = tokio::time::Instant::now();
+      let mut run = tokio::spawn(factory(shutdown.clone()));
+
+      let result = tokio::select! {
+        result = &mut run => result,
+        _ = shutdown.changed() => {
+          let _ = run.await;
+          break;
+        }
+      };
+
+      if *shutdown.borrow() {
+        break;
+      }
+
+      match result {
+        Ok(()) => tracing::warn!(task = name, "TASK_EXITED_UNEXPECTEDLY"),
+        Err(e) => tracing::error!(task = name, error = %e, "TASK_PANICKED"),
+      }
+
+      if started.elapsed() > MAX_BACKOFF {
+        backoff = INITIAL_BACKOFF;
+      }
+      tracing::info!(
+        task = name,
+        backoff_ms = backoff.as_millis() as u64,
+        "TASK_RESTARTING"
+      );
+
+      tokio::select! {
+        _ = tokio::time::sleep(backoff) => {},
+        _ = shutdown.changed() => break,
+      }
+      backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+
+    tracing::info!(task = name, "TASK_STOPPED");
+  })
+}
+
+/// Owns the shutdown signal and the handles of all supervised tasks so the
+/// server can stop them and wait for them during graceful shutdown.
+#[derive(Debug)]
+pub struct BackgroundTasks {
+  shutdown: Arc<watch::Sender<bool>>,
+  handles: Vec<JoinHandle<()>>,
+}
+
+impl Default for BackgroundTasks {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl BackgroundTasks {
+  pub fn new() -> Self {
+    let (shutdown, _) = watch::channel(false);
+    Self {
+      shutdown: Arc::new(shutdown),
+      handles: Vec::new(),
+    }
+  }
+
+  /// Returns a new receiver for the shutdown signal.
+  pub fn subscribe(&self) -> Shutdown {
+    self.shutdown.subscribe()
+  }
+
+  /// Starts `factory` under [`spawn_supervised`] and tracks its handle.
+  pub fn spawn<F, Fut>(
+    &mut self,
+    name: &'static str,
+    factory: F,
+  ) where
+    F: Fn(Shutdown) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+  {
+    let handle = spawn_supervised(name, self.subscribe(), factory);
+    self.handles.push(handle);
+  }
+
+  /// Returns a future that flips the shutdown signal once `signal` resolves.
+  ///
+  /// Meant to wrap the server's graceful-shutdown future so tasks start
+  /// winding down while in-flight requests drain.
+  pub fn trigger_on<S>(
+    &self,
+    signal: S,
+  ) -> impl Future<Output = ()> + Send + 'static
+  where
+    S: Future<Output = ()> + Send + 'static,
+  {
+    let shutdown = self.shutdown.clone();
+    async move {
+      signal.await;
+      let _ = shutdown.send(true);
+    }
+  }
+
+  /// Signals shutdown (if not already signalled) and waits for every task to finish.
+  pub async fn shutdown(self) {
+    let _ = self.shutdown.send(true);
+    for handle in self.handles {
+      let _ = handle.await;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[tokio::test(start_paused = true)]
+  async fn restarts_after_panic_and_stops_on_shutdown() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut tasks = BackgroundTasks::new();
+
+    let counter = runs.clone();
+    tasks.spawn("flaky", move |mut shutdown| {
+      let run = counter.fetch_add(1, Ordering::SeqCst);
+      async move {
+        if run == 0 {
+          panic!("first run fails");
+        }
+        let _ = shutdown.changed().await;
+      }
+    });
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    tasks.shutdown().await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+  }
+}
@@ -1,5 +1,8 @@
-use crate::services::HttpError;
-use chrono::{Duration, Utc};
+use crate::{
+  services::HttpError,
+  utils::clock::{Clock, SystemClock},
+};
+use chrono::Duration;
 use jsonwebtoken::errors::{Error, ErrorKind};
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
@@ -13,13 +16,22 @@ pub struct TokenClaims {
 pub fn create_token(
   data: String,
   secret: &[u8],
+) -> Result<String, Error> {
+  create_token_with_clock(data, secret, &SystemClock)
+}
+
+/// [`create_token`] with `iat` and `exp` taken from `clock`.
+pub fn create_token_with_clock(
+  data: String,
+  secret: &[u8],
+  clock: &dyn Clock,
 ) -> Result<String, Error> {
   // Validate input early
   if data.is_empty() {
     return Err(ErrorKind::InvalidSubject.into());
   }
 
-  let now = Utc::now();
+  let now = clock.utc_now();
   let claims = TokenClaims {
     sub: data,
     iat: now.timestamp() as usize,
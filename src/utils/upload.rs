@@ -0,0 +1,126 @@
+use anyhow::{Result, bail};
+use std::path::{Component, Path, PathBuf};
+
+/// Reduces a client-supplied filename to a single safe path component.
+///
+/// Both `/` and `\` are treated as separators so Windows-style paths are
+/// stripped on every platform; only the last component is kept. Rejects
+/// (with `INVALID_FILENAME`) names containing null bytes or any `..`
+/// component, and results that are empty, `.`, or contain `:` (drive-relative
+/// names, NTFS streams).
+///
+/// # Example
+///
+/// ```rust
+/// use axum_starter::utils::upload::sanitize_filename;
+///
+/// assert_eq!(sanitize_filename("C:\\Users\\me\\report.pdf").unwrap(), "report.pdf");
+/// assert!(sanitize_filename("../../etc/passwd").is_err());
+/// ```
+pub fn sanitize_filename(filename: &str) -> Result<&str> {
+  if filename.contains('\0') {
+    bail!("INVALID_FILENAME");
+  }
+
+  let mut parts = filename.split(['/', '\\']);
+  if parts.clone().any(|part| part == "..") {
+    bail!("INVALID_FILENAME");
+  }
+
+  match parts.next_back().map(str::trim) {
+    Some(name) if !name.is_empty() && name != "." && !name.contains(':') => Ok(name),
+    _ => bail!("INVALID_FILENAME"),
+  }
+}
+
+/// Joins a client-supplied `filename` onto `base`, guaranteeing the result is
+/// a direct child of `base`.
+///
+/// The filename is reduced with [`sanitize_filename`] first; the joined path
+/// is then checked component by component, so nothing can resolve outside
+/// `base` even before the file exists. Symlinks inside `base` are not
+/// followed here; keep upload directories free of them.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_starter::utils::upload::safe_path;
+/// use std::path::Path;
+///
+/// let path = safe_path(Path::new("public/uploads"), "/etc/passwd").unwrap();
+/// assert_eq!(path, Path::new("public/uploads/passwd"));
+/// ```
+pub fn safe_path(
+  base: &Path,
+  filename: &str,
+) -> Result<PathBuf> {
+  let name = sanitize_filename(filename)?;
+  let path = base.join(name);
+
+  let mut components = path.strip_prefix(base)?.components();
+  match (components.next(), components.next()) {
+    (Some(Component::Normal(_)), None) => Ok(path),
+    _ => bail!("INVALID_FILENAME"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const BASE: &str = "public/uploads";
+
+  fn resolve(filename: &str) -> Result<PathBuf> {
+    safe_path(Path::new(BASE), filename)
+  }
+
+  #[test]
+  fn keeps_plain_filenames() {
+    assert_eq!(
+      resolve("report.pdf").unwrap(),
+      Path::new(BASE).join("report.pdf")
+    );
+  }
+
+  #[test]
+  fn rejects_parent_traversal() {
+    assert!(resolve("../secret.txt").is_err());
+    assert!(resolve("../../etc/passwd").is_err());
+    assert!(resolve("a/../../b.txt").is_err());
+    assert!(resolve("..").is_err());
+  }
+
+  #[test]
+  fn strips_absolute_paths() {
+    assert_eq!(
+      resolve("/etc/passwd").unwrap(),
+      Path::new(BASE).join("passwd")
+    );
+  }
+
+  #[test]
+  fn rejects_null_bytes() {
+    assert!(resolve("report.pdf\0.png").is_err());
+  }
+
+  #[test]
+  fn handles_windows_separators() {
+    assert_eq!(
+      resolve("uploads\\docs\\report.pdf").unwrap(),
+      Path::new(BASE).join("report.pdf")
+    );
+    assert!(resolve("..\\..\\windows\\system32").is_err());
+    assert_eq!(
+      resolve("C:\\Windows\\win.ini").unwrap(),
+      Path::new(BASE).join("win.ini")
+    );
+    assert!(resolve("C:win.ini").is_err());
+  }
+
+  #[test]
+  fn rejects_empty_names() {
+    assert!(resolve("").is_err());
+    assert!(resolve("uploads/").is_err());
+    assert!(resolve(".").is_err());
+  }
+}
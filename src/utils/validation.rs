@@ -1,4 +1,23 @@
-use validator::ValidationErrors;
+use std::borrow::Cow;
+use validator::{ValidationError, ValidationErrors};
+
+/// Custom validator rejecting strings that are empty or only whitespace, which
+/// `required` and `length(min = ..)` let through (`"   "` has length 3):
+///
+/// ```rust,ignore
+/// #[validate(custom(function = "crate::utils::validation::non_blank"))]
+/// pub username: String,
+/// ```
+///
+/// Fails with code `non_blank`, reported by [`format_validation_errors`] as
+/// `username|non_blank|must not be blank|value="   "`; the body extractors
+/// answer it, like any validation failure, with `422` (`ERR034`).
+pub fn non_blank(value: &str) -> Result<(), ValidationError> {
+  if value.trim().is_empty() {
+    return Err(ValidationError::new("non_blank").with_message(Cow::Borrowed("must not be blank")));
+  }
+  Ok(())
+}
 
 pub fn format_validation_errors(errors: &ValidationErrors) -> String {
   let map_error = errors
@@ -42,3 +61,33 @@ pub fn format_validation_errors(errors: &ValidationErrors) -> String {
     map_error
   }
 }
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use validator::Validate;
+
+  #[derive(Validate)]
+  struct Named {
+    #[validate(custom(function = "non_blank"))]
+    name: String,
+  }
+
+  #[test]
+  fn non_blank_rejects_empty_and_whitespace() {
+    assert!(non_blank("").is_err());
+    assert!(non_blank(" \t\n").is_err());
+    assert!(non_blank(" ada ").is_ok());
+
+    let errors = Named {
+      name: "   ".to_string(),
+    }
+    .validate()
+    .unwrap_err();
+    assert_eq!(
+      format_validation_errors(&errors),
+      r#"name|non_blank|must not be blank|value="   ""#
+    );
+  }
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{ADMIN_TOKEN, TestApp};
+
+#[tokio::test]
+async fn backup_requires_admin_token() {
+  let app = TestApp::spawn().await;
+  let resp = app
+    .client
+    .post(format!("{}/admin/backup", app.address))
+    .header("x-admin-token", "wrong-token")
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 403);
+}
+
+#[tokio::test]
+async fn backup_writes_copy_and_returns_size() {
+  let app = TestApp::spawn().await;
+  let resp = app
+    .client
+    .post(format!("{}/admin/backup", app.address))
+    .header("x-admin-token", ADMIN_TOKEN)
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 201);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  let path = body["data"]["path"].as_str().unwrap();
+  assert!(path.starts_with(app.backup_dir.path().to_str().unwrap()));
+  let size = std::fs::metadata(path).unwrap().len();
+  assert!(size > 0);
+  assert_eq!(body["data"]["sizeBytes"], size);
+}
+
+#[tokio::test]
+async fn export_streams_users_as_csv() {
+  let app = TestApp::spawn().await;
+  app.register("ada@example.com", "ada", "Password123!").await;
+  app
+    .register("grace@example.com", "grace", "Password123!")
+    .await;
+
+  let resp = app
+    .client
+    .get(format!("{}/admin/export/users", app.address))
+    .header("x-admin-token", ADMIN_TOKEN)
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  assert_eq!(resp.headers()["content-type"], "text/csv; charset=utf-8");
+  assert!(resp.headers().get("content-length").is_none());
+  let csv = resp.text().await.unwrap();
+  let lines: Vec<&str> = csv.lines().collect();
+  assert_eq!(lines[0], "id,email,username,created_at");
+  assert_eq!(lines.len(), 3);
+  assert!(lines[1].contains("ada@example.com"));
+  assert!(!csv.contains("Password123!"));
+}
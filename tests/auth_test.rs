@@ -99,9 +99,19 @@ async fn refresh_with_invalid_token_returns_401() {
 }
 
 #[tokio::test]
-async fn register_short_password_returns_400() {
+async fn register_short_password_returns_422() {
   let app = TestApp::spawn().await;
 
   let resp = app.register(EMAIL, USERNAME, "short").await;
-  assert_eq!(resp.status(), 400);
+  assert_eq!(resp.status(), 422);
+}
+
+#[tokio::test]
+async fn register_blank_username_returns_422() {
+  let app = TestApp::spawn().await;
+
+  let resp = app.register(EMAIL, "     ", PASSWORD).await;
+  assert_eq!(resp.status(), 422);
+  let body = resp.text().await.unwrap();
+  assert!(body.contains("non_blank"), "{body}");
 }
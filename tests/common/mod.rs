@@ -1,24 +1,79 @@
+#![allow(dead_code)]
+
 use axum_starter::{
-  models::{AppEnv, AppState, Environment},
+  models::{AppState, Environment},
   modules::AppRoutes,
-  services::DBSqlite,
+  server::{AppServer, AppServerBuilder, ServerHandle},
+  services::{Cache, DBSqlite, Metrics, SessionStore},
+  utils::{Secret, runtime_config::RuntimeConfig, tasks::BackgroundTasks},
 };
 use diesel::RunQueryDsl;
 use std::sync::Arc;
-use tempfile::NamedTempFile;
+use tempfile::{NamedTempFile, TempDir};
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+/// `ADMIN_TOKEN` configured for every test app.
+pub const ADMIN_TOKEN: &str = "test-admin-token-for-integration-tests";
+
+/// Key accepted by the `ApiKey` extractor in every test app.
+pub const API_KEY: &str = "test-api-key-for-integration-tests";
+
+/// Webhook source configured for every test app, signed with [`WEBHOOK_SECRET`].
+pub const WEBHOOK_SOURCE: &str = "github";
+
+/// `WEBHOOK_SECRETS` entry for [`WEBHOOK_SOURCE`].
+pub const WEBHOOK_SECRET: &str = "test-webhook-secret-for-integration-tests";
 
 /// A running test server bound to an ephemeral port.
 pub struct TestApp {
   pub address: String,
   pub client: reqwest::Client,
+  /// Handle to the app's pool, e.g. for `assert_no_leaked_connections`
+  pub db: DBSqlite,
+  /// Directory `POST /admin/backup` writes to
+  pub backup_dir: TempDir,
+  /// Set by [`TestApp::spawn_server`]; `None` for the bare router
+  pub server: Option<ServerHandle>,
+  /// Publishes runtime config changes, as a `SIGHUP` reload would
+  pub runtime_config: watch::Sender<RuntimeConfig>,
   /// Keep the tempfile alive for the lifetime of TestApp (drops and deletes on test end)
   _db_file: NamedTempFile,
+  _analytics_db_file: Option<NamedTempFile>,
 }
 
 impl TestApp {
   /// Spin up a real server on a random port backed by a fresh SQLite temp file.
   pub async fn spawn() -> Self {
+    Self::spawn_inner(false, None::<fn(_) -> _>).await
+  }
+
+  /// Like [`TestApp::spawn`], with a second temp database as `analytics_db`.
+  pub async fn spawn_with_analytics_db() -> Self {
+    Self::spawn_inner(true, None::<fn(_) -> _>).await
+  }
+
+  /// Like [`TestApp::spawn`], but through [`AppServer::start`] on `PORT=0`,
+  /// so the full middleware stack and graceful shutdown are in play.
+  pub async fn spawn_server() -> Self {
+    Self::spawn_server_with(|server| server).await
+  }
+
+  /// Like [`TestApp::spawn_server`], with `configure` applied to the
+  /// [`AppServerBuilder`] first.
+  pub async fn spawn_server_with(
+    configure: impl FnOnce(AppServerBuilder) -> AppServerBuilder
+  ) -> Self {
+    Self::spawn_inner(false, Some(configure)).await
+  }
+
+  async fn spawn_inner<F>(
+    with_analytics_db: bool,
+    full_server: Option<F>,
+  ) -> Self
+  where
+    F: FnOnce(AppServerBuilder) -> AppServerBuilder,
+  {
     // Create a temporary SQLite file that is deleted when the test ends
     let db_file = NamedTempFile::new().expect("failed to create temp DB file");
     let db_path = db_file.path().to_str().unwrap().to_string();
@@ -26,36 +81,72 @@ impl TestApp {
     // Run migrations on the temp DB
     let db = DBSqlite::new(&db_path).expect("failed to create test DB pool");
     Self::run_migrations(&db).await;
+    let backup_dir = TempDir::new().expect("failed to create temp backup dir");
 
     let env = Environment {
-      mode: AppEnv::Local,
-      secret: "test-secret-key-for-integration-tests".to_string(),
-      port: 0, // not used — we bind via TcpListener directly
-      database_url: db_path,
-      timeout: 300,
+      port: 0, // OS-assigned; only read by `spawn_server`
+      database_url: Secret::new(db_path),
       cors_origins: vec!["http://localhost:3000".to_string()],
       log_dir: "/tmp".to_string(),
+      backup_dir: backup_dir.path().to_string_lossy().into_owned(),
+      admin_token: Some(Secret::new(ADMIN_TOKEN.to_string())),
+      api_keys: vec![Secret::new(API_KEY.to_string())],
+      webhook_secrets: vec![(
+        WEBHOOK_SOURCE.to_string(),
+        Secret::new(WEBHOOK_SECRET.to_string()),
+      )],
+      ..Environment::for_test()
     };
 
-    let app_state = Arc::new(AppState { env, db });
-
-    let router = AppRoutes::build(app_state.clone());
+    let analytics_db_file = with_analytics_db
+      .then(|| NamedTempFile::new().expect("failed to create temp analytics DB file"));
+    let analytics_db = analytics_db_file.as_ref().map(|file| {
+      DBSqlite::new(file.path().to_str().unwrap()).expect("failed to create analytics DB pool")
+    });
 
-    let listener = TcpListener::bind("127.0.0.1:0")
-      .await
-      .expect("failed to bind test listener");
-    let addr = listener.local_addr().expect("failed to get local address");
+    let runtime_config = watch::channel(RuntimeConfig::default()).0;
+    let sessions = SessionStore::isolated(env.session.clone());
+    let app_state = Arc::new(AppState {
+      env,
+      db: db.clone(),
+      analytics_db,
+      cache: Cache::default(),
+      sessions,
+      metrics: Metrics::default(),
+      runtime_config: runtime_config.clone(),
+    });
 
-    tokio::spawn(async move {
-      axum::serve(listener, router)
+    let (addr, server) = if let Some(configure) = full_server {
+      let server = configure(AppServer::builder(app_state, BackgroundTasks::new()))
+        .build()
+        .start()
         .await
-        .expect("test server failed");
-    });
+        .expect("failed to start test server");
+      let addr = server.local_addr.expect("test server listens on TCP");
+      (addr, Some(server))
+    } else {
+      let router = AppRoutes::build(app_state.clone()).expect("duplicate route registration");
+      let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind test listener");
+      let addr = listener.local_addr().expect("failed to get local address");
+      tokio::spawn(async move {
+        axum::serve(listener, router)
+          .await
+          .expect("test server failed");
+      });
+      (addr, None)
+    };
 
     TestApp {
       address: format!("http://127.0.0.1:{}", addr.port()),
       client: reqwest::Client::new(),
+      db,
+      backup_dir,
+      server,
+      runtime_config,
       _db_file: db_file,
+      _analytics_db_file: analytics_db_file,
     }
   }
 
@@ -75,6 +166,19 @@ impl TestApp {
       )
       .execute(conn)?;
 
+      // Create outbox_events table
+      diesel::sql_query(
+        "CREATE TABLE IF NOT EXISTS outbox_events (
+          id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+          event_type TEXT NOT NULL,
+          payload TEXT NOT NULL,
+          created_at TEXT NOT NULL,
+          published_at TEXT,
+          dedup_key TEXT NOT NULL UNIQUE
+        )",
+      )
+      .execute(conn)?;
+
       // Create refresh_tokens table
       diesel::sql_query(
         "CREATE TABLE IF NOT EXISTS refresh_tokens (
@@ -0,0 +1,20 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn openapi_json_lists_routes_and_error_envelope() {
+  let app = TestApp::spawn().await;
+  let resp = app
+    .client
+    .get(format!("{}/openapi.json", app.address))
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  let spec: serde_json::Value = resp.json().await.unwrap();
+  assert!(spec["paths"]["/health/live"].is_object());
+  assert!(spec["paths"]["/users/me"].is_object());
+  assert!(spec["components"]["schemas"]["HttpErrorFormat"].is_object());
+}
@@ -30,4 +30,72 @@ async fn readiness_returns_200_with_sqlite() {
   assert_eq!(resp.status(), 200);
   let body: serde_json::Value = resp.json().await.unwrap();
   assert_eq!(body["success"], true);
+  assert_eq!(body["message"], "READY");
+  assert_eq!(body["data"]["status"], "healthy");
+  assert_eq!(body["data"]["components"][0]["name"], "database");
+  let target = body["data"]["components"][0]["target"].as_str().unwrap();
+  assert!(target.starts_with("sqlite://"), "{target}");
+  // No ANALYTICS_DATABASE_URL, so no analytics component.
+  assert_eq!(body["data"]["components"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn readiness_includes_configured_analytics_db() {
+  let app = TestApp::spawn_with_analytics_db().await;
+  let resp = app
+    .client
+    .get(format!("{}/health/ready", app.address))
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["data"]["components"][1]["name"], "analytics_database");
+  assert_eq!(body["data"]["components"][1]["status"], "healthy");
+}
+
+#[tokio::test]
+async fn readiness_returns_connection_to_pool() {
+  let app = TestApp::spawn().await;
+  let baseline = app.db.pool_stats();
+  let resp = app
+    .client
+    .get(format!("{}/health/ready", app.address))
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  app.db.assert_no_leaked_connections(baseline).await;
+}
+
+#[tokio::test]
+async fn cors_allows_only_configured_origins() {
+  let app = TestApp::spawn().await;
+  let preflight = |origin: &'static str| {
+    app
+      .client
+      .request(
+        reqwest::Method::OPTIONS,
+        format!("{}/health/live", app.address),
+      )
+      .header("origin", origin)
+      .header("access-control-request-method", "GET")
+      .send()
+  };
+
+  let allowed = preflight("http://localhost:3000").await.unwrap();
+  assert_eq!(
+    allowed.headers()["access-control-allow-origin"],
+    "http://localhost:3000"
+  );
+
+  let denied = preflight("https://evil.example").await.unwrap();
+  assert!(
+    denied
+      .headers()
+      .get("access-control-allow-origin")
+      .is_none()
+  );
 }
@@ -0,0 +1,42 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn root_returns_service_info() {
+  let app = TestApp::spawn().await;
+  let resp = app
+    .client
+    .get(format!("{}/", app.address))
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["success"], true);
+  assert_eq!(body["data"]["name"], env!("CARGO_PKG_NAME"));
+  assert_eq!(body["data"]["version"], env!("CARGO_PKG_VERSION"));
+  assert_eq!(body["data"]["env"], "local");
+  assert_eq!(body["data"]["docs"], "/docs");
+}
+
+#[tokio::test]
+async fn root_serves_landing_page_to_browsers() {
+  let app = TestApp::spawn().await;
+  let resp = app
+    .client
+    .get(format!("{}/", app.address))
+    .header("accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+    .send()
+    .await
+    .expect("request failed");
+
+  assert_eq!(resp.status(), 200);
+  assert!(
+    resp.headers()["content-type"]
+      .to_str()
+      .unwrap()
+      .starts_with("text/html")
+  );
+}
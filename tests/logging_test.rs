@@ -0,0 +1,31 @@
+//! Logging installs a process-wide subscriber, so it is exercised in its own
+//! test binary instead of alongside other tests.
+
+use axum_starter::{
+  config,
+  models::{AppEnv, Environment},
+};
+
+#[test]
+fn init_logging_is_idempotent() {
+  let log_dir = tempfile::TempDir::new().unwrap();
+  let env = Environment {
+    mode: AppEnv::Production,
+    log_dir: log_dir.path().to_str().unwrap().to_string(),
+    log_filter: Some("off".to_string()),
+    ..Environment::for_test()
+  };
+
+  let first = config::init_logging(&env).expect("production installs a file writer");
+  // A second call, even for another mode, hands back the first setup.
+  let again = config::init_logging(&Environment {
+    mode: AppEnv::Local,
+    ..env
+  })
+  .expect("the first guard is kept");
+  assert!(std::ptr::eq(first, again));
+  // The first call owns the global default, so nothing else was installed.
+  assert!(
+    tracing::subscriber::set_global_default(tracing::subscriber::NoSubscriber::default()).is_err()
+  );
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn serves_on_an_os_assigned_port_until_shut_down() {
+  let mut app = TestApp::spawn_server().await;
+  let server = app.server.take().unwrap();
+  assert_ne!(server.local_addr.unwrap().port(), 0);
+
+  let resp = app
+    .client
+    .get(format!("{}/health/live", app.address))
+    .send()
+    .await
+    .expect("request failed");
+  assert_eq!(resp.status(), 200);
+  // The full stack runs, so the request id layer answers too
+  assert!(resp.headers().contains_key("x-request-id"));
+
+  server.shutdown().await.expect("server failed");
+  let refused = reqwest::Client::new()
+    .get(format!("{}/health/live", app.address))
+    .send()
+    .await;
+  assert!(refused.is_err());
+}
+
+#[tokio::test]
+async fn builder_overrides_layers_and_shutdown_signal() {
+  use axum::{http::HeaderValue, middleware::map_response, response::Response};
+  use axum_starter::server::ServerLayer;
+
+  let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+  let mut app = TestApp::spawn_server_with(|server| {
+    server
+      .without(ServerLayer::SecurityHeaders)
+      .layer(map_response(|mut res: Response| async move {
+        res
+          .headers_mut()
+          .insert("x-embedded", HeaderValue::from_static("yes"));
+        res
+      }))
+      .shutdown_signal(async move {
+        let _ = stopped.await;
+      })
+  })
+  .await;
+  let server = app.server.take().unwrap();
+
+  let resp = app
+    .client
+    .get(format!("{}/health/live", app.address))
+    .send()
+    .await
+    .expect("request failed");
+  assert_eq!(resp.status(), 200);
+  assert_eq!(resp.headers()["x-embedded"], "yes");
+  assert!(!resp.headers().contains_key("x-content-type-options"));
+
+  stop.send(()).unwrap();
+  server.wait().await.expect("server failed");
+  let refused = reqwest::Client::new()
+    .get(format!("{}/health/live", app.address))
+    .send()
+    .await;
+  assert!(refused.is_err());
+}
+
+#[tokio::test]
+async fn builder_layers_run_inside_the_request_id_layer() {
+  use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::{Next, from_fn},
+  };
+
+  let mut app = TestApp::spawn_server_with(|server| {
+    server.layer(from_fn(|req: Request, next: Next| async move {
+      let seen = if req.headers().contains_key("x-request-id") {
+        "yes"
+      } else {
+        "no"
+      };
+      let mut res = next.run(req).await;
+      res
+        .headers_mut()
+        .insert("x-saw-request-id", HeaderValue::from_static(seen));
+      res
+    }))
+  })
+  .await;
+
+  let resp = app
+    .client
+    .get(format!("{}/health/live", app.address))
+    .send()
+    .await
+    .expect("request failed");
+  assert_eq!(resp.status(), 200);
+  assert_eq!(resp.headers()["x-saw-request-id"], "yes");
+
+  app.server.take().unwrap().shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn preflights_skip_the_rate_limit() {
+  use axum_starter::utils::runtime_config::{RateLimitConfig, RuntimeConfig};
+
+  let app = TestApp::spawn_server().await;
+  app.runtime_config.send_replace(RuntimeConfig {
+    rate_limit: Some(RateLimitConfig {
+      requests: 1,
+      window_secs: 60,
+    }),
+    ..Default::default()
+  });
+  let get = || {
+    app
+      .client
+      .get(format!("{}/health/live", app.address))
+      .send()
+  };
+  // The limiter picks the budget up from its follower task
+  let mut throttled = false;
+  for _ in 0..100 {
+    if get().await.expect("request failed").status() == 429 {
+      throttled = true;
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+  }
+  assert!(throttled, "rate limit never applied");
+
+  let preflight = app
+    .client
+    .request(
+      reqwest::Method::OPTIONS,
+      format!("{}/health/live", app.address),
+    )
+    .header("origin", "http://localhost:3000")
+    .header("access-control-request-method", "GET")
+    .send()
+    .await
+    .expect("request failed");
+  assert_eq!(preflight.status(), 204);
+  assert_eq!(get().await.expect("request failed").status(), 429);
+}
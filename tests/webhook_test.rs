@@ -0,0 +1,119 @@
+mod common;
+
+use axum_starter::utils::hmac;
+use common::{API_KEY, TestApp, WEBHOOK_SECRET, WEBHOOK_SOURCE};
+use diesel::RunQueryDsl;
+
+const PAYLOAD: &str = r#"{"action":"opened","number":7}"#;
+
+async fn post_webhook(
+  app: &TestApp,
+  source: &str,
+  signature: &str,
+) -> reqwest::Response {
+  app
+    .client
+    .post(format!("{}/webhooks/{}", app.address, source))
+    .header("content-type", "application/json")
+    .header("x-signature-256", signature)
+    .body(PAYLOAD)
+    .send()
+    .await
+    .expect("request failed")
+}
+
+fn signature() -> String {
+  format!(
+    "sha256={}",
+    hmac::to_hex(&hmac::sign(WEBHOOK_SECRET.as_bytes(), PAYLOAD.as_bytes()))
+  )
+}
+
+#[derive(diesel::QueryableByName)]
+struct Event {
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  event_type: String,
+  #[diesel(sql_type = diesel::sql_types::Text)]
+  payload: String,
+}
+
+#[tokio::test]
+async fn signed_webhook_is_recorded_in_outbox() {
+  let app = TestApp::spawn().await;
+  let resp = post_webhook(&app, WEBHOOK_SOURCE, &signature()).await;
+
+  assert_eq!(resp.status(), 202);
+  let location = resp.headers()["location"].to_str().unwrap().to_string();
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["data"]["eventType"], "webhook.github");
+  assert_eq!(
+    location,
+    format!("/webhooks/events/{}", body["data"]["eventId"])
+  );
+
+  let events: Vec<Event> = app
+    .db
+    .execute(|conn| {
+      Ok(diesel::sql_query("SELECT event_type, payload FROM outbox_events").load(conn)?)
+    })
+    .await
+    .unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0].event_type, "webhook.github");
+  // Stored as received, not re-serialized
+  assert_eq!(events[0].payload, PAYLOAD);
+}
+
+#[tokio::test]
+async fn wrong_signature_is_rejected() {
+  let app = TestApp::spawn().await;
+  let forged = format!(
+    "sha256={}",
+    hmac::to_hex(&hmac::sign(b"guess", PAYLOAD.as_bytes()))
+  );
+
+  assert_eq!(
+    post_webhook(&app, WEBHOOK_SOURCE, &forged).await.status(),
+    401
+  );
+  assert_eq!(
+    post_webhook(&app, "stripe", &signature()).await.status(),
+    404
+  );
+}
+
+#[tokio::test]
+async fn status_url_reports_the_event_as_pending() {
+  let app = TestApp::spawn().await;
+  let resp = post_webhook(&app, WEBHOOK_SOURCE, &signature()).await;
+  let status_url = format!(
+    "{}{}",
+    app.address,
+    resp.headers()["location"].to_str().unwrap()
+  );
+
+  let unauthenticated = app.client.get(&status_url).send().await.unwrap();
+  assert_eq!(unauthenticated.status(), 401);
+
+  let status = app
+    .client
+    .get(&status_url)
+    .header("x-api-key", API_KEY)
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(status.status(), 200);
+  let body: serde_json::Value = status.json().await.unwrap();
+  assert_eq!(body["data"]["state"], "pending");
+  assert_eq!(body["data"]["eventType"], "webhook.github");
+  assert!(body["data"]["publishedAt"].is_null());
+
+  let missing = app
+    .client
+    .get(format!("{}/webhooks/events/999", app.address))
+    .header("x-api-key", API_KEY)
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(missing.status(), 404);
+}